@@ -0,0 +1,56 @@
+use memmap2::MmapMut;
+
+use crate::Result;
+
+/// Hint passed to `madvise` about how the mapped data file will be read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Advice {
+    /// No particular pattern, the kernel default.
+    #[default]
+    Normal,
+    /// Reads jump around, readahead is mostly wasted.
+    Random,
+    /// Reads go front to back, aggressive readahead pays off.
+    Sequential,
+    /// The range will be read soon, page it in ahead of time.
+    WillNeed,
+    /// The range won't be read soon, its cached pages can be dropped.
+    DontNeed,
+}
+
+impl Advice {
+    /// Whether it describes how the whole mapping is accessed, rather than being a one-off
+    /// request, and so should outlive remaps.
+    #[inline]
+    pub(crate) fn is_pattern(self) -> bool {
+        matches!(self, Self::Normal | Self::Random | Self::Sequential)
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn apply(self, mmap: &MmapMut, offset: usize, len: usize) -> Result<()> {
+        use memmap2::{Advice as MmapAdvice, UncheckedAdvice};
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        match self {
+            Self::Normal => mmap.advise_range(MmapAdvice::Normal, offset, len)?,
+            Self::Random => mmap.advise_range(MmapAdvice::Random, offset, len)?,
+            Self::Sequential => mmap.advise_range(MmapAdvice::Sequential, offset, len)?,
+            Self::WillNeed => mmap.advise_range(MmapAdvice::WillNeed, offset, len)?,
+            // The mapping is shared (or private but never written to when read only), so
+            // dropped pages are simply read back from the file
+            Self::DontNeed => unsafe {
+                mmap.unchecked_advise_range(UncheckedAdvice::DontNeed, offset, len)?
+            },
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn apply(self, _mmap: &MmapMut, _offset: usize, _len: usize) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use log::{info, warn};
+
+use crate::{Database, Error, Region, Result, checksum::crc32, os};
+
+/// File a committed batch is journaled to until it's fully applied and flushed.
+const JOURNAL: &str = "batch";
+
+#[derive(Debug)]
+enum Op {
+    Write {
+        region: Region,
+        data: Vec<u8>,
+        at: Option<u64>,
+        truncate: bool,
+    },
+    Truncate {
+        region: Region,
+        from: u64,
+    },
+    Remove(String),
+}
+
+/// Writes spanning several regions, buffered until applied together.
+///
+/// Nothing reaches the memory map before `commit` or `apply`, so dropping a batch leaves every
+/// region as it was. Operations are replayed in order and fail like their `Database`
+/// counterparts would at that point. Reads don't see buffered writes.
+///
+/// Committing is atomic: the operations are written to a journal before being applied, and
+/// a crash before the journal is durable leaves every region as it was, while a crash after
+/// it has the whole batch replayed on the next open.
+#[must_use = "a batch does nothing unless committed or applied"]
+#[derive(Debug)]
+pub struct Batch {
+    db: Database,
+    ops: Vec<Op>,
+}
+
+impl Batch {
+    pub(crate) fn new(db: Database) -> Self {
+        Self { db, ops: vec![] }
+    }
+
+    pub fn write_all_to_region(&mut self, region: &Region, data: &[u8]) {
+        self.push_write(region, data, None, false);
+    }
+
+    pub fn write_all_to_region_at(&mut self, region: &Region, data: &[u8], at: u64) {
+        self.push_write(region, data, Some(at), false);
+    }
+
+    pub fn truncate_write_all_to_region(&mut self, region: &Region, at: u64, data: &[u8]) {
+        self.push_write(region, data, Some(at), true);
+    }
+
+    pub fn truncate_region(&mut self, region: &Region, from: u64) {
+        self.ops.push(Op::Truncate {
+            region: region.clone(),
+            from,
+        });
+    }
+
+    pub fn remove_region_with_id(&mut self, id: &str) {
+        self.ops.push(Op::Remove(id.to_string()));
+    }
+
+    fn push_write(&mut self, region: &Region, data: &[u8], at: Option<u64>, truncate: bool) {
+        self.ops.push(Op::Write {
+            region: region.clone(),
+            data: data.to_vec(),
+            at,
+            truncate,
+        });
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Applies every operation and makes them durable together.
+    ///
+    /// Operations are validated against the regions' lengths first, so that one failing
+    /// leaves everything untouched. The database is then flushed, the operations journaled
+    /// and synced, applied, and flushed again before the journal is removed. Should applying
+    /// fail midway, the batch is completed on the next open.
+    pub fn commit(self) -> Result<()> {
+        let Self { db, ops } = self;
+        db.check_writable()?;
+
+        if ops.is_empty() {
+            return db.flush_durable();
+        }
+
+        let entries = Entry::resolve(ops)?;
+
+        // The journal is replayed on top of the state it was written on, so it must be durable
+        db.flush_durable()?;
+
+        let path = db.journal_path();
+        let mut bytes = vec![];
+        entries.iter().for_each(|entry| entry.encode(&mut bytes));
+        bytes.extend(crc32(0, &bytes).to_le_bytes());
+        let mut file = File::create(&path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        os::sync_dir(db.path())?;
+
+        db.replay(entries)?;
+        db.flush_durable()?;
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    /// Applies every operation without flushing, like writing them one by one.
+    pub fn apply(self) -> Result<()> {
+        let Self { db, ops } = self;
+
+        for op in ops {
+            match op {
+                Op::Write {
+                    region,
+                    data,
+                    at,
+                    truncate,
+                } => db.write_all_to_region_at_(&region, &data, at, truncate)?,
+                Op::Truncate { region, from } => db.truncate_region(&region, from)?,
+                Op::Remove(id) => {
+                    db.remove_region_with_id(&id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Journaled operation, resolved against the lengths its region will have when replayed so
+/// that replaying it over a batch that was already partly or fully applied is harmless.
+#[derive(Debug)]
+enum Entry {
+    /// Length of a region before the batch, restored before replaying its operations
+    Len {
+        id: String,
+        len: u64,
+    },
+    Write {
+        id: String,
+        data: Vec<u8>,
+        at: u64,
+        truncate: bool,
+    },
+    Truncate {
+        id: String,
+        from: u64,
+    },
+    Remove(String),
+}
+
+impl Entry {
+    fn resolve(ops: Vec<Op>) -> Result<Vec<Self>> {
+        let mut lens = HashMap::new();
+        let mut entries = vec![];
+
+        for op in ops {
+            match op {
+                Op::Write {
+                    region,
+                    data,
+                    at,
+                    truncate,
+                } => {
+                    let (id, len) = Self::len_of(&mut lens, &mut entries, &region);
+                    let at = at.unwrap_or(*len);
+                    if at > *len {
+                        return Err(Error::WriteOutOfBounds {
+                            position: at,
+                            region_len: *len,
+                        });
+                    }
+                    let end = at + data.len() as u64;
+                    *len = if truncate { end } else { end.max(*len) };
+                    entries.push(Self::Write {
+                        id,
+                        data,
+                        at,
+                        truncate,
+                    });
+                }
+                Op::Truncate { region, from } => {
+                    let (id, len) = Self::len_of(&mut lens, &mut entries, &region);
+                    if from > *len {
+                        return Err(Error::TruncateInvalid {
+                            from,
+                            current_len: *len,
+                        });
+                    }
+                    *len = from;
+                    entries.push(Self::Truncate { id, from });
+                }
+                Op::Remove(id) => entries.push(Self::Remove(id)),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Id and length of `region` as of the operations resolved so far, recording its length
+    /// before the batch the first time it's seen.
+    fn len_of<'a>(
+        lens: &'a mut HashMap<usize, u64>,
+        entries: &mut Vec<Self>,
+        region: &Region,
+    ) -> (String, &'a mut u64) {
+        let region_meta = region.meta().read();
+        let id = region_meta.id().to_string();
+        let len = lens.entry(region.index()).or_insert_with(|| {
+            entries.push(Self::Len {
+                id: id.clone(),
+                len: region_meta.len(),
+            });
+            region_meta.len()
+        });
+        (id, len)
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        let (tag, id) = match self {
+            Self::Len { id, .. } => (0, id),
+            Self::Write { id, .. } => (1, id),
+            Self::Truncate { id, .. } => (2, id),
+            Self::Remove(id) => (3, id),
+        };
+        bytes.push(tag);
+        bytes.extend((id.len() as u64).to_le_bytes());
+        bytes.extend(id.as_bytes());
+
+        match self {
+            Self::Len { len, .. } => bytes.extend(len.to_le_bytes()),
+            Self::Write {
+                data, at, truncate, ..
+            } => {
+                bytes.extend(at.to_le_bytes());
+                bytes.push(*truncate as u8);
+                bytes.extend((data.len() as u64).to_le_bytes());
+                bytes.extend(data);
+            }
+            Self::Truncate { from, .. } => bytes.extend(from.to_le_bytes()),
+            Self::Remove(_) => {}
+        }
+    }
+
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+            let (taken, rest) = bytes.split_at_checked(len)?;
+            *bytes = rest;
+            Some(taken)
+        }
+        fn take_u64(bytes: &mut &[u8]) -> Option<u64> {
+            take(bytes, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        }
+
+        let tag = take(bytes, 1)?[0];
+        let id_len = take_u64(bytes)? as usize;
+        let id = String::from_utf8(take(bytes, id_len)?.to_vec()).ok()?;
+
+        Some(match tag {
+            0 => Self::Len {
+                id,
+                len: take_u64(bytes)?,
+            },
+            1 => {
+                let at = take_u64(bytes)?;
+                let truncate = take(bytes, 1)?[0] != 0;
+                let data_len = take_u64(bytes)? as usize;
+                Self::Write {
+                    id,
+                    data: take(bytes, data_len)?.to_vec(),
+                    at,
+                    truncate,
+                }
+            }
+            2 => Self::Truncate {
+                id,
+                from: take_u64(bytes)?,
+            },
+            3 => Self::Remove(id),
+            _ => return None,
+        })
+    }
+}
+
+impl Database {
+    #[inline]
+    fn journal_path(&self) -> PathBuf {
+        self.path().join(JOURNAL)
+    }
+
+    /// Completes a batch whose commit was interrupted, called when opening the database.
+    ///
+    /// A journal whose checksum doesn't match was torn while being written, before anything
+    /// was applied, so it's discarded.
+    pub(crate) fn replay_journal(&self) -> Result<()> {
+        let path = self.journal_path();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let valid = bytes.len() >= 4 && {
+            let (body, checksum) = bytes.split_at(bytes.len() - 4);
+            crc32(0, body).to_le_bytes() == checksum
+        };
+        if !valid {
+            warn!("Discarding torn batch journal");
+            fs::remove_file(path)?;
+            return Ok(());
+        }
+
+        let mut body = &bytes[..bytes.len() - 4];
+        let mut entries = vec![];
+        while !body.is_empty() {
+            entries.push(Entry::decode(&mut body).ok_or_else(|| {
+                Error::InvalidJournal(format!("unreadable entry in {}", path.display()))
+            })?);
+        }
+
+        info!("Replaying {} batch journal entries.", entries.len());
+        self.replay(entries)?;
+        self.flush_durable()?;
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn replay(&self, entries: Vec<Entry>) -> Result<()> {
+        for entry in entries {
+            match entry {
+                Entry::Len { id, len } => {
+                    // Removed by the batch if it's missing
+                    let Some(region) = self.get_region(&id) else {
+                        continue;
+                    };
+                    let mut region_meta = region.meta().write();
+                    if len > region_meta.reserved() {
+                        return Err(Error::InvalidJournal(format!(
+                            "region {id} had {len} bytes, more than it reserves now"
+                        )));
+                    } else if len != region_meta.len() {
+                        region_meta.set_len(len);
+                        region_meta.mark_checksum_stale();
+                    }
+                }
+                Entry::Write {
+                    id,
+                    data,
+                    at,
+                    truncate,
+                } => {
+                    if let Some(region) = self.get_region(&id) {
+                        self.write_all_to_region_at_(&region, &data, Some(at), truncate)?;
+                    }
+                }
+                Entry::Truncate { id, from } => {
+                    if let Some(region) = self.get_region(&id) {
+                        self.truncate_region(&region, from)?;
+                    }
+                }
+                Entry::Remove(id) => {
+                    self.remove_region_with_id(&id)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{Database, Error, Layout, MAX_REGION_ID_LEN, PAGE_SIZE, Result};
+
+/// Placement of a region within the data file, as stored in a catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub start: u64,
+    pub len: u64,
+    pub reserved: u64,
+}
+
+/// JSON export and import of the region catalog.
+///
+/// A catalog only describes where regions live in the data file, it doesn't contain any data.
+impl Database {
+    /// Serializes every region's id, start, len and reserved to JSON, in layout order.
+    pub fn export_catalog(&self) -> Result<String> {
+        let entries = self
+            .iter_regions()
+            .map(|(start, region)| {
+                let meta = region.meta().read();
+                CatalogEntry {
+                    id: meta.id().to_owned(),
+                    start,
+                    len: meta.len(),
+                    reserved: meta.reserved(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::to_string_pretty(&entries).map_err(|e| Error::InvalidCatalog(e.to_string()))
+    }
+
+    /// Recreates the regions described by a catalog from `export_catalog`.
+    ///
+    /// Meant to rebuild a lost or corrupted regions metadata file on top of an intact data
+    /// file, so the database must not have any region yet. Every entry is validated against
+    /// the data file before anything is created.
+    pub fn import_catalog(&self, catalog: &str) -> Result<()> {
+        self.check_writable()?;
+
+        let mut entries = serde_json::from_str::<Vec<CatalogEntry>>(catalog)
+            .map_err(|e| Error::InvalidCatalog(e.to_string()))?;
+        entries.sort_unstable_by_key(|entry| entry.start);
+
+        let invalid = |reason: String| Err(Error::InvalidCatalog(reason));
+
+        let file_len = self.file_len()?;
+        let mut prev_end = 0;
+        let mut ids = HashSet::with_capacity(entries.len());
+        for entry in entries.iter() {
+            let CatalogEntry {
+                id,
+                start,
+                len,
+                reserved,
+            } = entry;
+            if id.is_empty() {
+                return invalid("empty region id".to_owned());
+            } else if id.len() > MAX_REGION_ID_LEN {
+                return invalid(format!(
+                    "region id {id} is longer than {MAX_REGION_ID_LEN} bytes"
+                ));
+            } else if !start.is_multiple_of(PAGE_SIZE) || !reserved.is_multiple_of(PAGE_SIZE) {
+                return invalid(format!("region {id} isn't page aligned"));
+            } else if *reserved == 0 || len > reserved {
+                return invalid(format!("region {id} has len {len} but reserved {reserved}"));
+            } else if *start < prev_end {
+                return invalid(format!("region {id} at {start} overlaps the previous one"));
+            } else if start + reserved > file_len {
+                return invalid(format!(
+                    "region {id} ends at {} past the data file length {file_len}",
+                    start + reserved
+                ));
+            } else if !ids.insert(id) {
+                return invalid(format!("region {id} is listed more than once"));
+            }
+            prev_end = start + reserved;
+        }
+
+        let mut regions = self.regions.write();
+        let mut layout = self.layout.write();
+
+        if regions.index_to_region().iter().flatten().next().is_some() {
+            return invalid("database already has regions".to_owned());
+        }
+
+        let created = entries
+            .into_iter()
+            .map(|entry| {
+                let region = regions.create_region(self, entry.id, entry.start, entry.reserved)?;
+                let mut meta = region.meta().write();
+                meta.set_len(entry.len);
+                meta.set_checksum(self.region_checksum(entry.start, entry.len));
+                drop(meta);
+                Ok(region)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        *layout = Layout::from(&*regions);
+        drop(layout);
+
+        regions.flush()?;
+        drop(regions);
+
+        if let Some(observer) = self.observer.as_deref() {
+            created
+                .iter()
+                .for_each(|region| observer.on_create(region.meta().read().id(), region.index()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::Region;
+
+/// `Read` + `Seek` over a region's data, returned by `Region::reader_cursor`.
+///
+/// Every read copies from the memory map without holding any lock in between, so the length
+/// is the region's current one, not the one at creation. Seeking past the end is allowed,
+/// reads then return 0 bytes.
+#[derive(Debug, Clone)]
+pub struct RegionCursor {
+    region: Region,
+    pos: u64,
+}
+
+impl RegionCursor {
+    pub(crate) fn new(region: Region) -> Self {
+        Self { region, pos: 0 }
+    }
+
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    #[inline]
+    pub fn region(&self) -> &Region {
+        &self.region
+    }
+}
+
+impl Read for RegionCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self
+            .region
+            .read_into(self.pos, buf)
+            .map_err(io::Error::other)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for RegionCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(pos) => {
+                self.pos = pos;
+                return Ok(pos);
+            }
+            SeekFrom::End(offset) => (self.region.meta().read().len(), offset),
+            SeekFrom::Current(offset) => (self.pos, offset),
+        };
+
+        self.pos = base.checked_add_signed(offset).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
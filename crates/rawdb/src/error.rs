@@ -1,6 +1,8 @@
 use std::{
     fmt::{self, Debug, Display},
-    fs, io, result,
+    fs, io,
+    path::PathBuf,
+    result,
 };
 
 pub type Result<T, E = Error> = result::Result<T, E>;
@@ -10,6 +12,11 @@ pub type Result<T, E = Error> = result::Result<T, E>;
 pub enum Error {
     IO(io::Error),
     TryLock(fs::TryLockError),
+    DatabaseDropped,
+    LockTimeout {
+        path: PathBuf,
+    },
+    ReadOnly,
 
     // Region errors
     RegionNotFound,
@@ -23,6 +30,10 @@ pub enum Error {
         position: u64,
         region_len: u64,
     },
+    WriteBeyondFile {
+        end: u64,
+        file_len: u64,
+    },
 
     // Truncate errors
     TruncateInvalid {
@@ -30,8 +41,18 @@ pub enum Error {
         current_len: u64,
     },
 
+    // Reserve errors
+    ReserveShrink {
+        reserved: u64,
+        requested: u64,
+    },
+
     // Metadata errors
     InvalidRegionId,
+    RegionIdTooLong {
+        len: usize,
+        max: usize,
+    },
     InvalidMetadataSize {
         expected: usize,
         actual: usize,
@@ -40,6 +61,13 @@ pub enum Error {
 
     // Layout errors
     RegionIndexMismatch,
+    RangeNotFree {
+        start: u64,
+        reserved: u64,
+    },
+    InvalidLayout(String),
+    InvalidCatalog(String),
+    InvalidJournal(String),
 
     // Hole punching errors
     HolePunchFailed {
@@ -66,6 +94,13 @@ impl fmt::Display for Error {
         match self {
             Error::IO(error) => Display::fmt(&error, f),
             Error::TryLock(_) => write!(f, "Database is locked by another process"),
+            Error::DatabaseDropped => {
+                write!(f, "Database was dropped while the region still exists")
+            }
+            Error::LockTimeout { path } => {
+                write!(f, "Timed out waiting for the lock on {}", path.display())
+            }
+            Error::ReadOnly => write!(f, "Database was opened read-only"),
 
             Error::RegionNotFound => write!(f, "Region not found"),
             Error::RegionAlreadyExists => write!(f, "Region already exists"),
@@ -84,13 +119,33 @@ impl fmt::Display for Error {
                 position, region_len
             ),
 
+            Error::WriteBeyondFile { end, file_len } => write!(
+                f,
+                "Write ending at {} is beyond the data file length {}",
+                end, file_len
+            ),
+
             Error::TruncateInvalid { from, current_len } => write!(
                 f,
                 "Cannot truncate to {} bytes (current length: {})",
                 from, current_len
             ),
 
+            Error::ReserveShrink {
+                reserved,
+                requested,
+            } => write!(
+                f,
+                "Cannot reserve {} bytes, below the current reservation of {}",
+                requested, reserved
+            ),
+
             Error::InvalidRegionId => write!(f, "Invalid region ID"),
+            Error::RegionIdTooLong { len, max } => write!(
+                f,
+                "Region ID of {} bytes is longer than the maximum of {}",
+                len, max
+            ),
             Error::InvalidMetadataSize { expected, actual } => write!(
                 f,
                 "Invalid metadata size: expected {} bytes, got {}",
@@ -99,6 +154,14 @@ impl fmt::Display for Error {
             Error::EmptyMetadata => write!(f, "Empty region metadata"),
 
             Error::RegionIndexMismatch => write!(f, "Region index mismatch in layout"),
+            Error::InvalidLayout(reason) => write!(f, "Invalid layout: {}", reason),
+            Error::InvalidCatalog(reason) => write!(f, "Invalid catalog: {}", reason),
+            Error::InvalidJournal(reason) => write!(f, "Invalid batch journal: {}", reason),
+            Error::RangeNotFree { start, reserved } => write!(
+                f,
+                "Range at offset {} (length {}) is not a free hole",
+                start, reserved
+            ),
 
             Error::HolePunchFailed { start, len, source } => write!(
                 f,
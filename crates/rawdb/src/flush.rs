@@ -0,0 +1,28 @@
+use std::{io, thread::JoinHandle};
+
+use crate::{Error, Result};
+
+/// Data flush running in the background, returned by `Database::flush_in_background`.
+///
+/// Dropping it without calling `wait` lets the flush finish on its own, unobserved, with the
+/// database kept open until then.
+#[must_use = "call `wait` to know whether the flush succeeded"]
+#[derive(Debug)]
+pub struct FlushHandle(JoinHandle<Result<()>>);
+
+impl FlushHandle {
+    pub(crate) fn new(handle: JoinHandle<Result<()>>) -> Self {
+        Self(handle)
+    }
+
+    /// Blocks until the data and region metadata are flushed.
+    pub fn wait(self) -> Result<()> {
+        self.0
+            .join()
+            .map_err(|_| Error::IO(io::Error::other("Background flush panicked")))?
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
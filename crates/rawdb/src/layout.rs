@@ -1,6 +1,6 @@
-use std::{collections::BTreeMap, mem};
+use std::{collections::BTreeMap, mem, sync::Arc};
 
-use crate::{Error, Result};
+use crate::{Error, HoleFit, PAGE_SIZE, Result};
 
 use super::{Region, Regions};
 
@@ -55,25 +55,48 @@ impl Layout {
         &self.start_to_hole
     }
 
+    /// Start and size of the biggest reusable hole, the earliest one on ties.
+    ///
+    /// Pending holes aren't reusable until the next flush so they're left out.
+    pub fn largest_hole(&self) -> Option<(u64, u64)> {
+        self.start_to_hole
+            .iter()
+            .max_by(|(start_a, gap_a), (start_b, gap_b)| {
+                gap_a.cmp(gap_b).then(start_b.cmp(start_a))
+            })
+            .map(|(start, gap)| (*start, *gap))
+    }
+
+    /// Number of reusable holes per size class, keyed by the class' smallest size in bytes.
+    ///
+    /// Classes double in size: a page, 2 pages, 4 pages and so on.
+    pub fn hole_histogram(&self) -> BTreeMap<u64, usize> {
+        let mut histogram = BTreeMap::new();
+        self.start_to_hole.values().for_each(|&gap| {
+            let pages = (gap / PAGE_SIZE).max(1);
+            let class = PAGE_SIZE << pages.ilog2();
+            *histogram.entry(class).or_default() += 1;
+        });
+        histogram
+    }
+
     pub fn len(&self) -> u64 {
-        let mut len = 0;
-        let mut start = 0;
-        if let Some((start_reserved, reserved)) = self.get_last_reserved() {
-            start = start_reserved;
-            len = start + reserved;
-        }
-        if let Some((hole_start, gap)) = self.get_last_hole()
-            && hole_start > start
-        {
-            start = hole_start;
-            len = start + gap;
-        }
-        if let Some((region_start, region)) = self.get_last_region()
-            && region_start > start
-        {
-            len = region_start + region.meta().read().reserved();
-        }
-        len
+        let reserved_end = self
+            .get_last_reserved()
+            .map_or(0, |(start, reserved)| start + reserved);
+        let hole_end = self.get_last_hole().map_or(0, |(start, gap)| start + gap);
+        // Pending holes can't be reused before the next flush, appending over them included
+        let pending_hole_end = self
+            .pending_holes
+            .last_key_value()
+            .map_or(0, |(start, gap)| start + gap);
+        let region_end = self
+            .get_last_region()
+            .map_or(0, |(start, region)| start + region.meta().read().reserved());
+        reserved_end
+            .max(hole_end)
+            .max(pending_hole_end)
+            .max(region_end)
     }
 
     pub fn get_last_region(&self) -> Option<(u64, &Region)> {
@@ -103,6 +126,10 @@ impl Layout {
             && self
                 .get_last_reserved()
                 .is_none_or(|(reserved_start, _)| last_start > reserved_start)
+            && self
+                .pending_holes
+                .last_key_value()
+                .is_none_or(|(hole_start, _)| last_start > *hole_start)
         {
             true
         } else {
@@ -124,7 +151,7 @@ impl Layout {
     pub fn remove_region(&mut self, region: &Region) -> Result<()> {
         let region_meta = region.meta().read();
         let start = region_meta.start();
-        let mut reserved = region_meta.reserved();
+        let reserved = region_meta.reserved();
 
         let removed = self.start_to_region.remove(&start);
 
@@ -136,22 +163,27 @@ impl Layout {
             return Err(Error::RegionIndexMismatch);
         }
 
+        self.free_range(start, reserved);
+
+        Ok(())
+    }
+
+    /// Marks a range that stopped belonging to a region as a pending hole.
+    pub fn free_range(&mut self, start: u64, mut len: u64) {
         // Coalesce with adjacent holes
-        reserved += self
+        len += self
             .start_to_hole
-            .remove(&(start + reserved))
+            .remove(&(start + len))
             .unwrap_or_default();
 
         // Mark as pending hole (can't reuse until flush)
         if let Some((&hole_start, gap)) = self.pending_holes.range_mut(..start).next_back()
             && hole_start + *gap == start
         {
-            *gap += reserved;
+            *gap += len;
         } else {
-            self.pending_holes.insert(start, reserved);
+            self.pending_holes.insert(start, len);
         }
-
-        Ok(())
     }
 
     pub fn get_hole(&self, start: u64) -> Option<u64> {
@@ -177,6 +209,47 @@ impl Layout {
         best_gap.map(|(_, s)| s)
     }
 
+    /// Largest hole that can hold `reserved` bytes, the earliest one on ties.
+    pub fn find_largest_adequate_hole(&self, reserved: u64) -> Option<u64> {
+        self.largest_hole()
+            .filter(|&(_, gap)| gap >= reserved)
+            .map(|(start, _)| start)
+    }
+
+    /// Earliest hole that can hold `reserved` bytes.
+    pub fn find_first_adequate_hole(&self, reserved: u64) -> Option<u64> {
+        self.start_to_hole
+            .iter()
+            .find(|&(_, &gap)| gap >= reserved)
+            .map(|(&start, _)| start)
+    }
+
+    /// Hole that can hold `reserved` bytes picked according to `fit`.
+    pub fn find_adequate_hole(&self, fit: HoleFit, reserved: u64) -> Option<u64> {
+        match fit {
+            HoleFit::BestFit => self.find_smallest_adequate_hole(reserved),
+            HoleFit::WorstFit => self.find_largest_adequate_hole(reserved),
+            HoleFit::FirstFit => self.find_first_adequate_hole(reserved),
+        }
+    }
+
+    /// Earliest hole starting before `before` that can hold `reserved` bytes.
+    pub fn find_first_adequate_hole_before(&self, before: u64, reserved: u64) -> Option<u64> {
+        self.start_to_hole
+            .range(..before)
+            .find(|&(_, &gap)| gap >= reserved)
+            .map(|(&start, _)| start)
+    }
+
+    /// Drops the last hole if nothing is laid out after it.
+    pub fn remove_trailing_hole(&mut self) -> Option<u64> {
+        let (start, gap) = self.get_last_hole()?;
+        if start + gap != self.len() {
+            return None;
+        }
+        self.start_to_hole.remove(&start)
+    }
+
     pub fn remove_or_compress_hole(&mut self, start: u64, compress_by: u64) {
         if let Some(gap) = self.start_to_hole.remove(&start)
             && gap != compress_by
@@ -190,6 +263,28 @@ impl Layout {
         }
     }
 
+    /// Carves `[start, start + reserved)` out of the hole containing it, if any.
+    pub fn take_hole_range(&mut self, start: u64, reserved: u64) -> bool {
+        let Some((&hole_start, &gap)) = self.start_to_hole.range(..=start).next_back() else {
+            return false;
+        };
+
+        if start + reserved > hole_start + gap {
+            return false;
+        }
+
+        self.start_to_hole.remove(&hole_start);
+        if hole_start < start {
+            self.start_to_hole.insert(hole_start, start - hole_start);
+        }
+        let end = start + reserved;
+        if end < hole_start + gap {
+            self.start_to_hole.insert(end, hole_start + gap - end);
+        }
+
+        true
+    }
+
     pub fn reserve(&mut self, start: u64, reserved: u64) {
         if self.start_to_reserved.insert(start, reserved).is_some() {
             unreachable!();
@@ -200,6 +295,89 @@ impl Layout {
         self.start_to_reserved.remove(&start)
     }
 
+    /// Checks the layout invariants against the regions and the data file length.
+    ///
+    /// Regions, holes and reservations must tile the file from offset 0 without overlaps
+    /// or gaps, holes must be coalesced and every region must be where its metadata says.
+    /// Free space past the last entry is allowed.
+    pub fn validate(&self, regions: &Regions, file_len: u64) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidLayout(reason));
+
+        let live = regions.index_to_region().iter().flatten().count();
+        if live != self.start_to_region.len() {
+            return invalid(format!(
+                "{live} regions but {} in layout",
+                self.start_to_region.len()
+            ));
+        }
+
+        let mut ranges = vec![];
+
+        for (&start, region) in &self.start_to_region {
+            let meta = region.meta().read();
+            if meta.start() != start {
+                return invalid(format!(
+                    "region '{}' is at {} in layout but its metadata says {}",
+                    meta.id(),
+                    start,
+                    meta.start()
+                ));
+            }
+            if meta.len() > meta.reserved() {
+                return invalid(format!(
+                    "region '{}' has length {} over its reserved {}",
+                    meta.id(),
+                    meta.len(),
+                    meta.reserved()
+                ));
+            }
+            if regions
+                .get_region_from_index(region.index())
+                .is_none_or(|r| !Arc::ptr_eq(&**r, &**region))
+            {
+                return invalid(format!("region '{}' isn't in regions", meta.id()));
+            }
+            ranges.push((start, meta.reserved(), "region"));
+        }
+
+        ranges.extend(self.start_to_hole.iter().map(|(&s, &l)| (s, l, "hole")));
+        ranges.extend(
+            self.pending_holes
+                .iter()
+                .map(|(&s, &l)| (s, l, "pending hole")),
+        );
+        ranges.extend(
+            self.start_to_reserved
+                .iter()
+                .map(|(&s, &l)| (s, l, "reserved")),
+        );
+        ranges.sort_unstable();
+
+        let mut prev: Option<(u64, u64, &str)> = None;
+        for (start, len, kind) in ranges {
+            let prev_end = prev.map_or(0, |(s, l, _)| s + l);
+            if start < prev_end {
+                return invalid(format!(
+                    "{kind} at {start} overlaps previous range ending at {prev_end}"
+                ));
+            }
+            if start > prev_end {
+                return invalid(format!("gap between {prev_end} and {kind} at {start}"));
+            }
+            if kind == "hole" && prev.is_some_and(|(_, _, kind)| kind == "hole") {
+                return invalid(format!("hole at {start} wasn't coalesced with previous"));
+            }
+            prev = Some((start, len, kind));
+        }
+
+        let end = prev.map_or(0, |(s, l, _)| s + l);
+        if end > file_len {
+            return invalid(format!("layout ends at {end} past file length {file_len}"));
+        }
+
+        Ok(())
+    }
+
     /// Promote pending holes to real holes after flush
     /// Safe to reuse now that metadata changes are durable
     pub fn promote_pending_holes(&mut self) {
@@ -4,37 +4,75 @@
 // #![doc = include_str!("../examples/db.rs")]
 // #![doc = "```\n"]
 
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
 use std::{
     collections::HashSet,
-    fs::{self, File, OpenOptions},
+    fs::{self, File, OpenOptions, TryLockError},
+    io,
     ops::Deref,
-    os::unix::io::AsRawFd,
     path::{Path, PathBuf},
-    sync::{Arc, Weak},
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use libc::off_t;
-use log::debug;
+use log::{debug, info};
 use memmap2::{MmapMut, MmapOptions};
-use parking_lot::{RwLock, RwLockReadGuard};
+use parking_lot::{RwLock, RwLockReadGuard, RwLockUpgradableReadGuard};
 
+mod advice;
+mod batch;
+mod catalog;
+mod checksum;
+mod cursor;
 pub mod error;
+mod flush;
 mod layout;
+#[cfg(feature = "tokio")]
+mod nonblocking;
+mod observer;
+mod options;
+mod os;
 mod reader;
 mod region;
 mod regions;
-
+mod snapshot;
+mod stats;
+mod txn;
+
+pub use advice::*;
+pub use batch::*;
+pub use catalog::*;
+use checksum::*;
+pub use cursor::*;
 pub use error::*;
+pub use flush::*;
 use layout::*;
+pub use observer::*;
+pub use options::*;
 use rayon::prelude::*;
 pub use reader::*;
 pub use region::*;
 use regions::*;
+pub use stats::*;
+pub use txn::*;
 
 pub const PAGE_SIZE: u64 = 4096;
 pub const PAGE_SIZE_MINUS_1: u64 = PAGE_SIZE - 1;
 const GB: usize = 1024 * 1024 * 1024;
 
+/// Returned by `Database::open_reporting`.
+#[derive(Debug)]
+pub struct OpenResult {
+    pub db: Database,
+    /// Whether the data file didn't exist before opening
+    pub created: bool,
+}
+
 /// Memory-mapped database with dynamic space allocation and hole punching.
 ///
 /// Provides efficient storage through memory mapping with automatic region management,
@@ -49,6 +87,17 @@ pub struct DatabaseInner {
     layout: RwLock<Layout>,
     file: RwLock<File>,
     mmap: RwLock<MmapMut>,
+    /// Writes that didn't need to move their region, since open.
+    in_place_writes: AtomicU64,
+    /// Writes that moved their region to a hole or to the end of the file, since open.
+    relocations: AtomicU64,
+    observer: Option<Arc<dyn RegionObserver>>,
+    growth_policy: GrowthPolicy,
+    hole_fit: HoleFit,
+    /// Access pattern set with `advise`, reapplied on every remap.
+    advice: RwLock<Advice>,
+    /// Opened with `open_read_only`, every write errors with `ReadOnly`.
+    read_only: bool,
 }
 
 impl Database {
@@ -57,24 +106,89 @@ impl Database {
         Self::open_with_min_len(path, 0)
     }
 
+    /// Same as `open` but also reports whether the data file was created by this call.
+    ///
+    /// Creation is detected atomically, so concurrent creators can't both see `created`.
+    pub fn open_reporting(path: &Path) -> Result<OpenResult> {
+        let (db, created) = Self::open_(path, DatabaseOptions::default(), false, None, false)?;
+        Ok(OpenResult { db, created })
+    }
+
     /// Opens or creates a database with a minimum initial file size.
     pub fn open_with_min_len(path: &Path, min_len: u64) -> Result<Self> {
-        fs::create_dir_all(path)?;
+        Self::open_with(path, DatabaseOptions::default().with_min_len(min_len))
+    }
 
-        let file = OpenOptions::new()
-            .read(true)
-            .create(true)
-            .write(true)
-            .truncate(false)
-            .open(Self::data_path_(path))?;
-        debug!("File opened.");
+    /// Same as `open` but retries with backoff while another process holds the lock, erroring
+    /// with `LockTimeout` once `timeout` elapses.
+    pub fn open_with_lock_timeout(path: &Path, timeout: Duration) -> Result<Self> {
+        Self::open_with(path, DatabaseOptions::default().with_lock_timeout(timeout))
+    }
 
-        file.try_lock()?;
-        debug!("File locked.");
+    /// Opens or creates a database with the given options.
+    pub fn open_with(path: &Path, options: DatabaseOptions) -> Result<Self> {
+        Self::open_(path, options, false, None, false).map(|(db, _)| db)
+    }
+
+    /// Same as `open` but calls `observer` on region creations, removals and relocations.
+    pub fn open_with_observer(path: &Path, observer: Arc<dyn RegionObserver>) -> Result<Self> {
+        Self::open_(
+            path,
+            DatabaseOptions::default(),
+            false,
+            Some(observer),
+            false,
+        )
+        .map(|(db, _)| db)
+    }
+
+    /// Opens a database whose regions metadata file may be damaged, salvaging what it can.
+    ///
+    /// Metadata slots that are unreadable, point outside of the data file, overlap another
+    /// region or reuse another region's id are logged and dropped, the rest is kept as is.
+    ///
+    /// Limitations: the data file carries no region ids or lengths, so dropped regions can't
+    /// be rebuilt from it and their space simply becomes free. A slot that is damaged but still
+    /// plausible (a wrong `len` within `reserved` for example) can't be detected and is kept.
+    pub fn open_recover(path: &Path) -> Result<Self> {
+        Self::open_(path, DatabaseOptions::default(), true, None, false).map(|(db, _)| db)
+    }
+
+    /// Opens an existing database for reading only, without locking it.
+    ///
+    /// Another process can keep writing to it in the meantime. The layout is the one
+    /// at the time of opening, and the data is mapped privately so nothing can ever be
+    /// written back. Every method that would write errors with `ReadOnly`, and a batch whose
+    /// commit was interrupted isn't replayed, see `Batch::commit`.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        Self::open_(path, DatabaseOptions::default(), false, None, true).map(|(db, _)| db)
+    }
+
+    fn open_(
+        path: &Path,
+        options: DatabaseOptions,
+        recover: bool,
+        observer: Option<Arc<dyn RegionObserver>>,
+        read_only: bool,
+    ) -> Result<(Self, bool)> {
+        let data_path = Self::data_path_(path);
+
+        if read_only {
+            let file = OpenOptions::new().read(true).open(&data_path)?;
+            let regions = Regions::open_read_only(path)?;
+            let mmap = unsafe { MmapOptions::new().map_copy(&file)? };
+            return Self::init(path, file, mmap, regions, options, false, None, true)
+                .map(|db| (db, false));
+        }
+
+        fs::create_dir_all(path)?;
+
+        let (file, created) = Self::open_data_file(&data_path, options.lock_timeout)?;
+        debug!("File opened and locked.");
 
         let file_len = file.metadata()?.len();
-        if file_len < min_len {
-            file.set_len(min_len)?;
+        if file_len < options.min_len {
+            file.set_len(options.min_len)?;
             debug!("File extended.");
             file.sync_all()?;
         }
@@ -83,19 +197,95 @@ impl Database {
         let mmap = Self::create_mmap(&file)?;
         debug!("Mmap created.");
 
+        Self::init(path, file, mmap, regions, options, recover, observer, false)
+            .map(|db| (db, created))
+    }
+
+    /// Opens and locks the data file, creating it if needed, retrying until `lock_timeout`
+    /// elapses while it's locked.
+    fn open_data_file(data_path: &Path, lock_timeout: Option<Duration>) -> Result<(File, bool)> {
+        let deadline = lock_timeout.map(|timeout| Instant::now() + timeout);
+        let mut backoff = Duration::from_millis(1);
+
+        loop {
+            let res = match os::open_exclusive(
+                OpenOptions::new().read(true).write(true).create_new(true),
+                data_path,
+            ) {
+                Ok(file) => Ok((file, true)),
+                Err(Error::IO(e)) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    os::open_exclusive(OpenOptions::new().read(true).write(true), data_path)
+                        .map(|file| (file, false))
+                }
+                Err(e) => Err(e),
+            };
+
+            let Some(deadline) = deadline else {
+                return res;
+            };
+            match res {
+                Err(Error::TryLock(TryLockError::WouldBlock)) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::LockTimeout {
+                            path: data_path.to_owned(),
+                        });
+                    }
+                    thread::sleep(backoff.min(deadline - now));
+                    backoff = (backoff * 2).min(Duration::from_millis(100));
+                }
+                res => return res,
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn init(
+        path: &Path,
+        file: File,
+        mmap: MmapMut,
+        regions: Regions,
+        options: DatabaseOptions,
+        recover: bool,
+        observer: Option<Arc<dyn RegionObserver>>,
+        read_only: bool,
+    ) -> Result<Self> {
         let db = Self(Arc::new(DatabaseInner {
             path: path.to_owned(),
             file: RwLock::new(file),
             mmap: RwLock::new(mmap),
             regions: RwLock::new(regions),
             layout: RwLock::new(Layout::default()),
+            in_place_writes: AtomicU64::new(0),
+            relocations: AtomicU64::new(0),
+            observer,
+            growth_policy: options.growth_policy,
+            hole_fit: options.hole_fit,
+            advice: RwLock::new(Advice::Normal),
+            read_only,
         }));
 
-        db.regions.write().fill_index_to_region(&db)?;
+        if recover {
+            let data_len = db.file_len()?;
+            let dropped = db
+                .regions
+                .write()
+                .fill_index_to_region_recovering(&db, data_len)?;
+            info!(
+                "Recovered {} regions, dropped {dropped}.",
+                db.regions.read().id_to_index().len()
+            );
+        } else {
+            db.regions.write().fill_index_to_region(&db)?;
+        }
         debug!("Filled regions.");
         *db.layout.write() = Layout::from(&*db.regions.read());
         debug!("Layout created.");
 
+        if !read_only {
+            db.replay_journal()?;
+        }
+
         Ok(db)
     }
 
@@ -104,6 +294,8 @@ impl Database {
     }
 
     pub fn set_min_len(&self, len: u64) -> Result<()> {
+        self.check_writable()?;
+
         let len = Self::ceil_number_to_page_size_multiple(len);
 
         let file_len = self.file_len()?;
@@ -113,13 +305,15 @@ impl Database {
 
         let mut mmap = self.mmap.write();
         let file = self.file.write();
+        os::unmap_before_resize(&mut mmap)?;
         file.set_len(len)?;
         file.sync_all()?;
-        *mmap = Self::create_mmap(&file)?;
+        *mmap = self.remap(&file)?;
         Ok(())
     }
 
     pub fn set_min_regions(&self, regions: usize) -> Result<()> {
+        self.check_writable()?;
         self.regions
             .write()
             .set_min_len((regions * SIZE_OF_REGION_METADATA) as u64)?;
@@ -132,40 +326,157 @@ impl Database {
     }
 
     /// Creates a region with the given ID, or returns it if it already exists.
+    #[inline]
     pub fn create_region_if_needed(&self, id: &str) -> Result<Region> {
+        self.create_region_with_capacity(id, PAGE_SIZE)
+    }
+
+    /// Creates a region with at least `min_reserved` bytes reserved (rounded up to a page multiple),
+    /// or returns it untouched if it already exists.
+    pub fn create_region_with_capacity(&self, id: &str, min_reserved: u64) -> Result<Region> {
         if let Some(region) = self.get_region(id) {
             return Ok(region);
         }
+        self.check_writable()?;
+        Self::check_region_id(id)?;
+
+        let reserved = Self::ceil_number_to_page_size_multiple(min_reserved.max(PAGE_SIZE));
 
         let mut regions = self.regions.write();
         let mut layout = self.layout.write();
 
-        let start = if let Some(start) = layout.find_smallest_adequate_hole(PAGE_SIZE) {
-            layout.remove_or_compress_hole(start, PAGE_SIZE);
+        let start = if let Some(start) = layout.find_adequate_hole(self.hole_fit, reserved) {
+            layout.remove_or_compress_hole(start, reserved);
             start
         } else {
-            let start = layout
-                .get_last_region()
-                .map(|(_, region)| {
-                    let region_meta = region.meta().read();
-                    region_meta.start() + region_meta.reserved()
-                })
-                .unwrap_or_default();
+            // Past reservations of writes moving a region to the end too, not just regions
+            let start = layout.len();
 
-            let len = start + PAGE_SIZE;
+            let len = start + reserved;
 
             self.set_min_len(len)?;
 
             start
         };
 
-        let region = regions.create_region(self, id.to_owned(), start)?;
+        let region = regions.create_region(self, id.to_owned(), start, reserved)?;
 
         layout.insert_region(start, &region);
+        drop(layout);
+        drop(regions);
+
+        if let Some(observer) = self.observer.as_deref() {
+            observer.on_create(id, region.index());
+        }
 
         Ok(region)
     }
 
+    /// Creates a region at exactly `start`, which must lie in a free hole.
+    ///
+    /// Errors with `RangeNotFree` if `[start, start + reserved)` isn't entirely within a hole.
+    /// Space freed by a removal only becomes a hole after the next flush.
+    pub fn create_region_at(&self, id: &str, start: u64, reserved: u64) -> Result<Region> {
+        self.check_writable()?;
+        Self::check_region_id(id)?;
+        if self.get_region(id).is_some() {
+            return Err(Error::RegionAlreadyExists);
+        }
+
+        let reserved = Self::ceil_number_to_page_size_multiple(reserved.max(PAGE_SIZE));
+
+        let mut regions = self.regions.write();
+        let mut layout = self.layout.write();
+
+        if !start.is_multiple_of(PAGE_SIZE) || !layout.take_hole_range(start, reserved) {
+            return Err(Error::RangeNotFree { start, reserved });
+        }
+
+        let region = regions.create_region(self, id.to_owned(), start, reserved)?;
+
+        layout.insert_region(start, &region);
+        drop(layout);
+        drop(regions);
+
+        if let Some(observer) = self.observer.as_deref() {
+            observer.on_create(id, region.index());
+        }
+
+        Ok(region)
+    }
+
+    /// Copies `region` into `dest` as a new region `new_id`, straight from one memory map to
+    /// the other.
+    ///
+    /// Only the `len` bytes in use are copied, the new region reserves just enough for them.
+    /// The attr bytes are copied too. `dest` may be this database. Errors with
+    /// `RegionAlreadyExists` if `dest` already has a region `new_id`.
+    pub fn copy_region_to(&self, region: &Region, dest: &Database, new_id: &str) -> Result<Region> {
+        if dest.get_region(new_id).is_some() {
+            return Err(Error::RegionAlreadyExists);
+        }
+
+        if Arc::ptr_eq(&self.0, &dest.0) {
+            return self.copy_region(region, new_id);
+        }
+
+        // Held until the copy is done so that the source can't change in between, in lock
+        // order: memory map then metadata
+        let mmap = self.mmap.read();
+        let region_meta = region.meta().read();
+        let start = region_meta.start() as usize;
+        let len = region_meta.len();
+
+        let copy = dest.create_region_with_capacity(new_id, len)?;
+        dest.write_all_to_region(&copy, &mmap[start..start + len as usize])?;
+        copy.set_attr(region_meta.attr())?;
+        drop(region_meta);
+        drop(mmap);
+
+        Ok(copy)
+    }
+
+    /// `copy_region_to` within this database, copying through a single read guard of the
+    /// memory map.
+    ///
+    /// The source can't be locked while the copy is created, as that locks the layout, so if
+    /// it grew in between the copy is reserved again.
+    fn copy_region(&self, region: &Region, new_id: &str) -> Result<Region> {
+        let len = region.meta().read().len();
+        let copy = self.create_region_with_capacity(new_id, len)?;
+
+        loop {
+            let mmap = self.mmap.read();
+            let region_meta = region.meta().read();
+            let mut copy_meta = copy.meta().write();
+
+            let len = region_meta.len();
+            if len > copy_meta.reserved() {
+                drop(copy_meta);
+                drop(region_meta);
+                drop(mmap);
+                self.reserve_region(&copy, len)?;
+                continue;
+            }
+
+            let from = region_meta.start() as usize;
+            let to = copy_meta.start() as usize;
+            // Regions never overlap and the copy can't move while its metadata is locked
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    mmap.as_ptr().add(from),
+                    mmap.as_ptr().add(to) as *mut u8,
+                    len as usize,
+                );
+            }
+            self.set_len_and_checksum(&mut copy_meta, 0, &mmap[to..to + len as usize], len);
+            copy_meta.set_attr(region_meta.attr());
+            drop(copy_meta);
+
+            return Ok(copy);
+        }
+    }
+
     #[inline]
     pub fn write_all_to_region(&self, region: &Region, data: &[u8]) -> Result<()> {
         self.write_all_to_region_at_(region, data, None, false)
@@ -176,6 +487,62 @@ impl Database {
         self.write_all_to_region_at_(region, data, Some(at), false)
     }
 
+    /// Like `write_all_to_region_at` but `at` may be past the current length.
+    ///
+    /// The gap between the current length and `at` reads back as zeros. It isn't written,
+    /// whole pages are punched and only its unaligned edges get zeroed.
+    /// The new length is `at + data.len()` if that's past the current one.
+    pub fn write_all_to_region_sparse(&self, region: &Region, data: &[u8], at: u64) -> Result<()> {
+        let region_meta = region.meta().read();
+        let len = region_meta.len();
+        let reserved = region_meta.reserved();
+        drop(region_meta);
+
+        if at <= len {
+            return self.write_all_to_region_at_(region, data, Some(at), false);
+        }
+
+        self.reserve_region(region, (at + data.len() as u64).max(reserved))?;
+
+        let start = region.meta().read().start();
+        self.zero_range(start + len, start + at)?;
+
+        let mut region_meta = region.meta().write();
+        region_meta.set_len(at);
+        region_meta.mark_checksum_stale();
+        drop(region_meta);
+
+        self.write_all_to_region_at_(region, data, Some(at), false)
+    }
+
+    /// Zeroes `[from, to)` of the file, whole pages are punched instead of written when the
+    /// platform supports it.
+    fn zero_range(&self, from: u64, to: u64) -> Result<()> {
+        let punch_from = Self::ceil_number_to_page_size_multiple(from).min(to);
+        let punch_to = (to / PAGE_SIZE * PAGE_SIZE).max(punch_from);
+
+        self.write(from, &vec![0; (punch_from - from) as usize])?;
+        self.write(punch_to, &vec![0; (to - punch_to) as usize])?;
+
+        if punch_from == punch_to {
+            return Ok(());
+        }
+
+        let file = self.file.read();
+        let mut mmap = self.mmap.write();
+        if Self::punch_hole(&file, punch_from, punch_to - punch_from).is_ok() {
+            *mmap = self.remap(&file)?;
+            return Ok(());
+        }
+        drop(mmap);
+        drop(file);
+
+        let zeros = vec![0; PAGE_SIZE as usize];
+        (punch_from..punch_to)
+            .step_by(PAGE_SIZE as usize)
+            .try_for_each(|at| self.write(at, &zeros))
+    }
+
     #[inline]
     pub fn truncate_write_all_to_region(
         &self,
@@ -193,6 +560,8 @@ impl Database {
         at: Option<u64>,
         truncate: bool,
     ) -> Result<()> {
+        self.check_writable()?;
+
         let region_meta = region.meta().read();
         let start = region_meta.start();
         let reserved = region_meta.reserved();
@@ -226,25 +595,24 @@ impl Database {
             // );
 
             if at.is_none() {
-                self.write(write_start, data);
+                self.write(write_start, data)?;
             }
 
             let mut region_meta = region.meta().write();
 
             if at.is_some() {
-                self.write(write_start, data);
+                self.write(write_start, data)?;
             }
 
-            region_meta.set_len(new_len);
+            self.set_len_and_checksum(&mut region_meta, at.unwrap_or(len), data, new_len);
+
+            self.in_place_writes.fetch_add(1, Ordering::Relaxed);
 
             return Ok(());
         }
 
         assert!(new_len > reserved);
-        let mut new_reserved = reserved;
-        while new_len > new_reserved {
-            new_reserved *= 2;
-        }
+        let new_reserved = self.growth_policy.grow(reserved, new_len);
         assert!(new_len <= new_reserved);
         let added_reserve = new_reserved - reserved;
 
@@ -260,10 +628,12 @@ impl Database {
             drop(region_meta);
             drop(layout);
 
-            self.write(write_start, data);
+            self.write(write_start, data)?;
 
             let mut region_meta = region.meta().write();
-            region_meta.set_len(new_len);
+            self.set_len_and_checksum(&mut region_meta, at.unwrap_or(len), data, new_len);
+
+            self.in_place_writes.fetch_add(1, Ordering::Relaxed);
 
             return Ok(());
         }
@@ -282,16 +652,18 @@ impl Database {
             drop(region_meta);
             drop(layout);
 
-            self.write(write_start, data);
+            self.write(write_start, data)?;
 
             let mut region_meta = region.meta().write();
-            region_meta.set_len(new_len);
+            self.set_len_and_checksum(&mut region_meta, at.unwrap_or(len), data, new_len);
+
+            self.in_place_writes.fetch_add(1, Ordering::Relaxed);
 
             return Ok(());
         }
 
         // Find hole big enough to move the region
-        if let Some(hole_start) = layout.find_smallest_adequate_hole(new_reserved) {
+        if let Some(hole_start) = layout.find_adequate_hole(self.hole_fit, new_reserved) {
             // info!("Move {region_index} to hole at {hole_start}");
 
             layout.remove_or_compress_hole(hole_start, new_reserved);
@@ -300,9 +672,9 @@ impl Database {
             self.write(
                 hole_start,
                 &self.mmap.read()[start as usize..write_start as usize],
-            );
+            )?;
 
-            self.write(hole_start + at.unwrap_or(len), data);
+            self.write(hole_start + at.unwrap_or(len), data)?;
 
             let mut layout = self.layout.write();
             layout.move_region(hole_start, region)?;
@@ -310,7 +682,12 @@ impl Database {
             let mut region_meta = region.meta().write();
             region_meta.set_start(hole_start);
             region_meta.set_reserved(new_reserved);
-            region_meta.set_len(new_len);
+            self.set_len_and_checksum(&mut region_meta, at.unwrap_or(len), data, new_len);
+            drop(region_meta);
+            drop(layout);
+
+            self.relocations.fetch_add(1, Ordering::Relaxed);
+            self.notify_relocate(region, start, hole_start);
 
             return Ok(());
         }
@@ -330,8 +707,8 @@ impl Database {
         self.write(
             new_start,
             &self.mmap.read()[start as usize..write_start as usize],
-        );
-        self.write(new_start + at.unwrap_or(len), data);
+        )?;
+        self.write(new_start + at.unwrap_or(len), data)?;
 
         let mut layout = self.layout.write();
         layout.move_region(new_start, region)?;
@@ -340,24 +717,125 @@ impl Database {
         let mut region_meta = region.meta().write();
         region_meta.set_start(new_start);
         region_meta.set_reserved(new_reserved);
+        self.set_len_and_checksum(&mut region_meta, at.unwrap_or(len), data, new_len);
+        drop(region_meta);
+        drop(layout);
+
+        self.relocations.fetch_add(1, Ordering::Relaxed);
+        self.notify_relocate(region, start, new_start);
+
+        Ok(())
+    }
+
+    /// Sets the length of a region after `data` was written at `at`, updating its checksum.
+    ///
+    /// Appends extend the previous checksum. Anything else only marks it stale, it's recomputed
+    /// by the next flush so small updates don't rehash the whole region.
+    fn set_len_and_checksum(
+        &self,
+        region_meta: &mut RegionMetadata,
+        at: u64,
+        data: &[u8],
+        new_len: u64,
+    ) {
+        let len = region_meta.len();
+        let checksum = region_meta.checksum();
+        if at == len
+            && new_len == len + data.len() as u64
+            && !region_meta.is_checksum_stale()
+            && (len == 0 || checksum != 0)
+        {
+            region_meta.set_checksum(crc32(checksum, data));
+        } else {
+            region_meta.mark_checksum_stale();
+        }
         region_meta.set_len(new_len);
+    }
+
+    fn region_checksum(&self, start: u64, len: u64) -> u32 {
+        crc32(0, &self.mmap.read()[start as usize..(start + len) as usize])
+    }
+
+    /// Recomputes the checksum of `region` if it was marked stale, returning whether it was.
+    ///
+    /// Only holds an upgradable lock on the metadata while hashing, so readers aren't blocked.
+    fn refresh_checksum(mmap: &MmapMut, region: &Region) -> bool {
+        let region_meta = region.meta().upgradable_read();
+        if !region_meta.is_checksum_stale() {
+            return false;
+        }
+        let start = region_meta.start() as usize;
+        let checksum = crc32(0, &mmap[start..start + region_meta.len() as usize]);
+        RwLockUpgradableReadGuard::upgrade(region_meta).set_checksum(checksum);
+        true
+    }
 
+    #[inline]
+    pub(crate) fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
         Ok(())
     }
 
+    /// Ids must fit in their metadata slot, see `MAX_REGION_ID_LEN`.
+    #[inline]
+    fn check_region_id(id: &str) -> Result<()> {
+        if id.len() > MAX_REGION_ID_LEN {
+            return Err(Error::RegionIdTooLong {
+                len: id.len(),
+                max: MAX_REGION_ID_LEN,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the database was opened with `open_read_only`.
+    #[inline]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn notify_relocate(&self, region: &Region, old_start: u64, new_start: u64) {
+        if let Some(observer) = self.observer.as_deref() {
+            let id = region.meta().read().id().to_string();
+            observer.on_relocate(&id, old_start, new_start);
+        }
+    }
+
+    /// Number of writes that didn't move their region since the database was opened.
+    #[inline]
+    pub fn in_place_writes(&self) -> u64 {
+        self.in_place_writes.load(Ordering::Relaxed)
+    }
+
+    /// Number of writes that moved their region since the database was opened.
+    ///
+    /// A high ratio compared to `in_place_writes` means regions would benefit
+    /// from being created with more capacity.
     #[inline]
-    fn write(&self, at: u64, data: &[u8]) {
+    pub fn relocations(&self) -> u64 {
+        self.relocations.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn write(&self, at: u64, data: &[u8]) -> Result<()> {
         let mmap = self.mmap.read();
         let data_len = data.len();
         let start = at as usize;
         let end = start + data_len;
         if end > mmap.len() {
-            unreachable!("Trying to write beyond mmap")
+            return Err(Error::WriteBeyondFile {
+                end: end as u64,
+                file_len: mmap.len() as u64,
+            });
         }
 
         (unsafe { std::slice::from_raw_parts_mut(mmap.as_ptr() as *mut u8, mmap.len()) })
             [start..end]
             .copy_from_slice(data);
+
+        Ok(())
     }
 
     ///
@@ -366,6 +844,7 @@ impl Database {
     /// Non destructive
     ///
     pub fn truncate_region(&self, region: &Region, from: u64) -> Result<()> {
+        self.check_writable()?;
         let mut region_meta = region.meta().write();
         let len = region_meta.len();
         if from == len {
@@ -377,6 +856,131 @@ impl Database {
             });
         }
         region_meta.set_len(from);
+        region_meta.mark_checksum_stale();
+        Ok(())
+    }
+
+    /// Lowers a region's reserved space to its length rounded up to a page, freeing the tail.
+    ///
+    /// The tail is punched right away and, like the space of a removed region, becomes a
+    /// reusable hole after the next flush.
+    pub fn shrink_region(&self, region: &Region) -> Result<()> {
+        self.check_writable()?;
+
+        let region_meta = region.meta().read();
+        let start = region_meta.start();
+        let reserved = region_meta.reserved();
+        let len = region_meta.len();
+        drop(region_meta);
+
+        let new_reserved = Self::ceil_number_to_page_size_multiple(len).max(PAGE_SIZE);
+        if new_reserved >= reserved {
+            return Ok(());
+        }
+
+        let file = self.file.read();
+        let mut mmap = self.mmap.write();
+        let mut layout = self.layout.write();
+        let mut region_meta = region.meta().write();
+
+        // A write may have grown or moved the region while no lock was held
+        if region_meta.start() != start
+            || region_meta.reserved() != reserved
+            || region_meta.len() > new_reserved
+        {
+            return Ok(());
+        }
+
+        let tail_start = start + new_reserved;
+        let tail = reserved - new_reserved;
+
+        if Self::approx_has_punchable_data(&mmap, tail_start, tail) {
+            Self::punch_hole(&file, tail_start, tail)?;
+            os::fsync(&file);
+            *mmap = self.remap(&file)?;
+        }
+
+        region_meta.set_reserved(new_reserved);
+        layout.free_range(tail_start, tail);
+
+        Ok(())
+    }
+
+    /// Grows a region's reserved space to `bytes` rounded up to a page, all at once.
+    ///
+    /// Meant for regions whose final size is known ahead, so that writes up to it don't go
+    /// through repeated growths and relocations. The region is expanded or moved like on a
+    /// write that doesn't fit, without writing anything. Errors with `ReserveShrink` if
+    /// `bytes` is below the current reservation, see `shrink_region` to release space.
+    pub fn reserve_region(&self, region: &Region, bytes: u64) -> Result<()> {
+        self.check_writable()?;
+
+        let region_meta = region.meta().read();
+        let start = region_meta.start();
+        let reserved = region_meta.reserved();
+        let len = region_meta.len();
+        drop(region_meta);
+
+        let new_reserved = Self::ceil_number_to_page_size_multiple(bytes);
+        if new_reserved < reserved {
+            return Err(Error::ReserveShrink {
+                reserved,
+                requested: bytes,
+            });
+        } else if new_reserved == reserved {
+            return Ok(());
+        }
+        let added_reserve = new_reserved - reserved;
+
+        let mut layout = self.layout.write();
+
+        if layout.is_last_anything(region) {
+            self.set_min_len(start + new_reserved)?;
+            region.meta().write().set_reserved(new_reserved);
+            return Ok(());
+        }
+
+        let hole_start = start + reserved;
+        if layout
+            .get_hole(hole_start)
+            .is_some_and(|gap| gap >= added_reserve)
+        {
+            layout.remove_or_compress_hole(hole_start, added_reserve);
+            region.meta().write().set_reserved(new_reserved);
+            return Ok(());
+        }
+
+        let new_start =
+            if let Some(hole_start) = layout.find_adequate_hole(self.hole_fit, new_reserved) {
+                layout.remove_or_compress_hole(hole_start, new_reserved);
+                hole_start
+            } else {
+                let new_start = layout.len();
+                self.set_min_len(new_start + new_reserved)?;
+                layout.reserve(new_start, new_reserved);
+                new_start
+            };
+        drop(layout);
+
+        self.write(
+            new_start,
+            &self.mmap.read()[start as usize..(start + len) as usize],
+        )?;
+
+        let mut layout = self.layout.write();
+        layout.move_region(new_start, region)?;
+        // Only set when appended at the end
+        layout.reserved(new_start);
+
+        let mut region_meta = region.meta().write();
+        region_meta.set_start(new_start);
+        region_meta.set_reserved(new_reserved);
+        drop(region_meta);
+        drop(layout);
+
+        self.relocations.fetch_add(1, Ordering::Relaxed);
+        self.notify_relocate(region, start, new_start);
+
         Ok(())
     }
 
@@ -388,32 +992,87 @@ impl Database {
     }
 
     pub fn rename_region(&self, old_id: &str, new_id: &str) -> Result<()> {
+        self.check_writable()?;
+        Self::check_region_id(new_id)?;
         self.regions.write().rename_region(old_id, new_id)
     }
 
     pub fn remove_region(&self, region: Region) -> Result<Option<Region>> {
+        self.check_writable()?;
+        let id = self
+            .observer
+            .is_some()
+            .then(|| region.meta().read().id().to_string());
+        let index = region.index();
+
         let mut regions = self.regions.write();
         let mut layout = self.layout.write();
         layout.remove_region(&region)?;
-        regions.remove_region(region)
+        let removed = regions.remove_region(region)?;
+        drop(layout);
+        drop(regions);
+
+        if let Some(observer) = self.observer.as_deref()
+            && let Some(id) = id
+        {
+            observer.on_remove(&id, index);
+        }
+
+        Ok(removed)
     }
 
-    pub fn retain_regions(&self, mut ids: HashSet<String>) -> Result<()> {
-        let regions_to_remove = self
-            .regions
-            .read()
+    /// Removes the region and returns the `(start, reserved)` range it occupied.
+    ///
+    /// Once flushed, the range can be reused as is with `create_region_at`.
+    pub fn remove_region_with_range(&self, region: Region) -> Result<Option<(u64, u64)>> {
+        let range = {
+            let region_meta = region.meta().read();
+            (region_meta.start(), region_meta.reserved())
+        };
+        Ok(self.remove_region(region)?.map(|_| range))
+    }
+
+    /// Removes every region whose id isn't in `ids`.
+    ///
+    /// Candidates are filtered in parallel, then all removed under a single lock of the
+    /// regions and layout instead of one per region.
+    pub fn retain_regions(&self, ids: HashSet<String>) -> Result<()> {
+        self.check_writable()?;
+
+        let mut regions = self.regions.write();
+        let mut layout = self.layout.write();
+
+        let regions_to_remove = regions
             .id_to_index()
-            .keys()
-            .filter(|id| !ids.remove(&**id))
-            .flat_map(|id| self.get_region(id))
+            .par_iter()
+            .filter(|(id, _)| !ids.contains(*id))
+            .flat_map(|(_, &index)| regions.get_region_from_index(index).cloned())
             .collect::<Vec<Region>>();
 
-        regions_to_remove
+        let removed = regions_to_remove
             .into_iter()
-            .try_for_each(|region| -> Result<()> {
-                self.remove_region(region)?;
-                Ok(())
+            .map(|region| -> Result<_> {
+                let id = self
+                    .observer
+                    .is_some()
+                    .then(|| region.meta().read().id().to_string());
+                let index = region.index();
+                layout.remove_region(&region)?;
+                regions.remove_region(region)?;
+                Ok((id, index))
             })
+            .collect::<Result<Vec<_>>>()?;
+        drop(layout);
+        drop(regions);
+
+        if let Some(observer) = self.observer.as_deref() {
+            removed
+                .into_iter()
+                .filter_map(|(id, index)| id.map(|id| (id, index)))
+                .for_each(|(id, index)| observer.on_remove(&id, index));
+        }
+
+        Ok(())
     }
 
     #[inline]
@@ -421,11 +1080,52 @@ impl Database {
         Ok(unsafe { MmapOptions::new().map_mut(file)? })
     }
 
+    /// Maps the data file again after its length or content changed, keeping the advice.
+    fn remap(&self, file: &File) -> Result<MmapMut> {
+        let mmap = Self::create_mmap(file)?;
+        let advice = *self.advice.read();
+        if advice != Advice::Normal {
+            advice.apply(&mmap, 0, mmap.len())?;
+        }
+        Ok(mmap)
+    }
+
     #[inline]
     pub fn mmap(&self) -> RwLockReadGuard<'_, MmapMut> {
         self.mmap.read()
     }
 
+    /// Starts an empty batch, see `Batch`.
+    pub fn begin_batch(&self) -> Batch {
+        Batch::new(self.clone())
+    }
+
+    /// Runs `f` on a new batch and commits it if `f` succeeds, discarding it otherwise.
+    pub fn batch<R, E>(&self, f: impl FnOnce(&mut Batch) -> Result<R, E>) -> Result<R, E>
+    where
+        E: From<Error>,
+    {
+        self.check_writable()?;
+        let mut batch = self.begin_batch();
+        let res = f(&mut batch)?;
+        batch.commit()?;
+        Ok(res)
+    }
+
+    /// Starts a read transaction, see `ReadTxn` for the guarantees it offers and their limits.
+    ///
+    /// Writes that grow the file block until it's dropped, deadlocking on this thread.
+    pub fn read_txn(&self) -> ReadTxn<'_> {
+        let mmap = self.mmap.read();
+        let regions = self.regions.read();
+        let snapshot = regions
+            .index_to_region()
+            .iter()
+            .map(|region| region.as_ref().map(|region| region.meta().read().clone()))
+            .collect();
+        ReadTxn::new(mmap, snapshot)
+    }
+
     #[inline]
     pub fn regions(&self) -> RwLockReadGuard<'_, Regions> {
         self.regions.read()
@@ -457,25 +1157,131 @@ impl Database {
         File::open(self.data_path()).map_err(Error::from)
     }
 
-    pub fn disk_usage(&self) -> String {
-        let path = self.data_path();
+    /// Hints the kernel that the `len` bytes at `start` will be read soon so it can page them
+    /// in ahead of time, complementing readahead for reads that jump around.
+    pub fn advise_will_need(&self, start: u64, len: u64) -> Result<()> {
+        let mmap = self.mmap.read();
+        let start = (start as usize).min(mmap.len());
+        let len = (len as usize).min(mmap.len() - start);
+        Advice::WillNeed.apply(&mmap, start, len)
+    }
+
+    /// Hints the kernel at how the whole data file is going to be read.
+    ///
+    /// Access patterns (`Normal`, `Random`, `Sequential`) stick and are reapplied whenever the
+    /// file gets remapped, while `WillNeed` and `DontNeed` only act on the current mapping.
+    /// Does nothing on platforms without `madvise`.
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        let mmap = self.mmap.read();
+        if advice.is_pattern() {
+            *self.advice.write() = advice;
+        }
+        advice.apply(&mmap, 0, mmap.len())
+    }
+
+    /// Every region with its start offset, in on-disk layout order.
+    pub fn iter_regions(&self) -> impl Iterator<Item = (u64, Region)> {
+        self.layout
+            .read()
+            .start_to_region()
+            .iter()
+            .map(|(start, region)| (*start, region.clone()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Sum of the length of every region, in bytes.
+    pub fn total_logical_size(&self) -> u64 {
+        self.regions
+            .read()
+            .index_to_region()
+            .iter()
+            .flatten()
+            .map(|region| region.meta().read().len())
+            .sum()
+    }
+
+    /// Sum of the reserved space of every region, in bytes.
+    pub fn total_reserved_size(&self) -> u64 {
+        self.regions
+            .read()
+            .index_to_region()
+            .iter()
+            .flatten()
+            .map(|region| region.meta().read().reserved())
+            .sum()
+    }
 
-        let output = std::process::Command::new("du")
-            .arg("-h")
-            .arg(&path)
-            .output()
-            .expect("Failed to run du");
+    /// Sum of the holes available for reuse, in bytes.
+    ///
+    /// Space freed since the last flush isn't counted yet.
+    pub fn total_hole_size(&self) -> u64 {
+        self.layout.read().start_to_hole().values().sum()
+    }
 
-        String::from_utf8_lossy(&output.stdout)
-            .replace(path.to_str().unwrap(), " ")
-            .trim()
-            .to_string()
+    /// Bytes actually allocated on disk by the data file, punched holes excluded.
+    pub fn disk_usage_bytes(&self) -> Result<u64> {
+        os::allocated_size(&self.file.read(), &self.data_path())
     }
 
+    /// Human readable size of the data file on disk, see `disk_usage_bytes`.
+    ///
+    /// Falls back to the file length minus the holes if the file can't be stat'ed.
+    pub fn disk_usage(&self) -> String {
+        let bytes = self.disk_usage_bytes().unwrap_or_else(|_| {
+            self.file_len()
+                .unwrap_or_default()
+                .saturating_sub(self.total_hole_size())
+        });
+        Self::format_bytes(bytes)
+    }
+
+    fn format_bytes(bytes: u64) -> String {
+        const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+        let mut size = bytes as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{bytes}B")
+        } else {
+            format!("{size:.1}{}", UNITS[unit])
+        }
+    }
+
+    /// Flushes the mmap (msync) then the dirty region metadata, recomputing stale checksums.
+    ///
+    /// Doesn't guarantee that file length changes survive a crash, see `flush_durable`.
+    #[inline]
     pub fn flush(&self) -> Result<()> {
+        self.flush_(false)
+    }
+
+    /// Same as `flush` but also fsyncs the data file before persisting region metadata,
+    /// so metadata never points to data or a file length that isn't durable yet.
+    #[inline]
+    pub fn flush_durable(&self) -> Result<()> {
+        self.flush_(true)
+    }
+
+    fn flush_(&self, durable: bool) -> Result<()> {
+        self.check_writable()?;
+
         let mmap = self.mmap.read();
         let regions = self.regions.read();
         mmap.flush()?;
+        if durable {
+            self.file.read().sync_all()?;
+        }
+        regions
+            .index_to_region()
+            .par_iter()
+            .flatten()
+            .for_each(|region| {
+                Self::refresh_checksum(&mmap, region);
+            });
         regions.flush()?;
 
         // Now that metadata is durable, pending holes can be reused
@@ -484,13 +1290,179 @@ impl Database {
         Ok(())
     }
 
+    /// Same as `flush` but on a background thread, keeping its order: the data is synced,
+    /// then region metadata is persisted and freed space becomes reusable.
+    ///
+    /// No region being flushed may be written to until `FlushHandle::wait` returns, or those
+    /// writes may or may not be part of the flush. Growing the file blocks until it's done.
+    pub fn flush_in_background(&self) -> Result<FlushHandle> {
+        self.check_writable()?;
+
+        let db = self.clone();
+        let handle = thread::Builder::new()
+            .name("rawdb-flush".to_string())
+            .spawn(move || db.flush())?;
+
+        Ok(FlushHandle::new(handle))
+    }
+
+    /// Flushes a single region's data range then its metadata, much cheaper than `flush`
+    /// when only a few regions changed.
+    ///
+    /// Only `[start, start + reserved)` is synced, so a region moved since its last sync
+    /// becomes durable at its new location. The space it moved out of stays a pending hole
+    /// until the next `flush`.
+    pub fn sync_region(&self, region: &Region) -> Result<()> {
+        self.check_writable()?;
+
+        let mmap = self.mmap.read();
+        let regions = self.regions.read();
+        let (start, reserved) = {
+            let region_meta = region.meta().read();
+            (region_meta.start(), region_meta.reserved())
+        };
+        mmap.flush_range(start as usize, reserved as usize)?;
+        Self::refresh_checksum(&mmap, region);
+        regions.flush_region(region)
+    }
+
+    /// Offline integrity check of the layout, see `Layout::validate`.
+    pub fn fsck(&self) -> Result<()> {
+        let file_len = self.file_len()?;
+        let regions = self.regions.read();
+        self.layout.read().validate(&regions, file_len)
+    }
+
+    /// Recomputes the checksum of every region and returns the ids of those that don't
+    /// match their stored one.
+    ///
+    /// Regions without a stored checksum, from databases written before checksums, are skipped.
+    /// Stale checksums, see `flush`, are recomputed instead of checked.
+    pub fn verify(&self) -> Result<Vec<String>> {
+        let mmap = self.mmap.read();
+        let regions = self.regions.read();
+        let mismatched = regions
+            .index_to_region()
+            .par_iter()
+            .flatten()
+            .filter_map(|region| {
+                if Self::refresh_checksum(&mmap, region) {
+                    return None;
+                }
+                let region_meta = region.meta().read();
+                let checksum = region_meta.checksum();
+                let start = region_meta.start() as usize;
+                let data = &mmap[start..start + region_meta.len() as usize];
+                (checksum != 0 && crc32(0, data) != checksum).then(|| region_meta.id().to_string())
+            })
+            .collect();
+        Ok(mismatched)
+    }
+
     #[inline]
     pub fn compact(&self) -> Result<()> {
+        self.check_writable()?;
         self.flush()?;
         self.punch_holes()
     }
 
+    /// Like `compact` but also moves regions leftward into holes until none fits anymore,
+    /// then shrinks the file to its last region.
+    pub fn defragment(&self) -> Result<()> {
+        self.check_writable()?;
+        self.flush()?;
+        while self.relocate_into_holes(usize::MAX)? > 0 {}
+        self.punch_holes()
+    }
+
+    /// Moves at most `max_moves` regions leftward into holes, returning how many moved.
+    ///
+    /// The incremental version of `defragment`: locks are only held during the call, so a
+    /// scheduler can spread the work across idle ticks. The database is consistent after
+    /// every step. Once it returns 0 the regions are laid out like after `defragment`, only
+    /// without the punched free space.
+    pub fn defragment_step(&self, max_moves: usize) -> Result<usize> {
+        self.check_writable()?;
+        self.flush()?;
+        self.relocate_into_holes(max_moves)
+    }
+
+    /// Walks regions by start and moves each one into the earliest hole before it
+    /// that fits its whole reservation, stopping after `max_moves` moves.
+    ///
+    /// Moved data is flushed, then region metadata once for the whole pass. Until then the
+    /// ranges regions moved out of are pending holes, so the old copies stay valid and a
+    /// later pass is needed to fill them. A region that doesn't fit in any earlier hole
+    /// stays where it is, leaving that hole in place.
+    fn relocate_into_holes(&self, max_moves: usize) -> Result<usize> {
+        let file = self.file.write();
+        let mut mmap = self.mmap.write();
+        let regions = self.regions.write();
+        let mut layout = self.layout.write();
+
+        let mut moves = vec![];
+
+        let by_start = layout
+            .start_to_region()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for region in by_start {
+            if moves.len() == max_moves {
+                break;
+            }
+
+            let region_meta = region.meta().read();
+            let start = region_meta.start();
+            let reserved = region_meta.reserved();
+            let len = region_meta.len();
+            drop(region_meta);
+
+            let Some(hole_start) = layout.find_first_adequate_hole_before(start, reserved) else {
+                continue;
+            };
+
+            layout.remove_or_compress_hole(hole_start, reserved);
+            mmap.copy_within(start as usize..(start + len) as usize, hole_start as usize);
+            mmap.flush_range(hole_start as usize, len as usize)?;
+
+            layout.move_region(hole_start, &region)?;
+            region.meta().write().set_start(hole_start);
+
+            moves.push((region, start, hole_start));
+        }
+
+        if !moves.is_empty() {
+            regions.flush()?;
+            layout.promote_pending_holes();
+        }
+
+        layout.remove_trailing_hole();
+
+        let len = Self::ceil_number_to_page_size_multiple(layout.len());
+        if len < file.metadata()?.len() {
+            os::unmap_before_resize(&mut mmap)?;
+            file.set_len(len)?;
+            file.sync_all()?;
+            *mmap = self.remap(&file)?;
+        }
+
+        drop(layout);
+        drop(regions);
+        drop(mmap);
+        drop(file);
+
+        let moved = moves.len();
+        for (region, old_start, new_start) in moves {
+            self.notify_relocate(&region, old_start, new_start);
+        }
+
+        Ok(moved)
+    }
+
     fn punch_holes(&self) -> Result<()> {
+        self.check_writable()?;
+
         let file = self.file.write();
         let mut mmap = self.mmap.write();
         let regions = self.regions.read();
@@ -535,10 +1507,8 @@ impl Database {
             .sum::<Result<usize>>()?;
 
         if punched > 0 {
-            unsafe {
-                libc::fsync(file.as_raw_fd());
-            }
-            *mmap = Self::create_mmap(&file)?;
+            os::fsync(&file);
+            *mmap = self.remap(&file)?;
         }
 
         Ok(())
@@ -672,7 +1642,69 @@ impl Database {
         Ok(())
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "freebsd")))]
+    #[cfg(windows)]
+    fn punch_hole(file: &File, start: u64, length: u64) -> Result<()> {
+        use std::{ffi::c_void, os::windows::io::AsRawHandle, ptr};
+        use windows_sys::Win32::System::{
+            IO::DeviceIoControl,
+            Ioctl::{FILE_ZERO_DATA_INFORMATION, FSCTL_SET_SPARSE, FSCTL_SET_ZERO_DATA},
+        };
+
+        let handle = file.as_raw_handle();
+        let mut returned = 0;
+
+        // Zeroing only releases blocks of sparse files, marking it again is a no-op
+        let mut result = unsafe {
+            DeviceIoControl(
+                handle,
+                FSCTL_SET_SPARSE,
+                ptr::null(),
+                0,
+                ptr::null_mut(),
+                0,
+                &mut returned,
+                ptr::null_mut(),
+            )
+        };
+
+        if result != 0 {
+            let zero_data = FILE_ZERO_DATA_INFORMATION {
+                FileOffset: start as i64,
+                BeyondFinalZero: (start + length) as i64,
+            };
+
+            result = unsafe {
+                DeviceIoControl(
+                    handle,
+                    FSCTL_SET_ZERO_DATA,
+                    &zero_data as *const FILE_ZERO_DATA_INFORMATION as *const c_void,
+                    size_of::<FILE_ZERO_DATA_INFORMATION>() as u32,
+                    ptr::null_mut(),
+                    0,
+                    &mut returned,
+                    ptr::null_mut(),
+                )
+            };
+        }
+
+        if result == 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(Error::HolePunchFailed {
+                start,
+                len: length,
+                source: err,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "freebsd",
+        windows
+    )))]
     fn punch_hole(_file: &File, _start: u64, _length: u64) -> Result<()> {
         Err(Error::String(
             "Hole punching not supported on this platform".to_string(),
@@ -697,12 +1729,13 @@ impl Deref for Database {
     }
 }
 
+#[cfg(target_os = "macos")]
 #[repr(C)]
 struct FPunchhole {
     fp_flags: u32,
     reserved: u32,
-    fp_offset: off_t,
-    fp_length: off_t,
+    fp_offset: libc::off_t,
+    fp_length: libc::off_t,
 }
 
 /// Weak reference to a Database that doesn't prevent it from being dropped.
@@ -713,11 +1746,14 @@ struct FPunchhole {
 pub struct WeakDatabase(Weak<DatabaseInner>);
 
 impl WeakDatabase {
+    /// Panics if the database was dropped, prefer `try_upgrade` where it may be.
     pub fn upgrade(&self) -> Database {
-        Database(
-            self.0
-                .upgrade()
-                .expect("Database was dropped while Region still exists"),
-        )
+        self.try_upgrade()
+            .expect("Database was dropped while Region still exists")
+    }
+
+    /// Returns `None` if the database was dropped.
+    pub fn try_upgrade(&self) -> Option<Database> {
+        self.0.upgrade().map(Database)
     }
 }
@@ -0,0 +1,33 @@
+use std::io;
+
+use crate::{Database, Error, Result};
+
+/// Async wrappers running the blocking operations on tokio's blocking thread pool.
+///
+/// Must be called from within a tokio runtime.
+impl Database {
+    /// Async version of `flush`.
+    pub async fn flush_async(&self) -> Result<()> {
+        self.spawn_blocking(|db| db.flush()).await
+    }
+
+    /// Async version of `flush_durable`.
+    pub async fn flush_durable_async(&self) -> Result<()> {
+        self.spawn_blocking(|db| db.flush_durable()).await
+    }
+
+    /// Async version of `compact`, which flushes then punches holes.
+    pub async fn compact_async(&self) -> Result<()> {
+        self.spawn_blocking(|db| db.compact()).await
+    }
+
+    async fn spawn_blocking<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&Database) -> Result<()> + Send + 'static,
+    {
+        let db = self.clone();
+        tokio::task::spawn_blocking(move || f(&db))
+            .await
+            .map_err(|e| Error::IO(io::Error::other(e)))?
+    }
+}
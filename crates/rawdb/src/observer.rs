@@ -0,0 +1,20 @@
+use std::fmt;
+
+/// Hooks called on region lifecycle events, for metrics and observability.
+///
+/// Hooks are invoked after the database released its locks, so they may call back into it.
+/// They run on the thread that triggered the event and should return quickly.
+pub trait RegionObserver: Send + Sync {
+    fn on_create(&self, _id: &str, _index: usize) {}
+
+    fn on_remove(&self, _id: &str, _index: usize) {}
+
+    /// Called when a write moved a region to a hole or to the end of the file.
+    fn on_relocate(&self, _id: &str, _old_start: u64, _new_start: u64) {}
+}
+
+impl fmt::Debug for dyn RegionObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RegionObserver")
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use crate::{Database, PAGE_SIZE};
+
+/// How a region's reserved space grows when a write doesn't fit in it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum GrowthPolicy {
+    /// Doubles the reserved space until the write fits.
+    #[default]
+    Double,
+    /// Adds a fixed number of bytes until the write fits.
+    Additive(u64),
+    /// Multiplies the reserved space by a ratio (above 1) until the write fits.
+    Ratio(f32),
+}
+
+impl GrowthPolicy {
+    /// New reserved size, grown from `reserved` until it can hold `len` bytes, page aligned.
+    pub fn grow(self, reserved: u64, len: u64) -> u64 {
+        let mut new_reserved = reserved;
+        while len > new_reserved {
+            new_reserved = match self {
+                Self::Double => new_reserved * 2,
+                Self::Additive(step) => {
+                    let step = step.max(1);
+                    new_reserved + (len - new_reserved).div_ceil(step) * step
+                }
+                // Always grow by at least a page, whatever the ratio
+                Self::Ratio(ratio) => ((new_reserved as f64 * ratio as f64).ceil() as u64)
+                    .max(new_reserved + PAGE_SIZE),
+            };
+        }
+        Database::ceil_number_to_page_size_multiple(new_reserved)
+    }
+}
+
+/// Which hole a region goes to when created or moved, among those big enough.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HoleFit {
+    /// The smallest one, keeps big holes for big regions.
+    #[default]
+    BestFit,
+    /// The largest one, leaves bigger remainders for regions that keep growing.
+    WorstFit,
+    /// The one closest to the start of the file.
+    FirstFit,
+}
+
+/// Options for `Database::open_with`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DatabaseOptions {
+    /// Minimum initial size of the data file, in bytes.
+    pub min_len: u64,
+    /// How regions grow when a write doesn't fit in their reserved space.
+    pub growth_policy: GrowthPolicy,
+    /// How long to keep retrying when another process holds the lock, fails right away if unset.
+    pub lock_timeout: Option<Duration>,
+    /// Which hole regions are placed in when they're created or moved.
+    pub hole_fit: HoleFit,
+}
+
+impl DatabaseOptions {
+    pub fn with_min_len(mut self, min_len: u64) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    pub fn with_growth_policy(mut self, growth_policy: GrowthPolicy) -> Self {
+        self.growth_policy = growth_policy;
+        self
+    }
+
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_hole_fit(mut self, hole_fit: HoleFit) -> Self {
+        self.hole_fit = hole_fit;
+        self
+    }
+}
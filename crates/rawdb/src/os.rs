@@ -0,0 +1,171 @@
+#[cfg(windows)]
+use std::io;
+use std::{
+    fs::{File, OpenOptions},
+    path::Path,
+};
+
+use memmap2::MmapMut;
+
+use crate::Result;
+
+#[cfg(unix)]
+pub use std::os::unix::fs::FileExt;
+
+/// Opens a file that no other process can open for writing until it's closed.
+///
+/// Takes an advisory lock on unix. Locks are mandatory on Windows and would also block the
+/// database's own read only handles, so write sharing is denied when opening instead.
+pub fn open_exclusive(options: &mut OpenOptions, path: &Path) -> Result<File> {
+    #[cfg(windows)]
+    {
+        use std::{fs::TryLockError, os::windows::fs::OpenOptionsExt};
+        use windows_sys::Win32::{
+            Foundation::ERROR_SHARING_VIOLATION,
+            Storage::FileSystem::{FILE_SHARE_DELETE, FILE_SHARE_READ},
+        };
+
+        options
+            .share_mode(FILE_SHARE_READ | FILE_SHARE_DELETE)
+            .open(path)
+            .map_err(|e| {
+                if e.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32) {
+                    TryLockError::WouldBlock.into()
+                } else {
+                    e.into()
+                }
+            })
+    }
+
+    #[cfg(not(windows))]
+    {
+        let file = options.open(path)?;
+        file.try_lock()?;
+        Ok(file)
+    }
+}
+
+/// Best effort flush of the file's data before remapping it.
+pub fn fsync(file: &File) {
+    #[cfg(unix)]
+    unsafe {
+        use std::os::fd::AsRawFd;
+
+        libc::fsync(file.as_raw_fd());
+    }
+
+    #[cfg(not(unix))]
+    let _ = file.sync_data();
+}
+
+/// Makes the creation or removal of files in the directory at `path` durable.
+///
+/// Windows can't open directories as files and doesn't need it, so it does nothing there.
+pub fn sync_dir(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(path)?.sync_all()?;
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Releases the mapping before the file is resized, which Windows refuses while it's mapped.
+///
+/// The caller must remap the file once resized. Does nothing on other platforms.
+pub fn unmap_before_resize(mmap: &mut MmapMut) -> Result<()> {
+    #[cfg(windows)]
+    {
+        *mmap = memmap2::MmapOptions::new().len(1).map_anon()?;
+    }
+
+    #[cfg(not(windows))]
+    let _ = mmap;
+
+    Ok(())
+}
+
+/// Bytes allocated on disk for the file at `path`, holes excluded.
+pub fn allocated_size(file: &File, path: &Path) -> Result<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let _ = path;
+        Ok(file.metadata()?.blocks() * 512)
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        use windows_sys::Win32::{
+            Foundation::{GetLastError, NO_ERROR},
+            Storage::FileSystem::{GetCompressedFileSizeW, INVALID_FILE_SIZE},
+        };
+
+        let _ = file;
+        let path = path
+            .as_os_str()
+            .encode_wide()
+            .chain([0])
+            .collect::<Vec<_>>();
+        let mut high = 0;
+        // Reports the allocated size of sparse files rather than their length
+        let low = unsafe { GetCompressedFileSizeW(path.as_ptr(), &mut high) };
+        if low == INVALID_FILE_SIZE && unsafe { GetLastError() } != NO_ERROR {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(((high as u64) << 32) | low as u64)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        Ok(file.metadata()?.len())
+    }
+}
+
+/// Positional reads and writes, mirroring `std::os::unix::fs::FileExt`.
+#[cfg(windows)]
+pub trait FileExt {
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+    fn write_all_at(&self, buf: &[u8], offset: u64) -> io::Result<()>;
+}
+
+#[cfg(windows)]
+impl FileExt for File {
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        use std::os::windows::fs::FileExt;
+
+        while !buf.is_empty() {
+            match self.seek_read(buf, offset) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_all_at(&self, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+        use std::os::windows::fs::FileExt;
+
+        while !buf.is_empty() {
+            match self.seek_write(buf, offset) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    buf = &buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
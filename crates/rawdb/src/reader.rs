@@ -1,5 +1,8 @@
+use std::ops::Deref;
+
 use memmap2::MmapMut;
 use parking_lot::RwLockReadGuard;
+use zerocopy::{FromBytes, Immutable, KnownLayout};
 
 use crate::RegionMetadata;
 
@@ -10,8 +13,8 @@ use crate::RegionMetadata;
 /// is complete to avoid blocking writes.
 #[derive(Debug)]
 pub struct Reader<'a> {
-    mmap: RwLockReadGuard<'a, MmapMut>,
-    region_meta: RwLockReadGuard<'a, RegionMetadata>,
+    mmap: Guard<'a, MmapMut>,
+    region_meta: Guard<'a, RegionMetadata>,
 }
 
 impl<'a> Reader<'a> {
@@ -20,7 +23,19 @@ impl<'a> Reader<'a> {
         mmap: RwLockReadGuard<'a, MmapMut>,
         region_meta: RwLockReadGuard<'a, RegionMetadata>,
     ) -> Self {
-        Self { mmap, region_meta }
+        Self {
+            mmap: Guard::Locked(mmap),
+            region_meta: Guard::Locked(region_meta),
+        }
+    }
+
+    /// Reader over locks already held elsewhere, such as by a `ReadTxn`.
+    #[inline]
+    pub(crate) fn borrowed(mmap: &'a MmapMut, region_meta: &'a RegionMetadata) -> Self {
+        Self {
+            mmap: Guard::Borrowed(mmap),
+            region_meta: Guard::Borrowed(region_meta),
+        }
     }
 
     #[inline(always)]
@@ -30,6 +45,7 @@ impl<'a> Reader<'a> {
         &self.mmap[start as usize..end as usize]
     }
 
+    /// Panics if the range is past the region's length, see `try_read`.
     #[inline(always)]
     pub fn read(&self, offset: u64, len: u64) -> &[u8] {
         assert!(offset + len <= self.region_meta.len());
@@ -41,14 +57,74 @@ impl<'a> Reader<'a> {
         self.read(0, self.region_meta.len())
     }
 
+    /// Same as `read` but returns `None` instead of panicking when the range goes past the
+    /// region's length, or past the data file if it's shorter than the region claims.
+    #[inline]
+    pub fn try_read(&self, offset: u64, len: u64) -> Option<&[u8]> {
+        let end = offset.checked_add(len)?;
+        if end > self.region_meta.len() {
+            return None;
+        }
+        let start = self.region_meta.start() + offset;
+        self.mmap.get(start as usize..(start + len) as usize)
+    }
+
+    #[inline]
+    pub fn try_read_all(&self) -> Option<&[u8]> {
+        self.try_read(0, self.region_meta.len())
+    }
+
     #[inline(always)]
     pub fn prefixed(&self, offset: u64) -> &[u8] {
         let start = self.region_meta.start() + offset;
         &self.mmap[start as usize..]
     }
 
+    /// Iterates over the region as packed fixed-size records of `T`, by value.
+    ///
+    /// Trailing bytes that don't form a whole record are ignored.
+    pub fn records<T>(&self) -> impl Iterator<Item = T> + '_
+    where
+        T: FromBytes,
+    {
+        let size = size_of::<T>();
+        assert!(size > 0, "Records can't be zero-sized");
+        self.read_all()
+            .chunks_exact(size)
+            .map(|chunk| T::read_from_bytes(chunk).unwrap())
+    }
+
+    /// Borrows `count` packed values of `T` starting `offset` bytes into the region, without copying.
+    ///
+    /// Only valid for data stored as plain values, such as `Format::Raw` vecs, never for
+    /// compressed pages. Panics if the range is past the region's length or isn't aligned for `T`.
+    pub fn read_typed_slice<T>(&self, offset: u64, count: usize) -> &[T]
+    where
+        T: FromBytes + Immutable + KnownLayout,
+    {
+        let bytes = self.read(offset, (count * size_of::<T>()) as u64);
+        <[T]>::ref_from_bytes(bytes).expect("Typed slice isn't aligned for its type")
+    }
+
     #[inline]
     pub fn region_meta(&self) -> &RegionMetadata {
         &self.region_meta
     }
 }
+
+#[derive(Debug)]
+enum Guard<'a, T> {
+    Locked(RwLockReadGuard<'a, T>),
+    Borrowed(&'a T),
+}
+
+impl<T> Deref for Guard<'_, T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Locked(guard) => guard,
+            Self::Borrowed(value) => value,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::{fs::File, mem, ops::Deref, sync::Arc};
 use memmap2::MmapMut;
 use parking_lot::{RwLock, RwLockReadGuard};
 
-use crate::{Database, Error, Result, WeakDatabase};
+use crate::{Database, Error, RegionCursor, Result, WeakDatabase};
 
 use super::{PAGE_SIZE, Reader};
 
@@ -32,11 +32,24 @@ pub struct RegionMetadata {
     reserved: u64,
     /// Unique identifier for the region.
     id: String,
+    /// CRC-32 of the region's data, 0 when unknown (databases written before checksums).
+    checksum: u32,
+    /// Whether `checksum` is outdated, recomputed on flush (not serialized).
+    checksum_stale: bool,
+    /// Opaque user bytes, zeroed by default.
+    attr: [u8; SIZE_OF_REGION_ATTR],
     /// Dirty flag for tracking changes (not serialized).
     dirty: bool,
 }
 
 pub const SIZE_OF_REGION_METADATA: usize = PAGE_SIZE as usize; // 4096 bytes for atomic writes
+pub const SIZE_OF_REGION_ATTR: usize = 16;
+/// The checksum and attr live in the last bytes of the slot, which older versions left zeroed.
+const CHECKSUM_OFFSET: usize = SIZE_OF_REGION_METADATA - 4;
+const ATTR_OFFSET: usize = CHECKSUM_OFFSET - SIZE_OF_REGION_ATTR;
+/// Longest region id in bytes, well within the slot's room between the lengths and the attr.
+pub const MAX_REGION_ID_LEN: usize = 1024;
+const _: () = assert!(MAX_REGION_ID_LEN <= ATTR_OFFSET - 32);
 
 impl Region {
     pub fn new(
@@ -72,11 +85,18 @@ impl Region {
         &self.meta
     }
 
+    /// Panics if the database was dropped.
     #[inline(always)]
     pub fn db(&self) -> Database {
         self.db.upgrade()
     }
 
+    /// Same as `db` but errors with `DatabaseDropped` instead of panicking.
+    #[inline(always)]
+    pub fn try_db(&self) -> Result<Database> {
+        self.db.try_upgrade().ok_or(Error::DatabaseDropped)
+    }
+
     pub fn create_reader(&self) -> Reader<'static> {
         let db = self.db();
         let mmap: RwLockReadGuard<'static, MmapMut> = unsafe { mem::transmute(db.mmap.read()) };
@@ -85,29 +105,77 @@ impl Region {
         Reader::new(mmap, region_meta)
     }
 
+    /// Copies the region's bytes starting at `at` into `buf`, returning how many were copied.
+    ///
+    /// Fewer than `buf.len()` are copied if the region ends first, none if `at` is past it.
+    pub fn read_into(&self, at: u64, buf: &mut [u8]) -> Result<usize> {
+        let db = self.try_db()?;
+        let mmap = db.mmap.read();
+        let region_meta = self.meta.read();
+
+        let len = region_meta.len().saturating_sub(at).min(buf.len() as u64) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let start = (region_meta.start() + at) as usize;
+        buf[..len].copy_from_slice(&mmap[start..start + len]);
+        Ok(len)
+    }
+
+    /// `Read` + `Seek` cursor over the region's data, see `RegionCursor`.
+    pub fn reader_cursor(&self) -> RegionCursor {
+        RegionCursor::new(self.clone())
+    }
+
     pub fn open_db_read_only_file(&self) -> Result<File> {
-        self.db().open_read_only_file()
+        self.try_db()?.open_read_only_file()
     }
 
     pub fn write_all_at(&self, data: &[u8], at: u64) -> Result<()> {
-        self.db().write_all_to_region_at(self, data, at)
+        self.try_db()?.write_all_to_region_at(self, data, at)
     }
 
     pub fn truncate(&self, from: u64) -> Result<()> {
-        self.db().truncate_region(self, from)
+        self.try_db()?.truncate_region(self, from)
     }
 
     pub fn truncate_write_all(&self, from: u64, data: &[u8]) -> Result<()> {
-        self.db().truncate_write_all_to_region(self, from, data)
+        self.try_db()?
+            .truncate_write_all_to_region(self, from, data)
+    }
+
+    /// Grows the reserved space to `bytes` in one go, see `Database::reserve_region`.
+    pub fn reserve(&self, bytes: u64) -> Result<()> {
+        self.try_db()?.reserve_region(self, bytes)
+    }
+
+    /// Releases the reserved space past the region's length, see `Database::shrink_region`.
+    pub fn shrink_to_fit(&self) -> Result<()> {
+        self.try_db()?.shrink_region(self)
+    }
+
+    /// User bytes stored alongside the region's metadata.
+    #[inline]
+    pub fn attr(&self) -> [u8; SIZE_OF_REGION_ATTR] {
+        self.meta.read().attr()
+    }
+
+    /// Sets the user bytes, persisted on the next flush.
+    #[inline]
+    pub fn set_attr(&self, attr: [u8; SIZE_OF_REGION_ATTR]) -> Result<()> {
+        self.try_db()?.check_writable()?;
+        self.meta.write().set_attr(attr);
+        Ok(())
     }
 
     pub fn rename(&self, new_id: &str) -> Result<()> {
         let old_id = self.meta().read().id().to_string();
-        self.db().rename_region(&old_id, new_id)
+        self.try_db()?.rename_region(&old_id, new_id)
     }
 
     pub fn remove(self) -> Result<()> {
-        self.db().remove_region(self)?;
+        self.try_db()?.remove_region(self)?;
         Ok(())
     }
 }
@@ -115,7 +183,10 @@ impl Region {
 impl RegionMetadata {
     fn validate_id(id: &str) {
         assert!(!id.is_empty(), "Region id must not be empty");
-        assert!(id.len() <= 1024, "Region id must be <= 1024 bytes");
+        assert!(
+            id.len() <= MAX_REGION_ID_LEN,
+            "Region id must be <= {MAX_REGION_ID_LEN} bytes"
+        );
         assert!(
             !id.chars().any(|c| c.is_control()),
             "Region id must not contain control characters"
@@ -134,6 +205,9 @@ impl RegionMetadata {
             len,
             reserved,
             start,
+            checksum: 0,
+            checksum_stale: false,
+            attr: [0; SIZE_OF_REGION_ATTR],
             dirty: true,
         }
     }
@@ -188,6 +262,41 @@ impl RegionMetadata {
         self.dirty = true;
     }
 
+    #[inline(always)]
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    #[inline]
+    pub fn set_checksum(&mut self, checksum: u32) {
+        self.checksum = checksum;
+        self.checksum_stale = false;
+        self.dirty = true;
+    }
+
+    #[inline(always)]
+    pub fn is_checksum_stale(&self) -> bool {
+        self.checksum_stale
+    }
+
+    /// Marks the checksum as outdated, it's then persisted as unknown until recomputed.
+    #[inline]
+    pub fn mark_checksum_stale(&mut self) {
+        self.checksum_stale = true;
+        self.dirty = true;
+    }
+
+    #[inline(always)]
+    pub fn attr(&self) -> [u8; SIZE_OF_REGION_ATTR] {
+        self.attr
+    }
+
+    #[inline]
+    pub fn set_attr(&mut self, attr: [u8; SIZE_OF_REGION_ATTR]) {
+        self.attr = attr;
+        self.dirty = true;
+    }
+
     #[inline(always)]
     pub fn is_dirty(&self) -> bool {
         self.dirty
@@ -219,6 +328,13 @@ impl RegionMetadata {
         let id_len = id_bytes.len();
         bytes[24..32].copy_from_slice(&(id_len as u64).to_le_bytes());
         bytes[32..32 + id_len].copy_from_slice(id_bytes);
+        bytes[ATTR_OFFSET..CHECKSUM_OFFSET].copy_from_slice(&self.attr);
+        let checksum = if self.checksum_stale {
+            0
+        } else {
+            self.checksum
+        };
+        bytes[CHECKSUM_OFFSET..].copy_from_slice(&checksum.to_le_bytes());
 
         bytes
     }
@@ -237,8 +353,14 @@ impl RegionMetadata {
         let reserved = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
         let id_len = u64::from_le_bytes(bytes[24..32].try_into().unwrap()) as usize;
 
+        if id_len > ATTR_OFFSET - 32 {
+            return Err(Error::InvalidRegionId);
+        }
+
         let id = String::from_utf8(bytes[32..32 + id_len].to_vec())
             .map_err(|_| Error::InvalidRegionId)?;
+        let checksum = u32::from_le_bytes(bytes[CHECKSUM_OFFSET..].try_into().unwrap());
+        let attr = bytes[ATTR_OFFSET..CHECKSUM_OFFSET].try_into().unwrap();
 
         if start == 0 && len == 0 && reserved == 0 && id_len == 0 {
             return Err(Error::EmptyMetadata);
@@ -250,6 +372,9 @@ impl RegionMetadata {
             start,
             len,
             reserved,
+            checksum,
+            checksum_stale: false,
+            attr,
             dirty: false,
         })
     }
@@ -5,15 +5,15 @@ use std::{
     sync::Arc,
 };
 
-use std::os::unix::fs::FileExt;
+use log::warn;
 
-use crate::{Database, Error, RegionMetadata, Result};
-
-use super::{
-    PAGE_SIZE,
-    region::{Region, SIZE_OF_REGION_METADATA},
+use crate::{
+    Database, Error, PAGE_SIZE, RegionMetadata, Result,
+    os::{self, FileExt},
 };
 
+use super::region::{Region, SIZE_OF_REGION_METADATA};
+
 #[derive(Debug)]
 pub struct Regions {
     id_to_index: HashMap<String, usize>,
@@ -26,13 +26,28 @@ impl Regions {
     pub fn open(parent: &Path) -> Result<Self> {
         fs::create_dir_all(parent)?;
 
-        let file = OpenOptions::new()
-            .read(true)
-            .create(true)
-            .write(true)
-            .truncate(false)
-            .open(parent.join("regions"))?;
-        file.try_lock()?;
+        let file = os::open_exclusive(
+            OpenOptions::new()
+                .read(true)
+                .create(true)
+                .write(true)
+                .truncate(false),
+            &parent.join("regions"),
+        )?;
+
+        let file_len = file.metadata()?.len();
+
+        Ok(Self {
+            id_to_index: HashMap::new(),
+            index_to_region: vec![],
+            file,
+            file_len,
+        })
+    }
+
+    /// Opens the metadata file without write access nor locking, see `Database::open_read_only`.
+    pub fn open_read_only(parent: &Path) -> Result<Self> {
+        let file = OpenOptions::new().read(true).open(parent.join("regions"))?;
 
         let file_len = file.metadata()?.len();
 
@@ -69,6 +84,89 @@ impl Regions {
         Ok(())
     }
 
+    /// Best-effort version of `fill_index_to_region` for damaged metadata.
+    ///
+    /// A trailing partial slot is truncated away. Slots that can't be parsed, point outside
+    /// of `data_len`, overlap an earlier region or reuse an earlier id are logged and cleared.
+    /// Returns the number of slots that were dropped.
+    pub fn fill_index_to_region_recovering(
+        &mut self,
+        db: &Database,
+        data_len: u64,
+    ) -> Result<usize> {
+        let slot_size = SIZE_OF_REGION_METADATA as u64;
+        if !self.file_len.is_multiple_of(slot_size) {
+            warn!("Truncating partial region metadata slot");
+            self.file_len -= self.file_len % slot_size;
+            self.file.set_len(self.file_len)?;
+        }
+
+        let num_slots = (self.file_len / slot_size) as usize;
+        let mut dropped = vec![];
+        let mut candidates = vec![];
+
+        for index in 0..num_slots {
+            let mut buffer = vec![0; SIZE_OF_REGION_METADATA];
+            self.file
+                .read_exact_at(&mut buffer, index as u64 * slot_size)?;
+
+            match RegionMetadata::from_bytes(&buffer) {
+                Ok(meta) => {
+                    if !meta.start().is_multiple_of(PAGE_SIZE)
+                        || meta.len() > meta.reserved()
+                        || meta.start() + meta.reserved() > data_len
+                    {
+                        warn!("Dropping region '{}' with invalid bounds", meta.id());
+                        dropped.push(index);
+                    } else {
+                        candidates.push((index, meta));
+                    }
+                }
+                Err(Error::EmptyMetadata) => {}
+                Err(e) => {
+                    warn!("Dropping unreadable region metadata slot {index}: {e}");
+                    dropped.push(index);
+                }
+            }
+        }
+
+        // Lowest slots win on overlap or duplicate id
+        let mut accepted: Vec<(usize, RegionMetadata)> = vec![];
+        for (index, meta) in candidates {
+            let end = meta.start() + meta.reserved();
+            if let Some((_, other)) = accepted.iter().find(|(_, other)| {
+                other.id() == meta.id()
+                    || (meta.start() < other.start() + other.reserved() && other.start() < end)
+            }) {
+                warn!(
+                    "Dropping region '{}' conflicting with region '{}'",
+                    meta.id(),
+                    other.id()
+                );
+                dropped.push(index);
+            } else {
+                accepted.push((index, meta));
+            }
+        }
+
+        let empty = [0u8; SIZE_OF_REGION_METADATA];
+        for &index in &dropped {
+            self.file.write_all_at(&empty, index as u64 * slot_size)?;
+        }
+        if !dropped.is_empty() {
+            self.file.sync_data()?;
+        }
+
+        self.index_to_region
+            .resize_with(num_slots, Default::default);
+        for (index, meta) in accepted {
+            self.id_to_index.insert(meta.id().to_string(), index);
+            self.index_to_region[index] = Some(Region::from(db, index, meta));
+        }
+
+        Ok(dropped.len())
+    }
+
     pub fn set_min_len(&mut self, len: u64) -> Result<()> {
         if self.file_len < len {
             self.file.set_len(len)?;
@@ -77,7 +175,13 @@ impl Regions {
         Ok(())
     }
 
-    pub fn create_region(&mut self, db: &Database, id: String, start: u64) -> Result<Region> {
+    pub fn create_region(
+        &mut self,
+        db: &Database,
+        id: String,
+        start: u64,
+        reserved: u64,
+    ) -> Result<Region> {
         let index = self
             .index_to_region
             .iter()
@@ -86,7 +190,7 @@ impl Regions {
             .map(|(index, _)| index)
             .unwrap_or_else(|| self.index_to_region.len());
 
-        let region = Region::new(db, id.clone(), index, start, 0, PAGE_SIZE);
+        let region = Region::new(db, id.clone(), index, start, 0, reserved);
 
         self.set_min_len(((index + 1) * SIZE_OF_REGION_METADATA) as u64)?;
 
@@ -177,6 +281,19 @@ impl Regions {
         Ok(Some(region))
     }
 
+    /// Persists a single region's metadata slot if it's dirty.
+    pub fn flush_region(&self, region: &Region) -> Result<()> {
+        let mut region_meta = region.meta().write();
+        if region_meta.is_clean() {
+            return Ok(());
+        }
+        let start = (region.index() * SIZE_OF_REGION_METADATA) as u64;
+        self.file.write_all_at(&region_meta.to_bytes(), start)?;
+        self.file.sync_data()?;
+        region_meta.clear_dirty();
+        Ok(())
+    }
+
     pub fn flush(&self) -> Result<()> {
         let mut needs_sync = false;
 
@@ -0,0 +1,64 @@
+use std::{
+    fs::{self, OpenOptions},
+    io,
+    path::Path,
+};
+
+use crate::{Database, Error, Result, SIZE_OF_REGION_METADATA, os::FileExt};
+
+impl Database {
+    /// Copies the database to `dest` while it stays open, then opens and returns the copy.
+    ///
+    /// The copy holds every region as it is in memory, flushed or not. The memory map, the
+    /// layout and every region's metadata are read locked throughout, in lock order, which
+    /// excludes flushes, relocations and writes that update a region until the copy is done.
+    /// Appends may still land past a region's length and are left out of it.
+    ///
+    /// The data file is copied with `fs::copy`, which uses `copy_file_range` on Linux and
+    /// keeps holes sparse where the file system allows it. Errors if `dest` already holds a
+    /// database.
+    pub fn snapshot_to(&self, dest: &Path) -> Result<Database> {
+        let dest_data_path = Self::data_path_(dest);
+        if dest_data_path.exists() {
+            return Err(Error::IO(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", dest_data_path.display()),
+            )));
+        }
+
+        let mmap = self.mmap.read();
+        let regions = self.regions.read();
+        let layout = self.layout.read();
+        let metas = regions
+            .index_to_region()
+            .iter()
+            .map(|region| region.as_ref().map(|region| region.meta().read()))
+            .collect::<Vec<_>>();
+
+        fs::create_dir_all(dest)?;
+
+        let regions_file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dest.join("regions"))?;
+        regions_file.set_len((metas.len() * SIZE_OF_REGION_METADATA) as u64)?;
+        for (index, meta) in metas.iter().enumerate() {
+            // Removed regions' slots are left zeroed
+            if let Some(meta) = meta {
+                regions_file
+                    .write_all_at(&meta.to_bytes(), (index * SIZE_OF_REGION_METADATA) as u64)?;
+            }
+        }
+        regions_file.sync_all()?;
+
+        // The memory map is shared, so the file already holds every write
+        fs::copy(self.data_path(), &dest_data_path)?;
+
+        drop(metas);
+        drop(layout);
+        drop(regions);
+        drop(mmap);
+
+        Database::open(dest)
+    }
+}
@@ -0,0 +1,55 @@
+use serde_derive::Serialize;
+
+use crate::{Database, Result};
+
+/// Snapshot of how the data file is used, see `Database::stats`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DatabaseStats {
+    pub region_count: usize,
+    /// Sum of the length of every region
+    pub used_bytes: u64,
+    /// Sum of the reserved space of every region
+    pub reserved_bytes: u64,
+    /// Sum of the holes available for reuse, space freed since the last flush excluded
+    pub hole_bytes: u64,
+    pub file_len: u64,
+    pub largest_hole: u64,
+    /// `hole_bytes / file_len`, 0 for an empty file
+    pub fragmentation_ratio: f64,
+}
+
+impl Database {
+    /// Gathers the usage metrics of the database in one go, meant to be exported as is.
+    pub fn stats(&self) -> Result<DatabaseStats> {
+        let file_len = self.file_len()?;
+
+        let regions = self.regions.read();
+        let layout = self.layout.read();
+
+        let (region_count, used_bytes, reserved_bytes) = regions
+            .index_to_region()
+            .iter()
+            .flatten()
+            .fold((0, 0, 0), |(count, used, reserved), region| {
+                let meta = region.meta().read();
+                (count + 1, used + meta.len(), reserved + meta.reserved())
+            });
+
+        let hole_bytes = layout.start_to_hole().values().sum::<u64>();
+        let largest_hole = layout.largest_hole().map_or(0, |(_, gap)| gap);
+
+        Ok(DatabaseStats {
+            region_count,
+            used_bytes,
+            reserved_bytes,
+            hole_bytes,
+            file_len,
+            largest_hole,
+            fragmentation_ratio: if file_len == 0 {
+                0.0
+            } else {
+                hole_bytes as f64 / file_len as f64
+            },
+        })
+    }
+}
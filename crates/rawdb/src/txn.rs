@@ -0,0 +1,54 @@
+use memmap2::MmapMut;
+use parking_lot::RwLockReadGuard;
+
+use crate::{Error, Reader, Region, RegionMetadata, Result};
+
+/// Point-in-time read view over every region of a database.
+///
+/// Pins the memory map and snapshots the metadata (start, len) of every region
+/// when created, so readers built from it all see the same lengths even if
+/// vecs keep being appended to and flushed meanwhile.
+///
+/// This is not full isolation, the memory map is shared and isn't copied on write:
+///
+/// - Writes in place, below a region's snapshot length (`write_all_to_region_at`,
+///   `truncate_write_all_to_region`, ...) land in the same memory the transaction
+///   reads, so its readers see them.
+/// - A region that outgrows its reservation is copied to a new place and its old
+///   space becomes a pending hole, which is only reused after a flush. Snapshot
+///   bytes of moved regions thus stay valid as long as no flush happens while the
+///   transaction is alive.
+///
+/// Any write that needs to grow the file takes the memory map write lock and
+/// blocks until the transaction is dropped. On the thread holding the transaction
+/// such a write never returns, it deadlocks. Drop the transaction as soon as
+/// possible, and before writing from the same thread.
+#[derive(Debug)]
+pub struct ReadTxn<'a> {
+    mmap: RwLockReadGuard<'a, MmapMut>,
+    regions: Vec<Option<RegionMetadata>>,
+}
+
+impl<'a> ReadTxn<'a> {
+    pub(crate) fn new(
+        mmap: RwLockReadGuard<'a, MmapMut>,
+        regions: Vec<Option<RegionMetadata>>,
+    ) -> Self {
+        Self { mmap, regions }
+    }
+
+    /// Creates a reader serving the region's bytes as of the transaction start.
+    pub fn reader(&self, region: &Region) -> Result<Reader<'_>> {
+        let region_meta = self.region_meta(region).ok_or(Error::RegionNotFound)?;
+        Ok(Reader::borrowed(&self.mmap, region_meta))
+    }
+
+    /// Metadata of the region as of the transaction start, `None` if it was created after.
+    #[inline]
+    pub fn region_meta(&self, region: &Region) -> Option<&RegionMetadata> {
+        self.regions
+            .get(region.index())
+            .and_then(Option::as_ref)
+            .filter(|meta| meta.id() == region.meta().read().id())
+    }
+}
@@ -1,6 +1,8 @@
-use rawdb::{Database, PAGE_SIZE, Result};
+use rawdb::{Advice, Database, DatabaseOptions, Error, GrowthPolicy, HoleFit, PAGE_SIZE, Result};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use tempfile::TempDir;
 
 /// Helper to create a temporary test database
@@ -1115,6 +1117,35 @@ fn test_reader_bounds_check() {
     let _ = reader.read(0, 100);
 }
 
+#[test]
+fn test_reader_try_read() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("test")?;
+    db.write_all_to_region(&region, b"Short")?;
+
+    let reader = region.create_reader();
+
+    // In range
+    assert_eq!(reader.try_read(0, 2), Some(&b"Sh"[..]));
+    assert_eq!(reader.try_read(1, 3), Some(&b"hor"[..]));
+    assert_eq!(reader.try_read_all(), Some(&b"Short"[..]));
+
+    // Exactly at the boundary
+    assert_eq!(reader.try_read(0, 5), Some(&b"Short"[..]));
+    assert_eq!(reader.try_read(3, 2), Some(&b"rt"[..]));
+    assert_eq!(reader.try_read(5, 0), Some(&b""[..]));
+
+    // Past the end, including within the reserved space and overflowing ranges
+    assert_eq!(reader.try_read(0, 6), None);
+    assert_eq!(reader.try_read(5, 1), None);
+    assert_eq!(reader.try_read(100, 0), None);
+    assert_eq!(reader.try_read(1, u64::MAX), None);
+    assert_eq!(reader.try_read(u64::MAX, 1), None);
+
+    Ok(())
+}
+
 // ============================================================================
 // Extreme Cases
 // ============================================================================
@@ -1211,6 +1242,33 @@ fn test_retain_regions_edge_cases() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_retain_regions_large_set() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    (0..10_000).try_for_each(|i| -> Result<()> {
+        db.create_region_if_needed(&format!("region{i}"))?;
+        Ok(())
+    })?;
+
+    let keep = (0..10_000)
+        .filter(|i| i % 2 == 0)
+        .map(|i| format!("region{i}"))
+        .collect::<std::collections::HashSet<_>>();
+    db.retain_regions(keep.clone())?;
+
+    let survivors = db
+        .regions()
+        .id_to_index()
+        .keys()
+        .cloned()
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(survivors, keep);
+    assert_eq!(db.layout().start_to_region().len(), 5_000);
+
+    Ok(())
+}
+
 // ============================================================================
 // Layout and Hole Management
 // ============================================================================
@@ -1859,6 +1917,40 @@ fn test_rename_to_existing_name_fails() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_rename_region_keeps_data_in_place() -> Result<()> {
+    let temp = TempDir::new()?;
+    let path = temp.path();
+
+    let (start, reserved, len) = {
+        let db = Database::open(path)?;
+        let _before = db.create_region_if_needed("before")?;
+        let region = db.create_region_if_needed("original")?;
+        db.write_all_to_region(&region, b"Stays put")?;
+
+        assert!(db.rename_region("missing", "other").is_err());
+
+        db.rename_region("original", "renamed")?;
+        db.flush()?;
+
+        let meta = region.meta().read();
+        (meta.start(), meta.reserved(), meta.len())
+    };
+
+    let db = Database::open(path)?;
+    assert!(db.get_region("original").is_none());
+    let region = db.get_region("renamed").unwrap();
+    {
+        let meta = region.meta().read();
+        assert_eq!(meta.start(), start);
+        assert_eq!(meta.reserved(), reserved);
+        assert_eq!(meta.len(), len);
+    }
+    assert_eq!(region.create_reader().read_all(), b"Stays put");
+
+    Ok(())
+}
+
 #[test]
 fn test_rename_after_remove_and_recreate() -> Result<()> {
     let (db, _temp) = setup_test_db()?;
@@ -2022,3 +2114,1579 @@ fn test_concurrent_renames() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_total_logical_and_reserved_size() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    assert_eq!(db.total_logical_size(), 0);
+    assert_eq!(db.total_reserved_size(), 0);
+    assert_eq!(db.total_hole_size(), 0);
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_if_needed("region2")?;
+
+    db.write_all_to_region(&region1, &[1; 100])?;
+    db.write_all_to_region(&region2, &vec![2; PAGE_SIZE as usize + 1])?;
+
+    assert_eq!(db.total_logical_size(), 100 + PAGE_SIZE + 1);
+    assert_eq!(db.total_reserved_size(), PAGE_SIZE + 2 * PAGE_SIZE);
+
+    drop(region1);
+    db.remove_region_with_id("region1")?;
+
+    assert_eq!(db.total_logical_size(), PAGE_SIZE + 1);
+    assert_eq!(db.total_reserved_size(), 2 * PAGE_SIZE);
+    assert!(!db.disk_usage().is_empty());
+
+    // The removed region's space becomes a hole on flush
+    assert_eq!(db.total_hole_size(), 0);
+    db.flush()?;
+    assert_eq!(db.total_hole_size(), PAGE_SIZE);
+
+    Ok(())
+}
+
+#[test]
+fn test_read_txn_snapshot() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_if_needed("region2")?;
+    db.write_all_to_region(&region1, &[1, 2, 3])?;
+    drop(region2);
+    db.remove_region_with_id("region2")?;
+    db.flush()?;
+
+    let txn = db.read_txn();
+
+    // Reuses the hole left by region2, no file growth needed
+    let region3 = db.create_region_if_needed("region3")?;
+    db.write_all_to_region(&region1, &[6, 7])?;
+    db.truncate_region(&region1, 4)?;
+
+    assert_eq!(txn.reader(&region1)?.read_all(), &[1, 2, 3]);
+    assert!(txn.reader(&region3).is_err());
+
+    // Writes in place aren't isolated
+    db.write_all_to_region_at(&region1, &[9], 0)?;
+    assert_eq!(txn.reader(&region1)?.read_all(), &[9, 2, 3]);
+    drop(txn);
+
+    assert_eq!(region1.create_reader().read_all(), &[9, 2, 3, 6]);
+
+    Ok(())
+}
+
+#[test]
+fn test_flush_durable_persistence() -> Result<()> {
+    let temp = TempDir::new()?;
+    let path = temp.path();
+
+    {
+        let db = Database::open(path)?;
+        let region = db.create_region_if_needed("durable")?;
+        db.write_all_to_region(&region, &vec![7; 3 * PAGE_SIZE as usize])?;
+        db.flush_durable()?;
+    }
+
+    {
+        let db = Database::open(path)?;
+        let region = db.get_region("durable").expect("Region should persist");
+        assert_eq!(region.meta().read().len(), 3 * PAGE_SIZE);
+        assert!(db.file_len()? >= 4 * PAGE_SIZE);
+        assert!(region.create_reader().read_all().iter().all(|&b| b == 7));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_create_region_with_capacity() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let small = db.create_region_if_needed("small")?;
+    let big = db.create_region_with_capacity("big", 10 * PAGE_SIZE + 1)?;
+    let after = db.create_region_if_needed("after")?;
+
+    // Rounded up to a page multiple
+    assert_eq!(big.meta().read().reserved(), 11 * PAGE_SIZE);
+    assert_eq!(big.meta().read().start(), PAGE_SIZE);
+    assert_eq!(after.meta().read().start(), 12 * PAGE_SIZE);
+    assert_eq!(small.meta().read().reserved(), PAGE_SIZE);
+
+    // Existing regions are returned untouched
+    let same = db.create_region_with_capacity("small", 100 * PAGE_SIZE)?;
+    assert_eq!(same.meta().read().reserved(), PAGE_SIZE);
+
+    // Writes within the capacity don't move the region
+    for _ in 0..10 {
+        db.write_all_to_region(&big, &[1; PAGE_SIZE as usize])?;
+    }
+    let meta = big.meta().read();
+    assert_eq!(meta.start(), PAGE_SIZE);
+    assert_eq!(meta.reserved(), 11 * PAGE_SIZE);
+    assert_eq!(meta.len(), 10 * PAGE_SIZE);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_counters() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let _region2 = db.create_region_if_needed("region2")?;
+
+    db.write_all_to_region(&region1, &[1; 100])?;
+    db.write_all_to_region(&region1, &[2; 100])?;
+    assert_eq!(db.in_place_writes(), 2);
+    assert_eq!(db.relocations(), 0);
+
+    // Doesn't fit and region2 is right after, so region1 has to move
+    db.write_all_to_region(&region1, &[3; PAGE_SIZE as usize])?;
+    assert_eq!(db.in_place_writes(), 2);
+    assert_eq!(db.relocations(), 1);
+
+    // Now last, grows in place
+    db.write_all_to_region(&region1, &[4; 2 * PAGE_SIZE as usize])?;
+    assert_eq!(db.in_place_writes(), 3);
+    assert_eq!(db.relocations(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_reader_records() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("records")?;
+    let values = [1_u32, 2, 3, 0xDEADBEEF];
+    let bytes = values
+        .iter()
+        .flat_map(|v| v.to_ne_bytes())
+        .collect::<Vec<_>>();
+    db.write_all_to_region(&region, &bytes)?;
+
+    let reader = region.create_reader();
+    assert_eq!(reader.records::<u32>().collect::<Vec<_>>(), values);
+    drop(reader);
+
+    // Trailing partial record is ignored
+    db.write_all_to_region(&region, &[7, 7])?;
+    let reader = region.create_reader();
+    assert_eq!(reader.records::<u32>().count(), 4);
+    assert_eq!(reader.records::<u16>().count(), 9);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_all_to_region_sparse() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("sparse")?;
+
+    // Strict method still refuses to leave a gap
+    assert!(db.write_all_to_region_at(&region, &[1, 2, 3], 100).is_err());
+
+    db.write_all_to_region_sparse(&region, &[1, 2, 3], 100)?;
+    assert_eq!(region.meta().read().len(), 103);
+
+    let reader = region.create_reader();
+    assert!(reader.read(0, 100).iter().all(|&b| b == 0));
+    assert_eq!(reader.read(100, 3), &[1, 2, 3]);
+    drop(reader);
+
+    // Within the current length, behaves like a regular write
+    db.write_all_to_region_sparse(&region, &[9], 50)?;
+    let reader = region.create_reader();
+    assert_eq!(region.meta().read().len(), 103);
+    assert_eq!(reader.read(49, 3), &[0, 9, 0]);
+    drop(reader);
+
+    // Stale bytes left past the length by a truncation read back as zeros too
+    db.write_all_to_region(&region, &[7; 2 * PAGE_SIZE as usize])?;
+    db.truncate_region(&region, 10)?;
+    db.write_all_to_region_sparse(&region, &[4], 3 * PAGE_SIZE)?;
+    let reader = region.create_reader();
+    assert!(reader.read(10, 3 * PAGE_SIZE - 10).iter().all(|&b| b == 0));
+    assert_eq!(reader.read(3 * PAGE_SIZE, 1), &[4]);
+    drop(reader);
+
+    // A gap far past the end costs neither memory nor writes
+    let far = 64 * 1024 * 1024 * 1024;
+    db.write_all_to_region_sparse(&region, &[5], far)?;
+    assert_eq!(region.meta().read().len(), far + 1);
+    let reader = region.create_reader();
+    assert_eq!(reader.read(far - 1, 2), &[0, 5]);
+    drop(reader);
+    assert!(db.disk_usage_bytes()? < 1024 * 1024 * 1024);
+
+    Ok(())
+}
+
+#[test]
+fn test_remove_region_with_range_and_create_region_at() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let _region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_with_capacity("region2", 2 * PAGE_SIZE)?;
+    let _region3 = db.create_region_if_needed("region3")?;
+
+    let range = db.remove_region_with_range(region2)?;
+    assert_eq!(range, Some((PAGE_SIZE, 2 * PAGE_SIZE)));
+    let (start, reserved) = range.unwrap();
+
+    // Not reusable before flush
+    assert!(matches!(
+        db.create_region_at("new", start, reserved),
+        Err(Error::RangeNotFree { .. })
+    ));
+
+    db.flush()?;
+
+    // Occupied range
+    assert!(db.create_region_at("new", 0, PAGE_SIZE).is_err());
+    // Overflows the hole
+    assert!(db.create_region_at("new", start, 3 * PAGE_SIZE).is_err());
+
+    let region = db.create_region_at("new", start, reserved)?;
+    assert_eq!(region.meta().read().start(), start);
+    assert_eq!(region.meta().read().reserved(), reserved);
+    assert!(db.layout().start_to_hole().is_empty());
+
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_async_flush_and_compact() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &[1; 100])?;
+
+    runtime.block_on(async {
+        db.flush_async().await?;
+        db.remove_region(region)?;
+        db.compact_async().await
+    })?;
+
+    assert!(db.get_region("region").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_open_reporting() -> Result<()> {
+    let temp = TempDir::new()?;
+
+    let open = Database::open_reporting(temp.path())?;
+    assert!(open.created);
+    let region = open.db.create_region_if_needed("region")?;
+    open.db.write_all_to_region(&region, &[1, 2, 3])?;
+    open.db.flush()?;
+    drop(region);
+    drop(open);
+
+    let open = Database::open_reporting(temp.path())?;
+    assert!(!open.created);
+    assert!(open.db.get_region("region").is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_fsck() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+    db.fsck()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_if_needed("region2")?;
+    let region3 = db.create_region_if_needed("region3")?;
+    db.fsck()?;
+
+    // Move region1 to the end and leave a pending hole behind
+    db.write_all_to_region(&region1, &[1; 2 * PAGE_SIZE as usize])?;
+    db.fsck()?;
+
+    db.remove_region(region2)?;
+    db.fsck()?;
+
+    // Pending holes get promoted and coalesced
+    db.flush()?;
+    db.fsck()?;
+
+    db.write_all_to_region(&region3, &[3; 10])?;
+    db.flush()?;
+    drop((region1, region3));
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    db.fsck()?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_open_recover() -> Result<()> {
+    use std::{fs::OpenOptions, os::unix::fs::FileExt};
+
+    let temp = TempDir::new()?;
+
+    {
+        let db = Database::open(temp.path())?;
+        for name in ["region0", "region1", "region2"] {
+            let region = db.create_region_if_needed(name)?;
+            db.write_all_to_region(&region, name.as_bytes())?;
+        }
+        db.flush()?;
+    }
+
+    let slot = PAGE_SIZE;
+    let regions = OpenOptions::new()
+        .write(true)
+        .open(temp.path().join("regions"))?;
+    // Absurd id length in region1's slot
+    regions.write_all_at(&u64::MAX.to_le_bytes(), slot + 24)?;
+    // region2 now overlaps region0
+    regions.write_all_at(&0_u64.to_le_bytes(), 2 * slot)?;
+    // Partial trailing slot
+    regions.write_all_at(&[1; 10], 3 * slot)?;
+    drop(regions);
+
+    let db = Database::open_recover(temp.path())?;
+    assert!(db.get_region("region1").is_none());
+    assert!(db.get_region("region2").is_none());
+    let region0 = db.get_region("region0").unwrap();
+    assert_eq!(region0.create_reader().read_all(), b"region0");
+    db.fsck()?;
+
+    // Recovered space is reusable and the repaired metadata reopens normally
+    db.create_region_if_needed("region3")?;
+    db.flush()?;
+    drop((region0, db));
+
+    let db = Database::open(temp.path())?;
+    assert!(db.get_region("region0").is_some());
+    assert!(db.get_region("region3").is_some());
+    db.fsck()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_region_observer() -> Result<()> {
+    use parking_lot::Mutex;
+    use rawdb::RegionObserver;
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<String>>);
+
+    impl RegionObserver for Events {
+        fn on_create(&self, id: &str, index: usize) {
+            self.0.lock().push(format!("create {id} {index}"));
+        }
+
+        fn on_remove(&self, id: &str, index: usize) {
+            self.0.lock().push(format!("remove {id} {index}"));
+        }
+
+        fn on_relocate(&self, id: &str, old_start: u64, new_start: u64) {
+            self.0
+                .lock()
+                .push(format!("relocate {id} {old_start} {new_start}"));
+        }
+    }
+
+    let temp = TempDir::new()?;
+    let events = Arc::new(Events::default());
+    let db = Database::open_with_observer(temp.path(), events.clone())?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_if_needed("region2")?;
+    // Existing regions don't trigger anything
+    db.create_region_if_needed("region1")?;
+
+    // Moves to the end of the file
+    db.write_all_to_region(&region1, &[1; PAGE_SIZE as usize + 1])?;
+
+    db.remove_region(region2)?;
+
+    let region3 = db.create_region_if_needed("region3")?;
+    let region3_index = region3.index();
+    drop(region3);
+    db.retain_regions(["region1".to_string()].into_iter().collect())?;
+
+    assert_eq!(
+        *events.0.lock(),
+        vec![
+            "create region1 0".to_string(),
+            "create region2 1".to_string(),
+            format!("relocate region1 0 {}", 2 * PAGE_SIZE),
+            "remove region2 1".to_string(),
+            format!("create region3 {region3_index}"),
+            format!("remove region3 {region3_index}"),
+        ]
+    );
+
+    drop(region1);
+
+    Ok(())
+}
+
+#[test]
+fn test_defragment_relocates_regions() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let regions = (0..10_u8)
+        .map(|i| {
+            let region = db.create_region_if_needed(&format!("region{i}"))?;
+            db.write_all_to_region(&region, &[i; 1000])?;
+            Ok(region)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    db.flush()?;
+
+    let kept = regions
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, region)| {
+            if i % 2 == 1 {
+                db.remove_region(region).unwrap();
+                None
+            } else {
+                Some((i as u8, region))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let len_before = db.file_len()?;
+    db.defragment()?;
+
+    assert!(db.layout().start_to_hole().is_empty());
+    assert_eq!(db.file_len()?, 5 * PAGE_SIZE);
+    assert!(db.file_len()? < len_before);
+    db.fsck()?;
+
+    for (i, region) in &kept {
+        assert_eq!(region.meta().read().start(), *i as u64 / 2 * PAGE_SIZE);
+        assert_eq!(region.create_reader().read_all(), [*i; 1000]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_defragment_step() -> Result<()> {
+    let setup = || -> Result<(Database, TempDir)> {
+        let (db, temp) = setup_test_db()?;
+        for i in 0..10_u8 {
+            let region = db.create_region_if_needed(&format!("region{i}"))?;
+            db.write_all_to_region(&region, &[i; 1000])?;
+        }
+        db.flush()?;
+        for i in (1..10).step_by(2) {
+            db.remove_region_with_id(&format!("region{i}"))?;
+        }
+        Ok((db, temp))
+    };
+
+    let (compacted, _temp) = setup()?;
+    compacted.defragment()?;
+
+    let (db, _temp) = setup()?;
+    let mut steps = 0;
+    while db.defragment_step(1)? > 0 {
+        steps += 1;
+        db.fsck()?;
+    }
+    assert_eq!(steps, 4);
+
+    // Metadata is flushed once per step, so space freed by a move is only reused by the next,
+    // holes next to it included
+    let (batched, _temp) = setup()?;
+    assert_eq!(batched.defragment_step(usize::MAX)?, 2);
+    batched.fsck()?;
+    let starts = (0..10_u8)
+        .step_by(2)
+        .map(|i| {
+            let region = batched.get_region(&format!("region{i}")).unwrap();
+            let start = region.meta().read().start();
+            start / PAGE_SIZE
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(starts, [0, 1, 4, 5, 8]);
+    assert_eq!(batched.layout().start_to_hole().len(), 2);
+    while batched.defragment_step(usize::MAX)? > 0 {}
+    assert!(batched.layout().start_to_hole().is_empty());
+    assert_eq!(batched.file_len()?, compacted.file_len()?);
+
+    assert!(db.layout().start_to_hole().is_empty());
+    assert_eq!(db.file_len()?, compacted.file_len()?);
+    for i in (0..10_u8).step_by(2) {
+        let id = format!("region{i}");
+        let region = db.get_region(&id).unwrap();
+        let expected = compacted.get_region(&id).unwrap();
+        assert_eq!(region.meta().read().start(), expected.meta().read().start());
+        assert_eq!(region.create_reader().read_all(), [i; 1000]);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_verify_checksums() -> Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    let (db, temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_if_needed("region2")?;
+    db.write_all_to_region(&region1, &[1; 1000])?;
+    db.write_all_to_region(&region2, &[2; 500])?;
+    db.write_all_to_region(&region2, &[3; 500])?;
+    db.write_all_to_region_at(&region2, &[4; 10], 100)?;
+    // Forces region1 to move
+    db.write_all_to_region(&region1, &[5; PAGE_SIZE as usize])?;
+    db.truncate_region(&region1, 2000)?;
+    assert!(db.verify()?.is_empty());
+
+    db.flush()?;
+    drop(region1);
+    drop(region2);
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    assert!(db.verify()?.is_empty());
+
+    let start = db.get_region("region2").unwrap().meta().read().start();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(temp.path().join("data"))?
+        .write_all_at(&[9], start + 42)?;
+
+    assert_eq!(db.verify()?, vec!["region2".to_string()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_checksum_refreshed_on_flush() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &[1; 1000])?;
+    assert!(!region.meta().read().is_checksum_stale());
+    let appended = region.meta().read().checksum();
+
+    db.write_all_to_region_at(&region, &[2; 10], 100)?;
+    assert!(region.meta().read().is_checksum_stale());
+    db.truncate_region(&region, 500)?;
+    assert!(region.meta().read().is_checksum_stale());
+
+    db.flush()?;
+    let region_meta = region.meta().read();
+    assert!(!region_meta.is_checksum_stale());
+    assert_ne!(region_meta.checksum(), appended);
+    let checksum = region_meta.checksum();
+    drop(region_meta);
+    drop(region);
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    assert_eq!(
+        db.get_region("region").unwrap().meta().read().checksum(),
+        checksum
+    );
+    assert!(db.verify()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_open_read_only() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, b"Hello, World!")?;
+    db.flush()?;
+
+    // The writer keeps its lock while readers come and go
+    std::thread::scope(|scope| {
+        let handles = (0..2)
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    let db = Database::open_read_only(temp.path())?;
+                    assert!(db.is_read_only());
+
+                    let region = db.get_region("region").unwrap();
+                    assert_eq!(region.create_reader().read_all(), b"Hello, World!");
+
+                    assert!(matches!(
+                        db.write_all_to_region(&region, b"!"),
+                        Err(Error::ReadOnly)
+                    ));
+                    assert!(matches!(
+                        db.truncate_region(&region, 0),
+                        Err(Error::ReadOnly)
+                    ));
+                    assert!(matches!(
+                        db.create_region_if_needed("other"),
+                        Err(Error::ReadOnly)
+                    ));
+                    assert!(matches!(db.compact(), Err(Error::ReadOnly)));
+                    assert!(matches!(db.defragment(), Err(Error::ReadOnly)));
+                    assert!(matches!(db.flush(), Err(Error::ReadOnly)));
+                    assert!(matches!(db.flush_durable(), Err(Error::ReadOnly)));
+                    assert!(matches!(db.sync_region(&region), Err(Error::ReadOnly)));
+                    assert!(matches!(db.flush_in_background(), Err(Error::ReadOnly)));
+                    assert!(matches!(region.set_attr([1; 16]), Err(Error::ReadOnly)));
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap())
+    })?;
+
+    assert_eq!(region.create_reader().read_all(), b"Hello, World!");
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_region() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let small = db.create_region_if_needed("small")?;
+    let large = db.create_region_if_needed("large")?;
+    db.flush()?;
+
+    db.write_all_to_region(&small, &[1; 100])?;
+    db.write_all_to_region(&large, &vec![2; 64 * PAGE_SIZE as usize])?;
+    db.sync_region(&small)?;
+
+    // Only the small region's span was synced, not the whole file
+    assert!(small.meta().read().reserved() * 16 < db.file_len()?);
+    assert!(small.meta().read().is_clean());
+    assert!(large.meta().read().is_dirty());
+
+    drop(small);
+    drop(large);
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    let small = db.get_region("small").unwrap();
+    assert_eq!(small.create_reader().read_all(), [1; 100]);
+    assert_eq!(db.get_region("large").unwrap().meta().read().len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_region_attr() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    assert_eq!(region.attr(), [0; 16]);
+
+    let attr = *b"format:v2 le\0\0\0\x01";
+    region.set_attr(attr)?;
+    db.write_all_to_region(&region, &[1; 10])?;
+    db.flush()?;
+
+    drop(region);
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    let region = db.get_region("region").unwrap();
+    assert_eq!(region.attr(), attr);
+    assert_eq!(region.create_reader().read_all(), [1; 10]);
+
+    Ok(())
+}
+
+#[test]
+fn test_region_id_too_long() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let longest = "a".repeat(rawdb::MAX_REGION_ID_LEN);
+    let too_long = "a".repeat(rawdb::MAX_REGION_ID_LEN + 1);
+    let too_long_error = |res: Result<_>| matches!(res, Err(Error::RegionIdTooLong { .. }));
+
+    assert!(too_long_error(
+        db.create_region_if_needed(&too_long).map(|_| ())
+    ));
+    assert!(too_long_error(
+        db.create_region_at(&too_long, 0, PAGE_SIZE).map(|_| ())
+    ));
+    assert!(db.get_region(&too_long).is_none());
+
+    // The longest id doesn't spill over the attr and checksum
+    let region = db.create_region_if_needed(&longest)?;
+    let attr = [7; 16];
+    region.set_attr(attr)?;
+    db.write_all_to_region(&region, &[1; 10])?;
+    assert!(too_long_error(db.rename_region(&longest, &too_long)));
+    db.flush()?;
+
+    drop(region);
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    let region = db.get_region(&longest).unwrap();
+    assert_eq!(region.attr(), attr);
+    assert!(db.verify()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_iter_regions() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    db.create_region_if_needed("region2")?;
+    db.create_region_if_needed("region3")?;
+
+    // Growing region1 past its reservation moves it to the end of the file
+    db.write_all_to_region(&region1, &vec![1; PAGE_SIZE as usize + 1])?;
+
+    let regions = db.iter_regions().collect::<Vec<_>>();
+    assert_eq!(regions.len(), 3);
+    assert!(regions.windows(2).all(|w| w[0].0 < w[1].0));
+    regions
+        .iter()
+        .for_each(|(start, region)| assert_eq!(*start, region.meta().read().start()));
+    assert_eq!(
+        regions
+            .iter()
+            .map(|(_, region)| region.meta().read().id().to_owned())
+            .collect::<Vec<_>>(),
+        ["region2", "region3", "region1"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_catalog_round_trip() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    let region2 = db.create_region_if_needed("region2")?;
+    let region3 = db.create_region_if_needed("region3")?;
+    db.write_all_to_region(&region1, &[1; 100])?;
+    db.write_all_to_region(&region2, &[2; 50])?;
+    db.write_all_to_region(&region3, &[3; 10])?;
+    db.remove_region(region2)?;
+    db.flush()?;
+
+    let catalog = db.export_catalog()?;
+    drop(region1);
+    drop(region3);
+    drop(db);
+
+    // Lose the regions metadata, keeping the data file
+    std::fs::remove_file(temp.path().join("regions"))?;
+
+    let db = Database::open(temp.path())?;
+    assert!(db.get_region("region1").is_none());
+
+    // Entries must fit within the data file
+    let too_far = catalog.replace(
+        &format!("\"start\": {}", PAGE_SIZE * 2),
+        &format!("\"start\": {}", PAGE_SIZE * 1000),
+    );
+    assert!(matches!(
+        db.import_catalog(&too_far),
+        Err(Error::InvalidCatalog(_))
+    ));
+    assert!(db.import_catalog("not json").is_err());
+
+    // Ids must be unique
+    let duplicate = catalog.replace("region3", "region1");
+    assert!(matches!(
+        db.import_catalog(&duplicate),
+        Err(Error::InvalidCatalog(reason)) if reason.contains("more than once")
+    ));
+
+    db.import_catalog(&catalog)?;
+    assert_eq!(db.export_catalog()?, catalog);
+    assert!(db.import_catalog(&catalog).is_err());
+    assert!(db.verify()?.is_empty());
+    assert_eq!(
+        db.layout().start_to_hole().iter().collect::<Vec<_>>(),
+        [(&PAGE_SIZE, &PAGE_SIZE)]
+    );
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    assert_eq!(
+        db.get_region("region1").unwrap().create_reader().read_all(),
+        [1; 100]
+    );
+    assert_eq!(
+        db.get_region("region3").unwrap().create_reader().read_all(),
+        [3; 10]
+    );
+    assert!(db.get_region("region2").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_growth_policy() -> Result<()> {
+    let temp = TempDir::new()?;
+    let options = DatabaseOptions::default().with_growth_policy(GrowthPolicy::Additive(PAGE_SIZE));
+    let db = Database::open_with(temp.path(), options)?;
+
+    let region = db.create_region_if_needed("region")?;
+    let chunk = vec![7; PAGE_SIZE as usize / 2];
+    for i in 1..=16 {
+        db.write_all_to_region(&region, &chunk)?;
+        let pages = (i * chunk.len() as u64).div_ceil(PAGE_SIZE);
+        assert_eq!(region.meta().read().reserved(), pages * PAGE_SIZE);
+    }
+
+    // The default keeps doubling
+    let (db, _temp) = setup_test_db()?;
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &vec![7; PAGE_SIZE as usize * 5])?;
+    assert_eq!(region.meta().read().reserved(), PAGE_SIZE * 8);
+
+    assert_eq!(
+        GrowthPolicy::Ratio(1.5).grow(PAGE_SIZE * 4, PAGE_SIZE * 5),
+        PAGE_SIZE * 6
+    );
+    assert_eq!(
+        GrowthPolicy::Ratio(1.0).grow(PAGE_SIZE, PAGE_SIZE + 1),
+        PAGE_SIZE * 2
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_shrink_to_fit() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    db.write_all_to_region(&region1, &vec![1; PAGE_SIZE as usize * 10])?;
+    let region2 = db.create_region_if_needed("region2")?;
+    db.write_all_to_region(&region2, &[2; 10])?;
+    assert_eq!(region1.meta().read().reserved(), PAGE_SIZE * 16);
+    assert_eq!(region2.meta().read().start(), PAGE_SIZE * 16);
+
+    region1.truncate(100)?;
+    region1.shrink_to_fit()?;
+    assert_eq!(region1.meta().read().reserved(), PAGE_SIZE);
+
+    // The tail only becomes reusable once the new reservation is flushed
+    assert!(db.layout().start_to_hole().is_empty());
+    let region3 = db.create_region_if_needed("region3")?;
+    assert_eq!(region3.meta().read().start(), PAGE_SIZE * 17);
+
+    db.flush()?;
+    assert_eq!(
+        db.layout().start_to_hole().iter().collect::<Vec<_>>(),
+        [(&PAGE_SIZE, &(PAGE_SIZE * 15))]
+    );
+
+    // Nothing left to release
+    region1.shrink_to_fit()?;
+    assert_eq!(region1.meta().read().reserved(), PAGE_SIZE);
+
+    drop(region1);
+    drop(region2);
+    drop(region3);
+    drop(db);
+
+    let db = Database::open(temp.path())?;
+    let region1 = db.get_region("region1").unwrap();
+    assert_eq!(region1.meta().read().reserved(), PAGE_SIZE);
+    assert_eq!(region1.create_reader().read_all(), [1; 100]);
+    assert_eq!(
+        db.get_region("region2").unwrap().create_reader().read_all(),
+        [2; 10]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_disk_usage_bytes() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &vec![1; PAGE_SIZE as usize * 64])?;
+    db.flush()?;
+
+    let file_len = db.file_len()?;
+    assert!(db.disk_usage_bytes()? >= PAGE_SIZE * 64);
+
+    // Punching the released tail keeps the file length but frees its blocks
+    region.truncate(0)?;
+    region.shrink_to_fit()?;
+    assert_eq!(db.file_len()?, file_len);
+    assert!(db.disk_usage_bytes()? < file_len);
+    assert!(db.disk_usage_bytes()? <= PAGE_SIZE * 8);
+    assert!(!db.disk_usage().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_advise_will_need() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &[7; 1000])?;
+    db.flush()?;
+
+    let start = region.meta().read().start();
+    db.advise_will_need(start, 1000)?;
+    db.advise_will_need(start + 10, 1)?;
+
+    // Out of bounds ranges are clamped to the mapping
+    db.advise_will_need(start, u64::MAX / 2)?;
+    db.advise_will_need(u64::MAX / 2, 1)?;
+    db.advise_will_need(0, 0)?;
+
+    assert_eq!(region.create_reader().read_all(), &[7; 1000]);
+
+    Ok(())
+}
+
+#[test]
+fn test_advise() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &[3; 1000])?;
+    db.flush()?;
+
+    for advice in [
+        Advice::Random,
+        Advice::Sequential,
+        Advice::WillNeed,
+        Advice::DontNeed,
+        Advice::Normal,
+    ] {
+        db.advise(advice)?;
+        assert_eq!(region.create_reader().read_all(), &[3; 1000]);
+    }
+
+    // The access pattern survives the remap caused by growing the file
+    db.advise(Advice::Random)?;
+    db.write_all_to_region(&region, &vec![4; PAGE_SIZE as usize * 256])?;
+    db.flush()?;
+    assert_eq!(region.meta().read().len(), 1000 + PAGE_SIZE * 256);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(windows)]
+fn test_punch_hole_windows() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    db.write_all_to_region(&region, &vec![1; PAGE_SIZE as usize * 64])?;
+    let kept = db.create_region_if_needed("kept")?;
+    db.write_all_to_region(&kept, &[2; 1000])?;
+    db.flush()?;
+
+    assert!(db.disk_usage_bytes()? >= PAGE_SIZE * 64);
+
+    // Shrinking punches the released tail through FSCTL_SET_ZERO_DATA
+    region.truncate(PAGE_SIZE)?;
+    region.shrink_to_fit()?;
+    db.flush()?;
+
+    assert!(db.disk_usage_bytes()? <= db.file_len()?);
+    assert_eq!(region.create_reader().read_all(), &[1; PAGE_SIZE as usize]);
+    assert_eq!(kept.create_reader().read_all(), &[2; 1000]);
+
+    // Another writer can't open the database while it's open
+    assert!(matches!(Database::open(db.path()), Err(Error::TryLock(_))));
+
+    Ok(())
+}
+
+#[test]
+fn test_write_beyond_file_errors() -> Result<()> {
+    let temp = TempDir::new()?;
+
+    {
+        let db = Database::open(temp.path())?;
+        let first = db.create_region_if_needed("first")?;
+        let second = db.create_region_if_needed("second")?;
+        db.write_all_to_region(&first, &[1; 100])?;
+        db.write_all_to_region(&second, &[2; 100])?;
+        db.flush()?;
+        assert_eq!(second.meta().read().start(), PAGE_SIZE);
+    }
+
+    // Cut the data file so the second region now lies past its end
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(temp.path().join("data"))?
+        .set_len(PAGE_SIZE)?;
+
+    let db = Database::open(temp.path())?;
+    let second = db.get_region("second").unwrap();
+    assert!(matches!(
+        db.write_all_to_region(&second, &[3; 10]),
+        Err(Error::WriteBeyondFile { end, file_len })
+            if end == PAGE_SIZE + 110 && file_len == PAGE_SIZE
+    ));
+
+    let first = db.get_region("first").unwrap();
+    db.write_all_to_region(&first, &[4; 10])?;
+    assert_eq!(first.meta().read().len(), 110);
+
+    Ok(())
+}
+
+#[test]
+fn test_reserve_region() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region1 = db.create_region_if_needed("region1")?;
+    db.write_all_to_region(&region1, &[1; 100])?;
+    let region2 = db.create_region_if_needed("region2")?;
+    db.write_all_to_region(&region2, &[2; 100])?;
+
+    let chunk = vec![3; PAGE_SIZE as usize];
+    let chunks = 64;
+
+    // Pre-reserving moves the region once, then every write fits in place
+    region1.reserve(100 + PAGE_SIZE * chunks)?;
+    assert_eq!(region1.meta().read().reserved(), PAGE_SIZE * 65);
+    db.fsck()?;
+    assert_eq!(region1.create_reader().read_all(), [1; 100]);
+    let relocations = db.relocations();
+    for _ in 0..chunks {
+        db.write_all_to_region(&region1, &chunk)?;
+    }
+    assert_eq!(db.relocations(), relocations);
+    assert_eq!(region1.meta().read().len(), 100 + PAGE_SIZE * chunks);
+
+    // Without it the same writes go through several growths
+    let region3 = db.create_region_if_needed("region3")?;
+    db.write_all_to_region(&region3, &[1; 100])?;
+    let region4 = db.create_region_if_needed("region4")?;
+    db.write_all_to_region(&region4, &[2; 100])?;
+    let relocations = db.relocations();
+    for _ in 0..chunks {
+        db.write_all_to_region(&region3, &chunk)?;
+    }
+    assert!(db.relocations() > relocations);
+
+    // Last region grows in place, and shrinking is rejected
+    region4.reserve(PAGE_SIZE * 10)?;
+    assert_eq!(region4.meta().read().reserved(), PAGE_SIZE * 10);
+    assert!(matches!(
+        region4.reserve(PAGE_SIZE),
+        Err(Error::ReserveShrink { .. })
+    ));
+    region4.reserve(PAGE_SIZE * 10 - 1)?;
+    assert_eq!(region4.meta().read().reserved(), PAGE_SIZE * 10);
+    assert_eq!(region2.create_reader().read_all(), [2; 100]);
+    assert_eq!(region4.create_reader().read_all(), [2; 100]);
+
+    db.flush()?;
+    db.fsck()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_open_with_lock_timeout() -> Result<()> {
+    let (db, temp) = setup_test_db()?;
+    let path = temp.path().to_owned();
+
+    let res = thread::spawn({
+        let path = path.clone();
+        move || Database::open_with_lock_timeout(&path, Duration::from_millis(50))
+    })
+    .join()
+    .unwrap();
+    assert!(matches!(res, Err(Error::LockTimeout { path: p }) if p.starts_with(&path)));
+
+    let waiter =
+        thread::spawn(move || Database::open_with_lock_timeout(&path, Duration::from_secs(10)));
+    thread::sleep(Duration::from_millis(100));
+    drop(db);
+    waiter.join().unwrap()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_try_upgrade_after_drop() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+    let region = db.create_region_if_needed("region")?;
+    let weak = db.weak_clone();
+    assert!(weak.try_upgrade().is_some());
+
+    drop(db);
+
+    assert!(weak.try_upgrade().is_none());
+    assert!(matches!(region.try_db(), Err(Error::DatabaseDropped)));
+    assert!(matches!(
+        region.write_all_at(&[1; 8], 0),
+        Err(Error::DatabaseDropped)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_flush_in_background() -> Result<()> {
+    let temp = TempDir::new()?;
+
+    {
+        let db = Database::open(temp.path())?;
+        let removed = db.create_region_with_capacity("removed", 2 * PAGE_SIZE)?;
+        let region = db.create_region_if_needed("flushed")?;
+        db.flush()?;
+        db.remove_region(removed)?;
+        region.write_all_at(&[7; 10_000], 0)?;
+
+        let handle = db.flush_in_background()?;
+
+        // Unrelated work while the data is being flushed
+        let other = db.create_region_if_needed("other")?;
+        assert_eq!(other.meta().read().len(), 0);
+
+        handle.wait()?;
+
+        // The removed region's space is reusable once the flush is done
+        let reused = db.create_region_if_needed("reused")?;
+        assert!(reused.meta().read().start() < region.meta().read().start());
+    }
+
+    let db = Database::open(temp.path())?;
+    let region = db.get_region("flushed").unwrap();
+    assert_eq!(region.create_reader().read_all(), &[7; 10_000]);
+
+    Ok(())
+}
+
+#[test]
+fn test_batch() -> Result<()> {
+    let temp = TempDir::new()?;
+
+    {
+        let db = Database::open(temp.path())?;
+        let a = db.create_region_if_needed("a")?;
+        let b = db.create_region_if_needed("b")?;
+        a.write_all_at(b"before", 0)?;
+        db.flush()?;
+
+        let res = db.batch(|tx| {
+            tx.truncate_write_all_to_region(&a, 0, b"overwritten");
+            tx.write_all_to_region(&b, b"appended");
+            Err::<(), _>(Error::ReadOnly)
+        });
+        assert!(res.is_err());
+        assert_eq!(a.create_reader().read_all(), b"before");
+        assert_eq!(b.meta().read().len(), 0);
+
+        // Dropped without being committed
+        let mut batch = db.begin_batch();
+        batch.write_all_to_region(&b, b"dropped");
+        drop(batch);
+        assert_eq!(b.meta().read().len(), 0);
+
+        db.batch(|tx| {
+            tx.truncate_write_all_to_region(&a, 0, b"after");
+            tx.write_all_to_region(&b, b"appended");
+            tx.write_all_to_region(&b, b" twice");
+            Ok::<_, Error>(())
+        })?;
+    }
+
+    let db = Database::open(temp.path())?;
+    assert_eq!(
+        db.get_region("a").unwrap().create_reader().read_all(),
+        b"after"
+    );
+    assert_eq!(
+        db.get_region("b").unwrap().create_reader().read_all(),
+        b"appended twice"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_crash_between_ops() -> Result<()> {
+    use rawdb::RegionObserver;
+    use std::panic::{self, AssertUnwindSafe};
+
+    struct CrashOnRelocate;
+    impl RegionObserver for CrashOnRelocate {
+        fn on_relocate(&self, _id: &str, _old_start: u64, _new_start: u64) {
+            panic!("crash");
+        }
+    }
+
+    let temp = TempDir::new()?;
+    let grown = [1; PAGE_SIZE as usize + 1];
+
+    {
+        let db = Database::open_with_observer(temp.path(), Arc::new(CrashOnRelocate))?;
+        let a = db.create_region_if_needed("a")?;
+        let b = db.create_region_if_needed("b")?;
+        a.write_all_at(b"before", 0)?;
+        b.write_all_at(b"before", 0)?;
+        db.flush()?;
+
+        // Growing `a` past its reservation moves it, and the observer crashes before `b` is written
+        let crashed = panic::catch_unwind(AssertUnwindSafe(|| {
+            db.batch(|tx| {
+                tx.truncate_write_all_to_region(&a, 0, &grown);
+                tx.truncate_write_all_to_region(&b, 0, b"after");
+                Ok::<_, Error>(())
+            })
+        }));
+        assert!(crashed.is_err());
+        assert_eq!(b.create_reader().read_all(), b"before");
+        assert!(temp.path().join("batch").exists());
+    }
+
+    // The journal was durable, so reopening completes the batch
+    {
+        let db = Database::open(temp.path())?;
+        assert!(!temp.path().join("batch").exists());
+        assert_eq!(
+            db.get_region("a").unwrap().create_reader().read_all(),
+            grown
+        );
+        assert_eq!(
+            db.get_region("b").unwrap().create_reader().read_all(),
+            b"after"
+        );
+        assert!(db.verify()?.is_empty());
+
+        // Invalid operations are caught before anything is journaled or applied
+        let a = db.get_region("a").unwrap();
+        let b = db.get_region("b").unwrap();
+        let res = db.batch(|tx| {
+            tx.truncate_write_all_to_region(&a, 0, b"a");
+            tx.truncate_region(&b, 100);
+            Ok::<_, Error>(())
+        });
+        assert!(matches!(res, Err(Error::TruncateInvalid { .. })));
+        assert_eq!(a.create_reader().read_all(), grown);
+    }
+
+    // A journal torn while being written is discarded
+    std::fs::write(temp.path().join("batch"), b"torn")?;
+    let db = Database::open(temp.path())?;
+    assert!(!temp.path().join("batch").exists());
+    assert_eq!(
+        db.get_region("a").unwrap().create_reader().read_all(),
+        grown
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_region_read_into() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    let data = (0..100).collect::<Vec<u8>>();
+    region.write_all_at(&data, 0)?;
+
+    let mut buf = [0u8; 100];
+    assert_eq!(region.read_into(0, &mut buf)?, 100);
+    assert_eq!(&buf[..], region.create_reader().read_all());
+
+    // Clamped to the region's length
+    let mut buf = [0u8; 32];
+    assert_eq!(region.read_into(90, &mut buf)?, 10);
+    assert_eq!(&buf[..10], &data[90..]);
+    assert_eq!(region.read_into(100, &mut buf)?, 0);
+    assert_eq!(region.read_into(1_000, &mut buf)?, 0);
+
+    // The copy outlives any lock, so it can be sent elsewhere
+    let region_ = region.clone();
+    let copied = std::thread::spawn(move || {
+        let mut buf = [0u8; 8];
+        region_.read_into(4, &mut buf).map(|_| buf)
+    })
+    .join()
+    .unwrap()?;
+    assert_eq!(copied, [4, 5, 6, 7, 8, 9, 10, 11]);
+
+    Ok(())
+}
+
+#[test]
+fn test_region_cursor() -> Result<()> {
+    use std::io::{self, Read, Seek, SeekFrom};
+
+    let (db, _temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    let data = (0..20_000).map(|i| i as u8).collect::<Vec<u8>>();
+    region.write_all_at(&data, 0)?;
+
+    let mut cursor = region.reader_cursor();
+    let mut copied = vec![];
+    assert_eq!(io::copy(&mut cursor, &mut copied)?, 20_000);
+    assert_eq!(copied, region.create_reader().read_all());
+
+    let mut buf = [0u8; 4];
+    assert_eq!(cursor.seek(SeekFrom::End(-4))?, 19_996);
+    cursor.read_exact(&mut buf)?;
+    assert_eq!(&buf, &data[19_996..]);
+    assert_eq!(cursor.seek(SeekFrom::Current(-10))?, 19_990);
+    cursor.read_exact(&mut buf)?;
+    assert_eq!(&buf, &data[19_990..19_994]);
+
+    // Past the end reads nothing, before the start is an error
+    assert_eq!(cursor.seek(SeekFrom::Start(50_000))?, 50_000);
+    assert_eq!(cursor.read(&mut buf)?, 0);
+    assert!(cursor.seek(SeekFrom::End(-20_001)).is_err());
+    assert_eq!(cursor.position(), 50_000);
+
+    Ok(())
+}
+
+#[test]
+fn test_stats() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    for i in 0..4 {
+        let region = db.create_region_if_needed(&format!("region_{i}"))?;
+        region.write_all_at(&vec![i as u8; 5_000 * (i + 1)], 0)?;
+    }
+    db.remove_region_with_id("region_1")?;
+    db.flush()?;
+
+    let stats = db.stats()?;
+    assert_eq!(stats.region_count, 3);
+    assert_eq!(stats.used_bytes, 5_000 + 15_000 + 20_000);
+    assert!(stats.used_bytes <= stats.reserved_bytes);
+    assert!(stats.reserved_bytes <= stats.file_len);
+    assert_eq!(stats.file_len, db.file_len()?);
+    assert!(stats.hole_bytes > 0);
+    assert!(stats.largest_hole <= stats.hole_bytes);
+    assert_eq!(
+        stats.fragmentation_ratio,
+        stats.hole_bytes as f64 / stats.file_len as f64
+    );
+
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["region_count"], 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_hole_histogram() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+
+    for i in 0..10 {
+        let region = db.create_region_if_needed(&format!("region_{i}"))?;
+        region.write_all_at(&[1; 100], 0)?;
+        assert_eq!(region.meta().read().reserved(), PAGE_SIZE);
+    }
+    assert_eq!(db.layout().largest_hole(), None);
+    assert!(db.layout().hole_histogram().is_empty());
+
+    // Holes of 1, 2 and 3 pages, the last region keeps them from being trailing
+    for i in [1, 3, 4, 6, 7, 8] {
+        db.remove_region_with_id(&format!("region_{i}"))?;
+    }
+    db.flush()?;
+
+    let layout = db.layout();
+    assert_eq!(layout.largest_hole(), Some((6 * PAGE_SIZE, 3 * PAGE_SIZE)));
+    assert_eq!(
+        layout.hole_histogram(),
+        BTreeMap::from([(PAGE_SIZE, 1), (2 * PAGE_SIZE, 2)])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_hole_fit() -> Result<()> {
+    // Holes of 2, 1 and 3 pages, in that order
+    let expected = [
+        (HoleFit::BestFit, 4 * PAGE_SIZE),
+        (HoleFit::WorstFit, 6 * PAGE_SIZE),
+        (HoleFit::FirstFit, PAGE_SIZE),
+    ];
+
+    for (hole_fit, start) in expected {
+        let temp = TempDir::new()?;
+        let db = Database::open_with(
+            temp.path(),
+            DatabaseOptions::default().with_hole_fit(hole_fit),
+        )?;
+
+        for i in 0..10 {
+            db.create_region_if_needed(&format!("region_{i}"))?;
+        }
+        for i in [1, 2, 4, 6, 7, 8] {
+            db.remove_region_with_id(&format!("region_{i}"))?;
+        }
+        db.flush()?;
+
+        let region = db.create_region_if_needed("new")?;
+        assert_eq!(region.meta().read().start(), start, "{hole_fit:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_copy_region_to() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+    let (dest, _dest_temp) = setup_test_db()?;
+
+    let region = db.create_region_if_needed("region")?;
+    let data = (0..3 * PAGE_SIZE + 100)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    region.write_all_at(&data, 0)?;
+    region.set_attr([7; 16])?;
+    // Reserved past the length, which isn't copied
+    region.reserve(10 * PAGE_SIZE)?;
+
+    let copy = db.copy_region_to(&region, &dest, "copy")?;
+    assert_eq!(copy.meta().read().len(), data.len() as u64);
+    assert_eq!(copy.meta().read().reserved(), 4 * PAGE_SIZE);
+    assert_eq!(copy.attr(), region.attr());
+    assert_eq!(
+        copy.create_reader().read_all(),
+        region.create_reader().read_all()
+    );
+
+    assert!(matches!(
+        db.copy_region_to(&region, &dest, "copy"),
+        Err(Error::RegionAlreadyExists)
+    ));
+
+    // Within the same database, and persisted
+    let same = db.copy_region_to(&region, &db, "same")?;
+    assert_eq!(same.create_reader().read_all(), &data[..]);
+    assert_eq!(same.attr(), region.attr());
+    db.flush()?;
+    assert!(db.verify()?.is_empty());
+
+    // Copies to the same database while another thread keeps growing the file
+    let grower = db.create_region_if_needed("grower")?;
+    std::thread::scope(|scope| -> Result<()> {
+        let grow = scope.spawn(|| -> Result<()> {
+            (0..100).try_for_each(|_| db.write_all_to_region(&grower, &[1; PAGE_SIZE as usize]))
+        });
+        (0..20).try_for_each(|i| -> Result<()> {
+            let copy = db.copy_region_to(&region, &db, &format!("same{i}"))?;
+            assert_eq!(copy.create_reader().read_all(), &data[..]);
+            Ok(())
+        })?;
+        grow.join().unwrap()
+    })?;
+
+    // And to another one while the source database keeps taking its memory map write lock
+    std::thread::scope(|scope| -> Result<()> {
+        let compact = scope.spawn(|| (0..20).try_for_each(|_| db.compact()));
+        (0..20).try_for_each(|i| -> Result<()> {
+            let copy = db.copy_region_to(&region, &dest, &format!("other{i}"))?;
+            assert_eq!(copy.create_reader().read_all(), &data[..]);
+            Ok(())
+        })?;
+        compact.join().unwrap()
+    })?;
+
+    dest.flush()?;
+    let path = dest.path().to_path_buf();
+    drop(copy);
+    drop(dest);
+    let dest = Database::open(&path)?;
+    let copy = dest.get_region("copy").unwrap();
+    assert_eq!(copy.create_reader().read_all(), &data[..]);
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_to() -> Result<()> {
+    let (db, _temp) = setup_test_db()?;
+    let snapshot_temp = TempDir::new()?;
+    let snapshot_path = snapshot_temp.path().join("snapshot");
+
+    let r1 = db.create_region_if_needed("r1")?;
+    let r2 = db.create_region_if_needed("r2")?;
+    let removed = db.create_region_if_needed("removed")?;
+    db.write_all_to_region(&r1, &[1; 5000])?;
+    db.write_all_to_region(&r2, &[2; 100])?;
+    db.flush()?;
+    // Not flushed yet, still part of the snapshot
+    db.write_all_to_region(&r2, &[3; 100])?;
+    drop(removed);
+    db.remove_region_with_id("removed")?;
+
+    let snapshot = db.snapshot_to(&snapshot_path)?;
+
+    db.write_all_to_region_at(&r1, &[9; 10], 0)?;
+    db.truncate_region(&r2, 50)?;
+    db.create_region_if_needed("r3")?;
+    db.flush()?;
+
+    let check = |snapshot: &Database| {
+        assert!(snapshot.get_region("removed").is_none());
+        assert!(snapshot.get_region("r3").is_none());
+        let r1 = snapshot.get_region("r1").unwrap();
+        assert_eq!(r1.create_reader().read_all(), &[1; 5000][..]);
+        let r2 = snapshot.get_region("r2").unwrap();
+        let expected = [[2; 100], [3; 100]].concat();
+        assert_eq!(r2.create_reader().read_all(), &expected[..]);
+    };
+    check(&snapshot);
+    drop(snapshot);
+    check(&Database::open(&snapshot_path)?);
+
+    assert!(db.snapshot_to(&snapshot_path).is_err());
+
+    // Snapshots taken while another thread keeps rewriting and flushing are consistent
+    db.write_all_to_region_at(&r1, &[0; 5000], 0)?;
+    std::thread::scope(|scope| -> Result<()> {
+        let rewrite = scope.spawn(|| {
+            (0..50u8).try_for_each(|i| {
+                db.write_all_to_region_at(&r1, &[i; 5000], 0)?;
+                db.flush()
+            })
+        });
+        (0..10).try_for_each(|i| -> Result<()> {
+            let snapshot = db.snapshot_to(&snapshot_temp.path().join(format!("concurrent{i}")))?;
+            let r1 = snapshot.get_region("r1").unwrap();
+            let reader = r1.create_reader();
+            let data = reader.read_all();
+            assert_eq!(data.len(), 5000);
+            assert!(data.iter().all(|b| *b == data[0]));
+            assert!(snapshot.verify()?.is_empty());
+            Ok(())
+        })?;
+        rewrite.join().unwrap()
+    })?;
+
+    Ok(())
+}
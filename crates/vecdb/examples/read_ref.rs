@@ -0,0 +1,38 @@
+//! Sequential sum over 100M `u64`, copying each value vs borrowing it from the mmap.
+
+use std::{fs, path::Path, time::Instant};
+
+use vecdb::{AnyStoredVec, Database, GenericStoredVec, RawVec, Version};
+
+const LEN: usize = 100_000_000;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let _ = fs::remove_dir_all("read_ref");
+
+    let database = Database::open(Path::new("read_ref"))?;
+
+    let mut vec: RawVec<usize, u64> = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    (0..LEN as u64).for_each(|v| vec.push(v));
+    vec.flush()?;
+
+    let reader = vec.create_reader();
+
+    let instant = Instant::now();
+    let mut sum = 0_u64;
+    for i in 0..LEN {
+        sum = sum.wrapping_add(vec.unchecked_read_at(i, &reader)?);
+    }
+    println!("copy:      {sum} in {:?}", instant.elapsed());
+
+    let instant = Instant::now();
+    let mut sum = 0_u64;
+    for i in 0..LEN {
+        sum = sum.wrapping_add(*vec.read_ref_at(i, &reader)?);
+    }
+    println!("zero-copy: {sum} in {:?}", instant.elapsed());
+
+    drop(reader);
+    let _ = fs::remove_dir_all("read_ref");
+
+    Ok(())
+}
@@ -24,11 +24,31 @@ pub enum Error {
 
     WrongLength,
     WrongEndian,
-    DifferentVersion { found: Version, expected: Version },
+    CorruptedHeader,
+    DifferentVersion {
+        /// Name of the vec, or path of the version file
+        name: String,
+        found: Version,
+        expected: Version,
+    },
     IndexTooHigh,
     ExpectVecToHaveIndex,
     FailedKeyTryIntoUsize,
     DifferentCompressionMode,
+    TypeMismatch {
+        expected: String,
+        found: String,
+    },
+    IndexTypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The region isn't a header followed by whole values
+    RegionMisaligned {
+        len: usize,
+        header: usize,
+    },
+    Interrupted,
 }
 
 impl From<time::SystemTimeError> for Error {
@@ -101,17 +121,33 @@ impl fmt::Display for Error {
             Error::ZeroCopyError => write!(f, "ZeroCopy error"),
 
             Error::WrongEndian => write!(f, "Wrong endian"),
-            Error::DifferentVersion { found, expected } => {
+            Error::CorruptedHeader => write!(f, "Corrupted header"),
+            Error::DifferentVersion {
+                name,
+                found,
+                expected,
+            } => {
                 write!(
                     f,
-                    "Different version found: {found:?}, expected: {expected:?}"
+                    "Different version for {name}, found: {found}, expected: {expected}"
                 )
             }
             Error::IndexTooHigh => write!(f, "Index too high"),
             Error::ExpectVecToHaveIndex => write!(f, "Expect vec to have index"),
             Error::FailedKeyTryIntoUsize => write!(f, "Failed to convert key to usize"),
             Error::DifferentCompressionMode => write!(f, "Different compression mode chosen"),
+            Error::IndexTypeMismatch { expected, found } => {
+                write!(f, "Index type mismatch, expected {expected}, found {found}")
+            }
+            Error::TypeMismatch { expected, found } => {
+                write!(f, "Type mismatch, expected {expected}, found {found}")
+            }
+            Error::RegionMisaligned { len, header } => write!(
+                f,
+                "Region of {len} bytes isn't a {header} bytes header followed by whole values"
+            ),
             Error::WrongLength => write!(f, "Wrong length"),
+            Error::Interrupted => write!(f, "Interrupted by exit"),
             Error::Str(s) => write!(f, "{s}"),
             Error::String(s) => write!(f, "{s}"),
         }
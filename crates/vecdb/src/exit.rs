@@ -1,7 +1,15 @@
-use std::{process::exit, sync::Arc};
+use std::{
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 use log::info;
-use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use parking_lot::{Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{Error, Result};
 
 type Callbacks = Arc<Mutex<Vec<Box<dyn Fn() + Send + Sync>>>>;
 
@@ -10,10 +18,15 @@ type Callbacks = Arc<Mutex<Vec<Box<dyn Fn() + Send + Sync>>>>;
 /// Uses a read-write lock to coordinate between operations and shutdown signals (e.g., Ctrl-C).
 /// Operations hold read locks during critical sections, preventing shutdown until they complete.
 /// Registered rollbacks will be ran on exit.
+///
+/// Long computations register with `checkpointing` so that on Ctrl-C they get to reach their
+/// next checkpoint, flushing what they computed, before the program exits.
 #[derive(Default, Clone)]
 pub struct Exit {
     lock: Arc<RwLock<()>>,
     cleanup_callbacks: Callbacks,
+    triggered: Arc<AtomicBool>,
+    checkpointing: Arc<(Mutex<usize>, Condvar)>,
 }
 
 impl Exit {
@@ -21,6 +34,8 @@ impl Exit {
         Self {
             lock: Arc::new(RwLock::new(())),
             cleanup_callbacks: Arc::new(Mutex::new(Vec::new())),
+            triggered: Arc::new(AtomicBool::new(false)),
+            checkpointing: Arc::default(),
         }
     }
 
@@ -37,27 +52,81 @@ impl Exit {
     /// Make sure that no other crate sets one
     ///
     pub fn set_ctrlc_handler(&self) {
-        let lock_copy = self.lock.clone();
-        let callbacks = self.cleanup_callbacks.clone();
+        let exit = self.clone();
 
         ctrlc::set_handler(move || {
-            // Run cleanup callbacks
-            for callback in callbacks.lock().iter() {
-                callback();
-            }
-
-            if lock_copy.is_locked() {
-                info!("Waiting to exit safely...");
-            }
-            let _lock = lock_copy.write();
+            let _lock = exit.shutdown();
 
             info!("Exiting...");
-            exit(0);
+            process::exit(0);
         })
         .expect("Error setting Ctrl-C handler");
     }
 
+    /// What the Ctrl-C handler does before exiting: triggers the exit, runs the cleanup
+    /// callbacks, waits for checkpointing computations to stop then for every lock holder.
+    ///
+    /// Returns the write lock, no new critical section can start while it's held.
+    pub fn shutdown(&self) -> RwLockWriteGuard<'_, ()> {
+        self.trigger();
+
+        // Run cleanup callbacks
+        for callback in self.cleanup_callbacks.lock().iter() {
+            callback();
+        }
+
+        let (count, stopped) = &*self.checkpointing;
+        let mut count = count.lock();
+        if *count > 0 {
+            info!("Waiting for computations to reach a checkpoint...");
+        }
+        stopped.wait_while(&mut count, |count| *count > 0);
+        drop(count);
+
+        if self.lock.is_locked() {
+            info!("Waiting to exit safely...");
+        }
+        self.lock.write()
+    }
+
+    /// Registers a computation that checks `is_triggered` at checkpoints, `shutdown` waits
+    /// until the returned guard is dropped.
+    pub fn checkpointing(&self) -> CheckpointingGuard {
+        *self.checkpointing.0.lock() += 1;
+        CheckpointingGuard(self.checkpointing.clone())
+    }
+
     pub fn lock(&self) -> RwLockReadGuard<'_, ()> {
         self.lock.read()
     }
+
+    /// Asks long computations to stop at their next checkpoint, done by the Ctrl-C handler.
+    pub fn trigger(&self) {
+        self.triggered.store(true, Ordering::Release);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::Acquire)
+    }
+
+    /// Errors with `Interrupted` once the exit was triggered.
+    #[inline]
+    pub fn check(&self) -> Result<()> {
+        if self.is_triggered() {
+            return Err(Error::Interrupted);
+        }
+        Ok(())
+    }
+}
+
+/// Keeps `Exit::shutdown` waiting until dropped, see `Exit::checkpointing`.
+#[must_use]
+pub struct CheckpointingGuard(Arc<(Mutex<usize>, Condvar)>);
+
+impl Drop for CheckpointingGuard {
+    fn drop(&mut self) {
+        let (count, stopped) = &*self.0;
+        *count.lock() -= 1;
+        stopped.notify_all();
+    }
 }
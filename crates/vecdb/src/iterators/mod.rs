@@ -1,10 +1,14 @@
 use std::iter::FusedIterator;
 
 mod boxed;
+mod peekable;
+mod rev;
 mod typed;
 mod writer;
 
 pub use boxed::*;
+pub use peekable::*;
+pub use rev::*;
 pub use typed::*;
 pub use writer::*;
 
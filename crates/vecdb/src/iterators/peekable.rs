@@ -0,0 +1,121 @@
+use std::iter::FusedIterator;
+
+use crate::{BoxedVecIterator, TypedVecIterator, VecIndex, VecIterator, VecValue};
+
+/// Vec iterator with a one-slot lookahead buffer, returned by `IterableVec::iter_peekable`.
+///
+/// Implements `TypedVecIterator` itself, so it can stand in for the boxed iterator it wraps.
+/// Repositioning with `set_position_to` or `set_end_to` drops the peeked value.
+pub struct PeekableVecIterator<'a, I, T> {
+    iter: BoxedVecIterator<'a, I, T>,
+    index: usize,
+    peeked: Option<Option<T>>,
+}
+
+impl<'a, I, T> PeekableVecIterator<'a, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    /// Wraps an iterator whose end hasn't been moved, its position is derived from its length.
+    pub fn new(iter: BoxedVecIterator<'a, I, T>) -> Self {
+        let index = iter.vec_len() - iter.len();
+        Self {
+            iter,
+            index,
+            peeked: None,
+        }
+    }
+
+    /// Returns the value the next call to `next` will return, without advancing.
+    #[inline]
+    pub fn peek(&mut self) -> Option<T> {
+        self.peeked.get_or_insert_with(|| self.iter.next()).clone()
+    }
+
+    /// Index of the value the next call to `next` will return.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.index
+    }
+}
+
+impl<I, T> Iterator for PeekableVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let value = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.iter.next(),
+        };
+        if value.is_some() {
+            self.index += 1;
+        }
+        value
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match &self.peeked {
+            Some(Some(_)) => self.iter.len() + 1,
+            Some(None) => 0,
+            None => self.iter.len(),
+        };
+        (len, Some(len))
+    }
+}
+
+impl<I, T> VecIterator for PeekableVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    #[inline]
+    fn set_position_to(&mut self, i: usize) {
+        self.peeked = None;
+        self.index = i;
+        self.iter.set_position_to(i);
+    }
+
+    #[inline]
+    fn set_end_to(&mut self, i: usize) {
+        // The peeked value was already consumed from the inner iterator, step back over it
+        if self.peeked.take().is_some() {
+            self.iter.set_position_to(self.index);
+        }
+        self.iter.set_end_to(i);
+    }
+
+    #[inline]
+    fn vec_len(&self) -> usize {
+        self.iter.vec_len()
+    }
+}
+
+impl<I, T> TypedVecIterator for PeekableVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    type I = I;
+    type T = T;
+}
+
+impl<I, T> ExactSizeIterator for PeekableVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+}
+
+impl<I, T> FusedIterator for PeekableVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+}
@@ -0,0 +1,58 @@
+use std::iter::FusedIterator;
+
+use crate::{BoxedVecIterator, VecIndex, VecValue};
+
+/// Iterator over `(index, value)` pairs from the last index down to the first,
+/// returned by `IterableVec::iter_rev`.
+///
+/// Seeks backward one index at a time, which is cheap for raw vecs and decodes each page
+/// of compressed vecs only once since seeks within the decoded page don't decode again.
+pub struct RevVecIterator<'a, I, T> {
+    iter: BoxedVecIterator<'a, I, T>,
+    index: usize,
+}
+
+impl<'a, I, T> RevVecIterator<'a, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    pub fn new(iter: BoxedVecIterator<'a, I, T>) -> Self {
+        let index = iter.vec_len();
+        Self { iter, index }
+    }
+}
+
+impl<I, T> Iterator for RevVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    type Item = (I, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<(I, T)> {
+        self.index = self.index.checked_sub(1)?;
+        let value = self.iter.get_at(self.index)?;
+        Some((I::from(self.index), value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.index, Some(self.index))
+    }
+}
+
+impl<I, T> ExactSizeIterator for RevVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+}
+
+impl<I, T> FusedIterator for RevVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+}
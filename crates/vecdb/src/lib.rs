@@ -7,14 +7,18 @@
 // #![doc = include_str!("../examples/compressed.rs")]
 // #![doc = "```"]
 
-pub use rawdb::{Database, Error as RawDBError, PAGE_SIZE, Reader};
+pub use rawdb::{Batch, Database, Error as RawDBError, PAGE_SIZE, ReadTxn, Reader};
 #[cfg(feature = "derive")]
-pub use vecdb_derive::Compressable;
+#[doc(hidden)]
+pub use serde;
+#[cfg(feature = "derive")]
+pub use vecdb_derive::{Compressable, VecValue};
 
 mod error;
 mod exit;
 mod iterators;
 mod lookback;
+mod registry;
 mod stamp;
 mod traits;
 mod variants;
@@ -25,6 +29,7 @@ use variants::*;
 pub use error::*;
 pub use exit::*;
 pub use iterators::*;
+pub use registry::*;
 pub use stamp::*;
 pub use traits::*;
 pub use variants::*;
@@ -0,0 +1,94 @@
+use std::sync::{Arc, Weak};
+
+use parking_lot::{Mutex, RwLock};
+
+use crate::{GenericStoredVec, Result, VecIndex, VecValue};
+
+type Entry = Box<dyn Fn() -> Option<Result<bool>> + Send + Sync>;
+
+/// Vec shared between its owner and a `Registry`.
+pub type SharedVec<V> = Arc<RwLock<V>>;
+
+/// Weak registry of stored vectors, used to flush them all at once.
+///
+/// Registration is manual: importing a vec doesn't register it anywhere, since vecs are owned
+/// values that the registry couldn't reach. Each vec to track is handed to `register` once
+/// imported, which gives it back as a `SharedVec` to be used from then on.
+/// The registry only holds `Weak` references, so it never keeps a vec alive.
+/// Dropped vecs are pruned lazily, on the next call to `flush_all_dirty`.
+#[derive(Default, Clone)]
+pub struct Registry {
+    entries: Arc<Mutex<Vec<Entry>>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps a vec and tracks it weakly, for as long as the returned `SharedVec` is alive.
+    #[must_use = "the vec stops being tracked once the returned handle is dropped"]
+    pub fn register<I, T, V>(&self, vec: V) -> SharedVec<V>
+    where
+        I: VecIndex,
+        T: VecValue,
+        V: GenericStoredVec<I, T> + 'static,
+    {
+        let vec = Arc::new(RwLock::new(vec));
+        let weak: Weak<RwLock<V>> = Arc::downgrade(&vec);
+
+        self.entries.lock().push(Box::new(move || {
+            let vec = weak.upgrade()?;
+            let mut vec = vec.write();
+            if !vec.is_dirty() {
+                return Some(Ok(false));
+            }
+            Some(vec.flush().map(|_| true))
+        }));
+
+        vec
+    }
+
+    /// Number of tracked entries, including dropped vecs not yet pruned.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Flushes every live vec with uncommitted changes and returns how many were flushed.
+    ///
+    /// Entries whose vec was dropped are removed from the registry.
+    pub fn flush_all_dirty(&self) -> Result<usize> {
+        let mut entries = self.entries.lock();
+        let mut flushed = 0;
+        let mut res = Ok(());
+
+        entries.retain(|entry| match entry() {
+            None => false,
+            Some(Ok(true)) => {
+                flushed += 1;
+                true
+            }
+            Some(Ok(false)) => true,
+            Some(Err(e)) => {
+                if res.is_ok() {
+                    res = Err(e);
+                }
+                true
+            }
+        });
+
+        res.map(|_| flushed)
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("entries", &self.len())
+            .finish()
+    }
+}
@@ -23,6 +23,10 @@ impl Stamp {
     pub fn new(stamp: u64) -> Self {
         Self(stamp)
     }
+
+    pub fn swap_bytes(self) -> Self {
+        Self(self.0.swap_bytes())
+    }
 }
 
 impl From<u64> for Stamp {
@@ -16,6 +16,12 @@ pub const SEPARATOR: &str = "_to_";
 /// Common trait for all vectors providing metadata and utility methods.
 pub trait AnyVec: Send + Sync {
     fn version(&self) -> Version;
+    /// Lists this vec and every source contributing to its version, with their versions.
+    ///
+    /// Useful to find out which source bumped when a recompute triggers unexpectedly.
+    fn version_breakdown(&self) -> Vec<(String, Version)> {
+        vec![(self.name().to_string(), self.version())]
+    }
     fn name(&self) -> &str;
     fn len(&self) -> usize;
     fn is_empty(&self) -> bool {
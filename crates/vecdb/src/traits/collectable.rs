@@ -1,4 +1,4 @@
-use crate::{IterableVec, TypedVec, i64_to_usize};
+use crate::{IterableVec, Result, TypedVec, i64_to_usize};
 
 use super::{AnyVec, VecIndex, VecValue};
 
@@ -43,15 +43,67 @@ where
         self.collect_range(from, to)
     }
 
+    /// Calls `f` with the index and value of every item in the specified range,
+    /// without collecting them.
+    fn for_each_in_range(
+        &self,
+        from: Option<usize>,
+        to: Option<usize>,
+        mut f: impl FnMut(usize, &T),
+    ) {
+        let len = self.len();
+        let from = from.unwrap_or_default();
+        let to = to.map_or(len, |to| to.min(len));
+        if from >= to {
+            return;
+        }
+        let mut iter = self.iter();
+        iter.set_end_to(to);
+        iter.set_position_to(from);
+        iter.take(to - from)
+            .enumerate()
+            .for_each(|(i, value)| f(from + i, &value));
+    }
+
     /// Collects values in the specified range as JSON bytes.
     #[inline]
     fn collect_range_json_bytes(&self, from: Option<usize>, to: Option<usize>) -> Vec<u8> {
-        let vec = self.iter_range(from, to).collect::<Vec<_>>();
-        let mut bytes = Vec::with_capacity(self.len() * 21);
-        serde_json::to_writer(&mut bytes, &vec).unwrap();
+        let len = self.len();
+        let count = to
+            .map_or(len, |to| to.min(len))
+            .saturating_sub(from.unwrap_or_default());
+        let mut bytes = Vec::with_capacity(count * 21 + 2);
+        bytes.push(b'[');
+        self.for_each_in_range(from, to, |_, value| {
+            if bytes.len() > 1 {
+                bytes.push(b',');
+            }
+            serde_json::to_writer(&mut bytes, value).unwrap();
+        });
+        bytes.push(b']');
         bytes
     }
 
+    /// Collects values in the specified range as CSV, one value per line.
+    ///
+    /// Values are written with their JSON representation, quoted when it contains
+    /// a comma, a quote or a line break.
+    fn collect_range_csv(&self, from: Option<usize>, to: Option<usize>) -> Result<String> {
+        let mut csv = String::new();
+        for value in self.iter_range(from, to) {
+            let field = serde_json::to_string(&value)?;
+            if field.contains([',', '"', '\n', '\r']) {
+                csv.push('"');
+                csv.push_str(&field.replace('"', "\"\""));
+                csv.push('"');
+            } else {
+                csv.push_str(&field);
+            }
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
     // /// Collects values in the specified range as strings.
     // #[inline]
     // fn collect_range_string(&self, from: Option<usize>, to: Option<usize>) -> Vec<String>
@@ -88,6 +140,8 @@ where
 pub trait AnyCollectableVec: AnyVec {
     fn collect_range_json_bytes(&self, from: Option<usize>, to: Option<usize>) -> Vec<u8>;
 
+    fn collect_range_csv(&self, from: Option<usize>, to: Option<usize>) -> Result<String>;
+
     // fn iter_range_strings(
     //     &self,
     //     from: Option<i64>,
@@ -117,6 +171,10 @@ where
         <Self as CollectableVec<V::I, V::T>>::collect_range_json_bytes(self, from, to)
     }
 
+    fn collect_range_csv(&self, from: Option<usize>, to: Option<usize>) -> Result<String> {
+        <Self as CollectableVec<V::I, V::T>>::collect_range_csv(self, from, to)
+    }
+
     // fn iter_range_strings(
     //     &self,
     //     from: Option<i64>,
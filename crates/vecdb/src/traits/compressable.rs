@@ -9,6 +9,10 @@ where
     Self: VecValue + Copy + 'static + TransparentCompressable<Self::NumberType>,
 {
     type NumberType: pco::data_types::Number;
+
+    /// Evaluated wherever values are reinterpreted as `NumberType`, so that a layout mismatch
+    /// fails to compile. The derive asserts the sizes match, generic structs included.
+    const LAYOUT_CHECK: () = ();
 }
 
 pub trait AsInnerSlice<T>
@@ -23,6 +27,7 @@ where
     T: Compressable,
 {
     fn as_inner_slice(&self) -> &[T::NumberType] {
+        let () = T::LAYOUT_CHECK;
         assert_eq!(
             std::mem::size_of::<T>(),
             std::mem::size_of::<T::NumberType>()
@@ -46,6 +51,7 @@ where
     T: Compressable,
 {
     fn from_inner_slice(vec: Vec<T::NumberType>) -> Vec<T> {
+        let () = T::LAYOUT_CHECK;
         assert_eq!(
             std::mem::size_of::<T>(),
             std::mem::size_of::<T::NumberType>()
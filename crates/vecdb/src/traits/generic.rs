@@ -1,13 +1,14 @@
 use std::{
     cmp::Ordering,
     collections::{BTreeMap, BTreeSet},
-    fs,
+    fs, iter,
     path::PathBuf,
+    result,
 };
 
 use log::info;
 use rawdb::Reader;
-use zerocopy::FromBytes;
+use zerocopy::{FromBytes, IntoBytes};
 
 use crate::{AnyStoredVec, Error, Exit, Result, SEPARATOR, Stamp, Version, likely};
 
@@ -15,6 +16,8 @@ const ONE_KIB: usize = 1024;
 const ONE_MIB: usize = ONE_KIB * ONE_KIB;
 const ONE_GIB: usize = ONE_KIB * ONE_MIB;
 const MAX_CACHE_SIZE: usize = ONE_GIB;
+/// Widest span of updated indexes read at once by `changes_since`
+const UPDATED_READ_SPAN: usize = 4 * ONE_KIB;
 
 use super::{VecIndex, VecValue};
 
@@ -79,6 +82,17 @@ where
     #[doc(hidden)]
     fn unchecked_read_at(&self, index: usize, reader: &Reader) -> Result<T>;
 
+    /// Reads the stored values in `from..to`, ignoring pushed, updated and holes,
+    /// without checking the upper bound.
+    ///
+    /// Reads one value at a time by default, formats override it with a bulk read.
+    #[doc(hidden)]
+    fn unchecked_read_range_at(&self, from: usize, to: usize, reader: &Reader) -> Result<Vec<T>> {
+        (from..to)
+            .map(|i| self.unchecked_read_at(i, reader))
+            .collect()
+    }
+
     /// Reads value at usize index, creating a temporary reader.
     /// For multiple reads, prefer `read_at()` with a reused reader.
     #[inline]
@@ -159,6 +173,59 @@ where
         Ok(Some(self.unchecked_read_at(index, reader)?))
     }
 
+    /// Same as `get_or_read_at` over `[from, to)`, `to` being capped at the length.
+    ///
+    /// Stored values are read in one go, so compressed vecs decode each page once.
+    fn get_or_read_range_at(
+        &self,
+        from: usize,
+        to: usize,
+        reader: &Reader,
+    ) -> Result<Vec<Option<T>>> {
+        let mut values = self
+            .get_unholed_or_read_range_at(from, to, reader)?
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        if !values.is_empty() {
+            self.holes()
+                .range(from..from + values.len())
+                .for_each(|&i| values[i - from] = None);
+        }
+        Ok(values)
+    }
+
+    /// Same as `get_or_read_range_at` but returns the values underneath holes.
+    fn get_unholed_or_read_range_at(
+        &self,
+        from: usize,
+        to: usize,
+        reader: &Reader,
+    ) -> Result<Vec<T>> {
+        let to = to.min(self.len());
+        if from >= to {
+            return Ok(vec![]);
+        }
+
+        let stored_len = self.stored_len();
+        let stored_to = to.min(stored_len);
+
+        let mut values = vec![];
+        if from < stored_to {
+            values = self.unchecked_read_range_at(from, stored_to, reader)?;
+            self.updated()
+                .range(from..stored_to)
+                .for_each(|(&i, value)| values[i - from] = value.clone());
+        }
+        if to > stored_len {
+            values.extend_from_slice(
+                &self.pushed()[from.saturating_sub(stored_len)..to - stored_len],
+            );
+        }
+
+        Ok(values)
+    }
+
     /// Gets value from any layer at usize index, creating a temporary reader.
     /// For multiple reads, prefer `get_or_read_at()` with a reused reader.
     #[inline]
@@ -235,6 +302,26 @@ where
         pushed.get(offset)
     }
 
+    /// Gets the first value from any layer using provided reader.
+    /// Returns None if the vec is empty or its first index is a hole.
+    #[inline]
+    fn first(&self, reader: &Reader) -> Result<Option<T>> {
+        if self.len_() == 0 {
+            return Ok(None);
+        }
+        self.get_or_read_at(0, reader)
+    }
+
+    /// Gets the last value from any layer, pushed ones included, using provided reader.
+    /// Returns None if the vec is empty or its last index is a hole.
+    #[inline]
+    fn last(&self, reader: &Reader) -> Result<Option<T>> {
+        match self.len_().checked_sub(1) {
+            Some(index) => self.get_or_read_at(index, reader),
+            None => Ok(None),
+        }
+    }
+
     // ============================================================================
     // Length Operations
     // ============================================================================
@@ -289,6 +376,12 @@ where
         self.mut_pushed().push(value)
     }
 
+    /// Pushes all `values` to the end of the vector at once.
+    #[inline]
+    fn extend_from_slice(&mut self, values: &[T]) {
+        self.mut_pushed().extend_from_slice(values)
+    }
+
     /// Pushes a value if the index equals the current length, otherwise does nothing if already exists.
     /// Returns an error if the index is too high.
     #[inline]
@@ -601,6 +694,124 @@ where
         Ok(())
     }
 
+    // ============================================================================
+    // Range Operations
+    // ============================================================================
+
+    /// Values in `from..to` as seen through the iterator: stored values with updates
+    /// applied, then pushed ones, holes skipped.
+    ///
+    /// `to` is clamped to the length, an empty range gives an empty Vec. Stored values
+    /// are read in bulk, which beats reading them one by one for large ranges.
+    #[inline]
+    fn get_range(&self, from: I, to: I, reader: &Reader) -> Result<Vec<T>> {
+        self.get_range_at(from.to_usize(), to.to_usize(), reader)
+    }
+
+    /// Same as `get_range` but with usize indexes.
+    fn get_range_at(&self, from: usize, to: usize, reader: &Reader) -> Result<Vec<T>> {
+        let to = to.min(self.len_());
+        if from >= to {
+            return Ok(vec![]);
+        }
+
+        let stored_len = self.stored_len();
+
+        let mut values = if from < stored_len {
+            self.unchecked_read_range_at(from, to.min(stored_len), reader)?
+        } else {
+            vec![]
+        };
+
+        self.updated()
+            .range(from..to)
+            .for_each(|(&i, v)| values[i - from] = v.clone());
+
+        if to > stored_len {
+            values.extend_from_slice(
+                &self.pushed()[from.saturating_sub(stored_len)..to - stored_len],
+            );
+        }
+
+        let holes = self.holes();
+        if holes.range(from..to).next().is_some() {
+            let mut index = from;
+            values.retain(|_| {
+                let keep = !holes.contains(&index);
+                index += 1;
+                keep
+            });
+        }
+
+        Ok(values)
+    }
+
+    // ============================================================================
+    // Search Operations
+    // ============================================================================
+
+    /// Binary searches a vec sorted in ascending order, like `slice::binary_search`.
+    ///
+    /// Returns `Ok` with the index of a matching value (any of them if there are several)
+    /// or `Err` with the index where `target` could be inserted while keeping the order.
+    /// Values are seen like with `get_or_read_at`, errors if the vec has holes.
+    #[inline]
+    fn binary_search(&self, target: &T, reader: &Reader) -> Result<result::Result<usize, usize>>
+    where
+        T: Ord,
+    {
+        self.binary_search_by(|value| value.cmp(target), reader)
+    }
+
+    /// Same as `binary_search` but with a comparator, like `slice::binary_search_by`.
+    #[inline]
+    fn binary_search_by<F>(&self, f: F, reader: &Reader) -> Result<result::Result<usize, usize>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        self.binary_search_by_with(f, |index| self.unchecked_read_at(index, reader))
+    }
+
+    /// Binary search over every layer, stored values being read with `read_stored`.
+    #[doc(hidden)]
+    fn binary_search_by_with<F, R>(
+        &self,
+        mut f: F,
+        mut read_stored: R,
+    ) -> Result<result::Result<usize, usize>>
+    where
+        F: FnMut(&T) -> Ordering,
+        R: FnMut(usize) -> Result<T>,
+    {
+        if !self.holes().is_empty() {
+            return Err(Error::Str("Cannot binary search a vec with holes"));
+        }
+
+        let stored_len = self.stored_len();
+        let pushed = self.pushed();
+        let updated = self.updated();
+
+        let mut low = 0;
+        let mut high = self.len_();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let ordering = if mid >= stored_len {
+                f(&pushed[mid - stored_len])
+            } else if let Some(value) = updated.get(&mid) {
+                f(value)
+            } else {
+                f(&read_stored(mid)?)
+            };
+            match ordering {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+
+        Ok(Err(low))
+    }
+
     // ============================================================================
     // Collection Operations
     // ============================================================================
@@ -624,11 +835,7 @@ where
             return Ok(vec![]);
         }
 
-        let reader = self.create_reader();
-
-        (from..to)
-            .map(|i| self.get_or_read_at(i, &reader))
-            .collect::<Result<Vec<_>>>()
+        self.get_or_read_range_at(from, to, &self.create_reader())
     }
 
     // ============================================================================
@@ -757,6 +964,32 @@ where
         Ok(self.stamp())
     }
 
+    /// Undoes `changes`, as returned by `serialize_changes` when the vec was at `stamp`, then
+    /// restores the `prev_*` state like `rollback_before`.
+    ///
+    /// Same as `rollback` with changes kept by the caller instead of in the changes directory.
+    /// They must be the last ones flushed with `stamped_flush_with_changes`, which keeps the
+    /// `prev_*` state they're built from current. Errors if they were serialized at another
+    /// stamp.
+    fn rollback_to(&mut self, stamp: Stamp, changes: &[u8]) -> Result<()> {
+        let changes_stamp = changes
+            .get(..size_of::<u64>())
+            .ok_or(Error::WrongLength)
+            .and_then(|bytes| Ok(Stamp::new(u64::read_from_bytes(bytes)?)))?;
+        if changes_stamp != stamp {
+            return Err(Error::Str("Changes weren't serialized at the given stamp"));
+        }
+
+        self.deserialize_then_undo_changes(changes)?;
+
+        *self.mut_prev_stored_len() = self.stored_len();
+        *self.mut_prev_pushed() = self.pushed().to_vec();
+        *self.mut_prev_updated() = self.updated().clone();
+        *self.mut_prev_holes() = self.holes().clone();
+
+        Ok(())
+    }
+
     /// Rolls back the most recent change set.
     fn rollback(&mut self) -> Result<()> {
         let path = self
@@ -914,6 +1147,140 @@ where
         Ok(())
     }
 
+    /// Serializes what changed since the vec was at `stamp`, for `apply_changes` to bring a
+    /// copy of it at that stamp up to date.
+    ///
+    /// What changed is found from the change sets saved by `stamped_flush_with_changes`, every
+    /// value is included if they don't go back to `stamp`. Unflushed changes are included.
+    ///
+    /// Layout, lengths and indexes being `usize`:
+    /// - `stamp` then the vec's current stamp
+    /// - the index from which values were appended or truncated, the number of values from
+    ///   there to the end, then those values, with whatever is underneath holes
+    /// - the number of values updated before that index, their indexes, then their values
+    /// - the number of holes, then every hole
+    fn changes_since(&self, stamp: Stamp) -> Result<Vec<u8>> {
+        if stamp > self.stamp() {
+            return Err(Error::Str("Stamp is past the vec's stamp"));
+        }
+
+        let mut from = self.stored_len();
+        let mut updated = self.updated().keys().copied().collect::<BTreeSet<_>>();
+
+        if stamp < self.stamp() {
+            let mut reached = false;
+
+            if let Ok(dir) = fs::read_dir(self.changes_path()) {
+                for entry in dir {
+                    let path = entry?.path();
+                    let Some(file_stamp) = path
+                        .file_name()
+                        .and_then(|name| name.to_str()?.parse::<u64>().ok())
+                        .map(Stamp::from)
+                    else {
+                        continue;
+                    };
+                    if file_stamp <= stamp || file_stamp > self.stamp() {
+                        continue;
+                    }
+
+                    let bytes = fs::read(&path)?;
+                    let (prev_stamp, lowest, indexes) =
+                        read_touched_by_changes(&bytes, Self::SIZE_OF_T)?;
+                    reached |= prev_stamp == stamp;
+                    from = from.min(lowest);
+                    updated.extend(indexes);
+                }
+            }
+
+            if !reached {
+                from = 0;
+            }
+        }
+
+        let reader = self.create_reader();
+        let len = self.len();
+
+        let mut bytes = vec![];
+        bytes.extend(stamp.as_bytes());
+        bytes.extend(self.stamp().as_bytes());
+
+        bytes.extend(from.as_bytes());
+        bytes.extend((len - from).as_bytes());
+        bytes.extend(
+            self.get_unholed_or_read_range_at(from, len, &reader)?
+                .as_bytes(),
+        );
+
+        // Neighbouring indexes are read together, so compressed vecs decode each page once
+        let mut updated_indexes = vec![];
+        let mut updated_values = vec![];
+        let mut updated = updated.range(..from).copied().peekable();
+        while let Some(first) = updated.next() {
+            let mut group = vec![first];
+            group.extend(iter::from_fn(|| {
+                updated.next_if(|&i| i - first < UPDATED_READ_SPAN)
+            }));
+            let values = self.get_or_read_range_at(first, group.last().unwrap() + 1, &reader)?;
+            group.into_iter().for_each(|i| {
+                if let Some(value) = values[i - first].clone() {
+                    updated_indexes.push(i);
+                    updated_values.push(value);
+                }
+            });
+        }
+        bytes.extend(updated_indexes.len().as_bytes());
+        bytes.extend(updated_indexes.as_bytes());
+        bytes.extend(updated_values.as_bytes());
+
+        let holes = self.holes().iter().copied().collect::<Vec<_>>();
+        bytes.extend(holes.len().as_bytes());
+        bytes.extend(holes.as_bytes());
+
+        Ok(bytes)
+    }
+
+    /// Applies changes serialized by `changes_since` on a vec at the same stamp, see there for
+    /// the layout, then takes the stamp they lead to.
+    ///
+    /// Like any other change, they're persisted on the next flush.
+    fn apply_changes(&mut self, bytes: &[u8]) -> Result<()> {
+        let mut pos = 0;
+
+        let since = read_value::<Stamp>(bytes, &mut pos)?;
+        let stamp = read_value::<Stamp>(bytes, &mut pos)?;
+        if since != self.stamp() {
+            return Err(Error::Str("Changes don't start at the vec's stamp"));
+        }
+
+        let from = read_value::<usize>(bytes, &mut pos)?;
+        let count = read_value::<usize>(bytes, &mut pos)?;
+        let values = read_values::<T>(bytes, &mut pos, count)?;
+
+        let updated_len = read_value::<usize>(bytes, &mut pos)?;
+        let updated_indexes = read_values::<usize>(bytes, &mut pos, updated_len)?;
+        let updated_values = read_values::<T>(bytes, &mut pos, updated_len)?;
+
+        let holes_len = read_value::<usize>(bytes, &mut pos)?;
+        let holes = read_values::<usize>(bytes, &mut pos, holes_len)?;
+
+        if from > self.len() {
+            return Err(Error::Str("Changes start past the end of the vec"));
+        }
+
+        self.truncate_if_needed_at(from)?;
+        self.extend_from_slice(&values);
+        updated_indexes
+            .into_iter()
+            .zip(updated_values)
+            .try_for_each(|(i, value)| self.update_at(i, value))?;
+        *self.mut_holes() = holes.into_iter().collect();
+
+        self.update_stamp(stamp);
+
+        Ok(())
+    }
+
     // ============================================================================
     // Names
     // ============================================================================
@@ -937,3 +1304,43 @@ where
         format!("{}_holes", Self::vec_region_name_with(name))
     }
 }
+
+fn read_values<V>(bytes: &[u8], pos: &mut usize, count: usize) -> Result<Vec<V>>
+where
+    V: FromBytes,
+{
+    let len = size_of::<V>() * count;
+    let section = bytes.get(*pos..*pos + len).ok_or(Error::WrongLength)?;
+    *pos += len;
+    section
+        .chunks(size_of::<V>())
+        .map(|b| V::read_from_bytes(b).map_err(|_| Error::ZeroCopyError))
+        .collect()
+}
+
+fn read_value<V>(bytes: &[u8], pos: &mut usize) -> Result<V>
+where
+    V: FromBytes,
+{
+    Ok(read_values::<V>(bytes, pos, 1)?.remove(0))
+}
+
+/// Reads the stamp a change set starts from, the lowest index it appended to or truncated
+/// from and the indexes it updated, see `serialize_changes` for the layout.
+fn read_touched_by_changes(bytes: &[u8], size_of_t: usize) -> Result<(Stamp, usize, Vec<usize>)> {
+    let mut pos = 0;
+    let stamp = read_value::<Stamp>(bytes, &mut pos)?;
+    let prev_stored_len = read_value::<usize>(bytes, &mut pos)?;
+    let stored_len = read_value::<usize>(bytes, &mut pos)?;
+
+    // Skips the truncated values, the previous and current pushed ones, then the previous updates
+    pos += read_value::<usize>(bytes, &mut pos)? * size_of_t;
+    pos += read_value::<usize>(bytes, &mut pos)? * size_of_t;
+    pos += read_value::<usize>(bytes, &mut pos)? * size_of_t;
+    pos += read_value::<usize>(bytes, &mut pos)? * (size_of::<usize>() + size_of_t);
+
+    let modified_len = read_value::<usize>(bytes, &mut pos)?;
+    let indexes = read_values::<usize>(bytes, &mut pos, modified_len)?;
+
+    Ok((stamp, prev_stored_len.min(stored_len), indexes))
+}
@@ -1,6 +1,9 @@
 use std::collections::VecDeque;
 
-use crate::{AnyStoredVec, AnyVec, BoxedVecIterator, VecIndex, VecValue, lookback::Lookback};
+use crate::{
+    AnyStoredVec, AnyVec, BoxedVecIterator, PeekableVecIterator, RevVecIterator, VecIndex,
+    VecValue, lookback::Lookback,
+};
 
 /// Trait for vectors that can be iterated.
 pub trait IterableVec<I, T>: AnyVec {
@@ -10,6 +13,39 @@ pub trait IterableVec<I, T>: AnyVec {
         I: VecIndex,
         T: VecValue;
 
+    /// Iterates over the values in `[from, to)`.
+    ///
+    /// The end is set before anything is read, so compressed vecs only decode the pages
+    /// overlapping the range.
+    fn iter_at_range(&self, from: I, to: I) -> BoxedVecIterator<'_, I, T>
+    where
+        I: VecIndex,
+        T: VecValue,
+    {
+        let mut iter = self.iter();
+        iter.set_end_to(to.to_usize());
+        iter.set_position_to(from.to_usize());
+        iter
+    }
+
+    /// Iterates over `(index, value)` pairs from the last index down to the first.
+    fn iter_rev(&self) -> RevVecIterator<'_, I, T>
+    where
+        I: VecIndex,
+        T: VecValue,
+    {
+        RevVecIterator::new(self.iter())
+    }
+
+    /// Iterates with a one-slot lookahead, see `PeekableVecIterator::peek`.
+    fn iter_peekable(&self) -> PeekableVecIterator<'_, I, T>
+    where
+        I: VecIndex,
+        T: VecValue,
+    {
+        PeekableVecIterator::new(self.iter())
+    }
+
     /// Create a windowed lookback for efficient windowed access.
     /// Uses a ring buffer if many items will be processed, otherwise uses direct access.
     fn create_lookback(&self, skip: usize, window: usize, min_start: usize) -> Lookback<'_, I, T>
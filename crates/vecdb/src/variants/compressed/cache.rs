@@ -0,0 +1,63 @@
+use std::{collections::VecDeque, sync::Arc};
+
+/// Small LRU cache of decoded pages, used by random reads.
+///
+/// Capacity is expected to be small (a handful of pages), lookups are linear.
+/// A capacity of 0 disables the cache.
+#[derive(Debug)]
+pub struct PageCache<T> {
+    capacity: usize,
+    /// Most recently used last
+    pages: VecDeque<(usize, Arc<Vec<T>>)>,
+}
+
+impl<T> PageCache<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            pages: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.pages.len() > capacity {
+            self.pages.pop_front();
+        }
+    }
+
+    pub fn get(&mut self, page_index: usize) -> Option<Arc<Vec<T>>> {
+        let position = self.pages.iter().position(|(i, _)| *i == page_index)?;
+        let entry = self.pages.remove(position)?;
+        let page = entry.1.clone();
+        self.pages.push_back(entry);
+        Some(page)
+    }
+
+    pub fn insert(&mut self, page_index: usize, page: Arc<Vec<T>>) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Some(position) = self.pages.iter().position(|(i, _)| *i == page_index) {
+            self.pages.remove(position);
+        } else if self.pages.len() == self.capacity {
+            self.pages.pop_front();
+        }
+        self.pages.push_back((page_index, page));
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.pages.clear();
+    }
+}
@@ -10,7 +10,6 @@ use rawdb::RegionMetadata;
 use crate::{
     AnyStoredVec, BUFFER_SIZE, Compressable, CompressedVec, GenericStoredVec, Result,
     TypedVecIterator, VecIndex, VecIterator, likely, unlikely,
-    variants::MAX_UNCOMPRESSED_PAGE_SIZE,
 };
 
 use super::super::pages::Pages;
@@ -21,6 +20,7 @@ pub struct CleanCompressedVecIterator<'a, I, T> {
     pub(crate) _vec: &'a CompressedVec<I, T>,
     file: File,         // Dedicated file handle for sequential reads
     file_position: u64, // Current position in the file
+    region_start: u64,
     // Compressed data buffer (to reduce syscalls)
     buffer: Vec<u8>,
     buffer_len: usize,
@@ -33,6 +33,9 @@ pub struct CleanCompressedVecIterator<'a, I, T> {
     pub(crate) stored_len: usize,
     index: usize,
     end_index: usize,
+    prefetch_pages: usize,
+    prefetched_until: usize, // Pages before this one were already advised
+    per_page: usize,
     _region_lock: RwLockReadGuard<'a, RegionMetadata>,
 }
 
@@ -41,13 +44,12 @@ where
     I: VecIndex,
     T: Compressable,
 {
-    const SIZE_OF_T: usize = size_of::<T>();
-    const PER_PAGE: usize = MAX_UNCOMPRESSED_PAGE_SIZE / Self::SIZE_OF_T;
     const NO_PAGE: usize = usize::MAX;
 
     pub fn new(vec: &'a CompressedVec<I, T>) -> Result<Self> {
         let file = vec.inner.region().open_db_read_only_file()?;
         let region_lock = vec.inner.region().meta().read();
+        let region_start = region_lock.start();
 
         let pages = vec.pages.read();
         let stored_len = vec.stored_len();
@@ -56,16 +58,20 @@ where
             _vec: vec,
             file,
             file_position: 0,
+            region_start,
             buffer: vec![0; BUFFER_SIZE],
             buffer_len: 0,
             buffer_page_start: 0,
-            decoded_values: Vec::with_capacity(Self::PER_PAGE),
+            decoded_values: Vec::with_capacity(vec.per_page),
             decoded_page_index: Self::NO_PAGE,
             decoded_len: 0,
             pages,
             stored_len,
             index: 0,
             end_index: stored_len,
+            prefetch_pages: vec.prefetch_pages,
+            prefetched_until: 0,
+            per_page: vec.per_page,
             _region_lock: region_lock,
         })
     }
@@ -104,18 +110,21 @@ where
         let last_needed_page = if self.end_index == 0 {
             0
         } else {
-            (self.end_index - 1) / Self::PER_PAGE
+            (self.end_index - 1) / self.per_page
         };
         let max_page = last_needed_page.min(self.pages.len().saturating_sub(1));
 
-        // Calculate how many pages we can fit in the buffer (respecting end_index)
+        // Calculate how many pages we can fit in the buffer (respecting end_index), pages
+        // rewritten by a flush may have moved, so only those laid back to back are read together
         let mut total_bytes = 0usize;
 
         for i in starting_page_index..=max_page {
             let page = self.pages.get(i)?;
             let page_bytes = page.bytes as usize;
 
-            if total_bytes + page_bytes > BUFFER_SIZE {
+            if page.start != start_offset + total_bytes as u64
+                || total_bytes + page_bytes > BUFFER_SIZE
+            {
                 break;
             }
 
@@ -126,14 +135,15 @@ where
             return None;
         }
 
-        if self.file_position != start_offset {
-            self.file_position = start_offset;
-            self.file.seek(SeekFrom::Start(start_offset)).unwrap();
+        let file_offset = self.region_start + start_offset;
+        if self.file_position != file_offset {
+            self.file.seek(SeekFrom::Start(file_offset)).unwrap();
         }
 
         self.file
             .read_exact(&mut self.buffer[..total_bytes])
             .unwrap();
+        self.file_position = file_offset + total_bytes as u64;
         self.buffer_len = total_bytes;
 
         Some(())
@@ -161,12 +171,46 @@ where
         Some(())
     }
 
+    /// Advise the kernel about the compressed bytes of the pages following `page_index`
+    fn prefetch_after(&mut self, page_index: usize) {
+        let from = (page_index + 1).max(self.prefetched_until);
+        let to = (page_index + 1 + self.prefetch_pages)
+            .min(self.pages.len())
+            .min(self.end_index.div_ceil(self.per_page));
+        if from >= to {
+            return;
+        }
+
+        let (start, end) = (from..to).filter_map(|i| self.pages.get(i)).fold(
+            (u64::MAX, 0),
+            |(start, end), page| {
+                (
+                    start.min(page.start),
+                    end.max(page.start + page.bytes as u64),
+                )
+            },
+        );
+        if start >= end {
+            return;
+        }
+        let len = end - start;
+        let start = self.region_start + start;
+
+        // Only a hint, reads work the same if it fails
+        let _ = self._vec.inner.region().db().advise_will_need(start, len);
+        self.prefetched_until = to;
+    }
+
     /// Decode a specific page from buffer (or read more data if needed)
     fn decode_page(&mut self, page_index: usize) -> Option<()> {
         if page_index >= self.pages.len() {
             return None;
         }
 
+        if self.prefetch_pages > 0 {
+            self.prefetch_after(page_index);
+        }
+
         // Fetch page metadata once
         let page = self.pages.get(page_index)?;
         let compressed_size = page.bytes as usize;
@@ -217,8 +261,8 @@ where
 
         self.index += 1;
 
-        let page_index = index / Self::PER_PAGE;
-        let in_page_index = index % Self::PER_PAGE;
+        let page_index = index / self.per_page;
+        let in_page_index = index % self.per_page;
 
         // Fast path: read from current decoded page
         if likely(self.has_decoded_page() && self.decoded_page_index == page_index) {
@@ -279,8 +323,8 @@ where
 
         // Check if new position is within the currently decoded page
         if self.has_decoded_page() {
-            let page_start = self.decoded_page_index * Self::PER_PAGE;
-            let page_end = page_start + Self::PER_PAGE;
+            let page_start = self.decoded_page_index * self.per_page;
+            let page_end = page_start + self.per_page;
 
             if new_index >= page_start && new_index < page_end {
                 // Keep decoded page, just update index
@@ -612,6 +656,29 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_compressed_clean_iter_prefetch() {
+        let (_temp, _db, mut vec) = setup();
+
+        for i in 0..50000 {
+            vec.push(i * 7 - 3);
+        }
+        vec.flush().unwrap();
+
+        let expected: Vec<i32> = vec.clean_iter().unwrap().collect();
+
+        for n in [1, 8, 1000] {
+            vec.set_prefetch_pages(n);
+            let collected: Vec<i32> = vec.clean_iter().unwrap().collect();
+            assert_eq!(collected, expected);
+
+            let mut iter = vec.clean_iter().unwrap();
+            iter.set_position_to(20000);
+            iter.set_end_to(30000);
+            assert_eq!(iter.collect::<Vec<_>>(), expected[20000..30000]);
+        }
+    }
+
     #[test]
     fn test_compressed_clean_iter_set_end_middle_of_page() {
         let (_temp, _db, mut vec) = setup();
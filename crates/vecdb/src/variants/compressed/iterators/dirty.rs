@@ -2,16 +2,18 @@ use std::iter::FusedIterator;
 
 use crate::{
     Compressable, CompressedVec, GenericStoredVec, Result, TypedVecIterator, VecIndex, VecIterator,
-    likely,
+    likely, unlikely,
 };
 
 use super::CleanCompressedVecIterator;
 
-/// Dirty compressed vec iterator, handles pushed values on top of stored data
+/// Dirty compressed vec iterator, handles holes, updates and pushed values on top of stored data
 pub struct DirtyCompressedVecIterator<'a, I, T> {
     inner: CleanCompressedVecIterator<'a, I, T>,
     index: usize,
     pushed_len: usize,
+    holes: bool,
+    updated: bool,
 }
 
 impl<'a, I, T> DirtyCompressedVecIterator<'a, I, T>
@@ -21,11 +23,15 @@ where
 {
     pub fn new(vec: &'a CompressedVec<I, T>) -> Result<Self> {
         let pushed_len = vec.pushed_len();
+        let holes = !vec.holes().is_empty();
+        let updated = !vec.updated().is_empty();
 
         Ok(Self {
             inner: CleanCompressedVecIterator::new(vec)?,
             index: 0,
             pushed_len,
+            holes,
+            updated,
         })
     }
 
@@ -46,8 +52,9 @@ where
         let new_pushed_len = new_total_len.saturating_sub(self.inner.stored_len);
         self.pushed_len = new_pushed_len;
 
-        // Cap inner iterator if new end is within stored range
+        // Cap inner iterator and stored length if new end is within stored range
         if absolute_end <= self.inner.stored_len {
+            self.inner.stored_len = absolute_end;
             self.inner.set_end_to(absolute_end);
         }
     }
@@ -63,10 +70,26 @@ where
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         let index = self.index;
+        if unlikely(index >= self.vec_len()) {
+            return None;
+        }
         self.index += 1;
 
+        if unlikely(self.holes) && self.inner._vec.holes().contains(&index) {
+            if index < self.inner.stored_len {
+                self.inner.next();
+            }
+            return self.next();
+        }
+
         if likely(index < self.inner.stored_len) {
-            return self.inner.next();
+            let value = self.inner.next();
+            if unlikely(self.updated)
+                && let Some(updated) = self.inner._vec.updated().get(&index)
+            {
+                return Some(*updated);
+            }
+            return value;
         }
 
         self.inner
@@ -87,6 +110,17 @@ where
             return None;
         }
 
+        // Slow path: step over each element so holes line up with the inner iterator
+        if self.holes {
+            for _ in 0..n {
+                if self.index < self.inner.stored_len {
+                    self.inner.next();
+                }
+                self.index += 1;
+            }
+            return self.next();
+        }
+
         // Skip elements in the inner iterator if we're still in the stored range
         if self.index < self.inner.stored_len {
             let skip_in_stored = (new_index.min(self.inner.stored_len)) - self.index;
@@ -110,9 +144,13 @@ where
         self.len()
     }
 
-    fn last(self) -> Option<T> {
+    fn last(mut self) -> Option<T> {
         let last_index = self.vec_len().checked_sub(1)?;
 
+        if self.holes || self.updated {
+            return self.nth(last_index - self.index);
+        }
+
         if last_index < self.inner.stored_len {
             // Last element is in stored data
             self.inner.last()
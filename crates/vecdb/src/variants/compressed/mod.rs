@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, BTreeSet},
     mem,
     path::PathBuf,
@@ -7,25 +8,30 @@ use std::{
 
 use log::info;
 use parking_lot::RwLock;
-use rawdb::{Database, Reader, Region};
+use rawdb::{Batch, Database, Reader, Region};
 
 use crate::{
-    AnyStoredVec, AnyVec, AsInnerSlice, BoxedVecIterator, Compressable, Error, Format,
+    AnyStoredVec, AnyVec, AsInnerSlice, BoxedVecIterator, Compressable, Error, Exit, Format,
     FromInnerSlice, GenericStoredVec, HEADER_OFFSET, Header, IterableVec, RawVec, Result, TypedVec,
-    VecIndex, Version, likely, variants::ImportOptions,
+    VecIndex, VecIterator, Version, likely, variants::ImportOptions,
 };
 
+mod cache;
 mod iterators;
 mod page;
 mod pages;
 
+use cache::*;
 pub use iterators::*;
 use page::*;
 use pages::*;
 
-const PCO_COMPRESSION_LEVEL: usize = 4;
-/// Maximum size in bytes of a single compressed (pco) page
-pub(crate) const MAX_UNCOMPRESSED_PAGE_SIZE: usize = 16 * 1024; // 16 KiB
+const DEFAULT_PCO_LEVEL: usize = 4;
+const MAX_PCO_LEVEL: usize = 12;
+/// Default uncompressed size in bytes of a single compressed (pco) page
+const DEFAULT_PAGE_SIZE: usize = 16 * 1024; // 16 KiB
+/// Upper bound of the page size, which must also fit the header's `u16` values per page
+const MAX_PAGE_SIZE: usize = 64 * 1024; // 64 KiB
 
 const VERSION: Version = Version::TWO;
 
@@ -38,6 +44,12 @@ const VERSION: Version = Version::TWO;
 pub struct CompressedVec<I, T> {
     inner: RawVec<I, T>,
     pages: Arc<RwLock<Pages>>,
+    /// Decoded pages kept for random reads, disabled by default
+    cache: Arc<RwLock<PageCache<T>>>,
+    /// Pages ahead of the current one that clean iterators hint the kernel about
+    prefetch_pages: usize,
+    /// Number of values per page, persisted in the header
+    per_page: usize,
 }
 
 impl<I, T> CompressedVec<I, T>
@@ -45,8 +57,6 @@ where
     I: VecIndex,
     T: Compressable,
 {
-    const PER_PAGE: usize = MAX_UNCOMPRESSED_PAGE_SIZE / Self::SIZE_OF_T;
-
     /// Same as import but will reset the vec under certain errors, so be careful !
     pub fn forced_import(db: &Database, name: &str, version: Version) -> Result<Self> {
         Self::forced_import_with((db, name, version).into())
@@ -57,11 +67,15 @@ where
         options.version = options.version + VERSION;
         let res = Self::import_with(options);
         match res {
-            Err(Error::DifferentCompressionMode)
-            | Err(Error::WrongEndian)
-            | Err(Error::WrongLength)
-            | Err(Error::DifferentVersion { .. }) => {
-                info!("Resetting {}...", options.name);
+            Err(
+                ref e @ (Error::DifferentCompressionMode
+                | Error::WrongEndian
+                | Error::CorruptedHeader
+                | Error::TypeMismatch { .. }
+                | Error::WrongLength
+                | Error::DifferentVersion { .. }),
+            ) => {
+                info!("Resetting {}: {e}", options.name);
 
                 let _ = options
                     .db
@@ -84,13 +98,48 @@ where
 
     #[inline]
     pub fn import_with(options: ImportOptions) -> Result<Self> {
-        let inner = RawVec::import_(options, Format::Compressed)?;
+        if options.pco_level.is_some_and(|level| level > MAX_PCO_LEVEL) {
+            return Err(Error::Str("Pco compression level must be within 0..=12"));
+        }
+        if options
+            .page_size
+            .is_some_and(|size| size < Self::SIZE_OF_T || size > MAX_PAGE_SIZE)
+        {
+            return Err(Error::Str(
+                "Page size must be within the size of a value and 64 KiB",
+            ));
+        }
+
+        let mut inner = RawVec::import_(options, Format::Compressed)?;
+
+        let level = options
+            .pco_level
+            .or(inner.header().pco_level())
+            .unwrap_or(DEFAULT_PCO_LEVEL);
+        if inner.header().pco_level() != Some(level) {
+            inner.mut_header().update_pco_level(level);
+        }
 
         let pages = Pages::import(options.db, &Self::pages_region_name_(options.name))?;
 
+        // Stored pages must be decoded with the geometry they were written with, which is the
+        // default one if it wasn't recorded
+        let per_page = match (inner.header().page_values(), options.page_size) {
+            (_, Some(size)) if pages.len() == 0 => size / Self::SIZE_OF_T,
+            (Some(per_page), _) => per_page,
+            (None, _) => DEFAULT_PAGE_SIZE / Self::SIZE_OF_T,
+        };
+        if inner.header().page_values() != Some(per_page) {
+            inner.mut_header().update_page_values(per_page);
+        }
+        inner.write_header_if_needed()?;
+
         let this = Self {
             inner,
             pages: Arc::new(RwLock::new(pages)),
+            cache: Arc::new(RwLock::new(PageCache::new(0))),
+            prefetch_pages: 0,
+            per_page,
         };
 
         this.update_stored_len(this.real_stored_len());
@@ -100,17 +149,199 @@ where
 
     #[inline]
     fn decode_page(&self, page_index: usize, reader: &Reader) -> Result<Vec<T>> {
-        Self::decode_page_(self.stored_len(), page_index, reader, &self.pages.read())
+        Self::decode_page_(
+            self.stored_len(),
+            self.per_page,
+            page_index,
+            reader,
+            &self.pages.read(),
+        )
+    }
+
+    /// Same as `decode_page` but goes through the page cache when enabled.
+    fn decode_page_cached(&self, page_index: usize, reader: &Reader) -> Result<Arc<Vec<T>>> {
+        if !self.cache.read().is_enabled() {
+            return Ok(Arc::new(self.decode_page(page_index, reader)?));
+        }
+
+        if let Some(page) = self.cache.write().get(page_index) {
+            return Ok(page);
+        }
+
+        let page = Arc::new(self.decode_page(page_index, reader)?);
+        self.cache.write().insert(page_index, page.clone());
+        Ok(page)
+    }
+
+    /// Sets how many decoded pages are kept around for random reads (0 disables the cache).
+    ///
+    /// The cache is cleared on every flush, since that's when stored pages change.
+    pub fn set_page_cache_capacity(&mut self, capacity: usize) {
+        self.cache.write().set_capacity(capacity);
+    }
+
+    pub fn page_cache_capacity(&self) -> usize {
+        self.cache.read().capacity()
+    }
+
+    /// Sets how many pages ahead clean iterators ask the kernel to page in (0 disables it).
+    ///
+    /// Each time an iterator moves to a new page, the compressed bytes of the next `n` pages
+    /// are advised with `MADV_WILLNEED`, which helps long scans over cold data on slow disks.
+    pub fn set_prefetch_pages(&mut self, n: usize) {
+        self.prefetch_pages = n;
+    }
+
+    pub fn prefetch_pages(&self) -> usize {
+        self.prefetch_pages
+    }
+
+    /// Number of pages flushed to disk.
+    pub fn page_count(&self) -> usize {
+        self.pages.read().len()
+    }
+
+    /// `(start, bytes, values)` of every flushed page, to diagnose compression ratios.
+    ///
+    /// Pages compress at most `page_size` bytes of values each, so `bytes` close to that
+    /// means the data barely compresses.
+    pub fn page_stats(&self) -> Vec<(u64, u32, u32)> {
+        self.pages
+            .read()
+            .iter()
+            .map(|page| (page.start, page.bytes, page.values))
+            .collect()
+    }
+
+    /// Bytes of the data region no page points to anymore.
+    ///
+    /// Pages whose re-encoding grew are written after the last one, and those that shrank
+    /// leave their tail behind. Both stay in the region until `compact` packs the pages.
+    pub fn dead_bytes(&self) -> u64 {
+        let pages = self.pages.read();
+        let used = pages.iter().map(|page| page.bytes as u64).sum::<u64>();
+        pages.data_end().map_or(0, |end| end - HEADER_OFFSET - used)
+    }
+
+    /// Same as `flush` but the writes to the vec's regions (data, pages, holes) are buffered
+    /// into `batch`, see `Database::batch` to make them durable together.
+    ///
+    /// The vec is ahead of its regions until the batch is applied, don't read from it before
+    /// then, nor keep using it if the batch is dropped.
+    pub fn flush_in(&mut self, batch: &mut Batch) -> Result<()> {
+        self.inner.write_header_if_needed_in(batch);
+        self.inner.write_holes_in(batch)?;
+
+        let stored_len = self.stored_len();
+        let pushed_len = self.pushed_len();
+        let real_stored_len = self.real_stored_len();
+        assert!(stored_len <= real_stored_len);
+        let truncated = stored_len != real_stored_len;
+        let has_new_data = pushed_len != 0;
+        let has_updated_data = !self.updated().is_empty();
+
+        if !has_new_data && !truncated && !has_updated_data {
+            // info!("Nothing to push {}", self.region_index());
+            return Ok(());
+        }
+
+        self.cache.write().clear();
+
+        let mut updated = mem::take(self.inner.mut_updated());
+
+        let mut pages = self.pages.write();
+        let pages_len = pages.len();
+        let level = self.pco_level();
+
+        // The page holding `stored_len` is re-encoded with the pushed values, pages past it were
+        // truncated away
+        let tail_page_index = self.index_to_page_index(stored_len);
+        let tail_index = self.page_index_to_index(tail_page_index);
+        assert!(tail_page_index <= pages_len);
+
+        let reader = self.create_static_reader();
+
+        let mut values = if tail_page_index < pages_len && tail_index != stored_len {
+            let mut values =
+                Self::decode_page_(stored_len, self.per_page, tail_page_index, &reader, &pages)?;
+            values.truncate(stored_len - tail_index);
+            values
+        } else {
+            vec![]
+        };
+        updated
+            .split_off(&tail_index)
+            .into_iter()
+            .for_each(|(i, v)| values[i - tail_index] = v);
+        values.append(&mut mem::take(self.inner.mut_pushed()));
+
+        if tail_page_index < pages_len {
+            pages.truncate(tail_page_index);
+        }
+        let truncate_at = pages.data_end().unwrap_or(HEADER_OFFSET);
+        let mut buf = vec![];
+
+        // Only the pages with updated values are re-encoded. Those that still fit are rewritten
+        // in place, the others move after the last page, see `dead_bytes`.
+        let mut updated = updated.into_iter().peekable();
+        while let Some(&(i, _)) = updated.peek() {
+            let page_index = self.index_to_page_index(i);
+            let page_start_index = self.page_index_to_index(page_index);
+            let mut page_values =
+                Self::decode_page_(stored_len, self.per_page, page_index, &reader, &pages)?;
+            while let Some((i, v)) = updated.next_if(|(i, _)| *i < page_start_index + self.per_page)
+            {
+                page_values[i - page_start_index] = v;
+            }
+
+            let bytes = Self::compress_page(&page_values, level);
+            let page = pages.get(page_index).unwrap();
+            let start = if bytes.len() <= page.bytes as usize {
+                batch.write_all_to_region_at(self.region(), &bytes, page.start);
+                page.start
+            } else {
+                let start = truncate_at + buf.len() as u64;
+                buf.extend_from_slice(&bytes);
+                start
+            };
+            pages.set(
+                page_index,
+                Page::new(start, bytes.len() as u32, page_values.len() as u32),
+            );
+        }
+
+        drop(reader);
+
+        values.chunks(self.per_page).for_each(|chunk| {
+            let bytes = Self::compress_page(chunk, level);
+            let page = Page::new(
+                truncate_at + buf.len() as u64,
+                bytes.len() as u32,
+                chunk.len() as u32,
+            );
+            buf.extend_from_slice(&bytes);
+            let page_index = pages.len();
+            pages.checked_push(page_index, page);
+        });
+
+        batch.truncate_write_all_to_region(self.region(), truncate_at, &buf);
+
+        self.update_stored_len(stored_len + pushed_len);
+
+        pages.flush_in(batch);
+
+        Ok(())
     }
 
     #[inline]
     fn decode_page_(
         stored_len: usize,
+        per_page: usize,
         page_index: usize,
         reader: &Reader,
         pages: &Pages,
     ) -> Result<Vec<T>> {
-        if Self::page_index_to_index(page_index) >= stored_len {
+        if page_index * per_page >= stored_len {
             return Err(Error::IndexTooHigh);
         } else if page_index >= pages.len() {
             return Err(Error::ExpectVecToHaveIndex);
@@ -140,22 +371,28 @@ where
     }
 
     #[inline]
-    fn compress_page(chunk: &[T]) -> Vec<u8> {
-        if chunk.len() > Self::PER_PAGE {
-            panic!();
-        }
+    fn compress_page(chunk: &[T], level: usize) -> Vec<u8> {
+        pco::standalone::simpler_compress(chunk.as_inner_slice(), level).unwrap()
+    }
+
+    /// Compression level used for new pages.
+    pub fn pco_level(&self) -> usize {
+        self.header().pco_level().unwrap_or(DEFAULT_PCO_LEVEL)
+    }
 
-        pco::standalone::simpler_compress(chunk.as_inner_slice(), PCO_COMPRESSION_LEVEL).unwrap()
+    /// Uncompressed size in bytes of a page.
+    pub fn page_size(&self) -> usize {
+        self.per_page * Self::SIZE_OF_T
     }
 
     #[inline]
-    fn index_to_page_index(index: usize) -> usize {
-        index / Self::PER_PAGE
+    fn index_to_page_index(&self, index: usize) -> usize {
+        index / self.per_page
     }
 
     #[inline]
-    fn page_index_to_index(page_index: usize) -> usize {
-        page_index * Self::PER_PAGE
+    fn page_index_to_index(&self, page_index: usize) -> usize {
+        page_index * self.per_page
     }
 
     #[inline]
@@ -187,7 +424,60 @@ where
 
     #[inline]
     pub fn is_dirty(&self) -> bool {
-        !self.is_pushed_empty()
+        !self.is_pushed_empty() || !self.holes().is_empty() || !self.updated().is_empty()
+    }
+
+    /// Rewrites the vec without its holes, see `RawVec::compact`, then moves its pages back
+    /// to back to reclaim the bytes left behind by pages that were rewritten elsewhere, see
+    /// `dead_bytes`.
+    pub fn compact(&mut self, exit: &Exit) -> Result<()> {
+        if let Some(&first_hole) = self.holes().first() {
+            let mut iter = self.dirty_iter()?;
+            iter.set_position_to(first_hole);
+            let values = iter.collect::<Vec<_>>();
+
+            self.truncate_if_needed_at(first_hole)?;
+            self.mut_pushed().extend(values);
+        }
+
+        self.safe_flush(exit)?;
+
+        let _lock = exit.lock();
+        self.pack_pages()
+    }
+
+    /// Moves every page from the first gap onwards right after the previous one, as is.
+    fn pack_pages(&mut self) -> Result<()> {
+        let mut pages = self.pages.write();
+
+        // Where the first page out of place should start
+        let mut at = HEADER_OFFSET;
+        let Some(first_moved) = pages.iter().position(|page| {
+            if page.start != at {
+                return true;
+            }
+            at += page.bytes as u64;
+            false
+        }) else {
+            return Ok(());
+        };
+
+        let reader = self.create_static_reader();
+        let mut buf = vec![];
+        for page_index in first_moved..pages.len() {
+            let page = pages.get(page_index).unwrap().clone();
+            let start = at + buf.len() as u64;
+            buf.extend_from_slice(reader.unchecked_read(page.start, page.bytes as u64));
+            pages.set(page_index, Page::new(start, page.bytes, page.values));
+        }
+        drop(reader);
+
+        let mut batch = self.db().begin_batch();
+        batch.truncate_write_all_to_region(self.region(), at, &buf);
+        pages.flush_in(&mut batch);
+        batch.apply()?;
+
+        Ok(())
     }
 
     /// Removes this vector and all its associated regions from the database
@@ -209,6 +499,9 @@ impl<I, T> Clone for CompressedVec<I, T> {
         Self {
             inner: self.inner.clone(),
             pages: self.pages.clone(),
+            cache: self.cache.clone(),
+            prefetch_pages: self.prefetch_pages,
+            per_page: self.per_page,
         }
     }
 }
@@ -286,89 +579,16 @@ where
         self.inner.stored_len()
     }
 
+    /// Derived from the number of pages and the last one, the others aren't read.
     #[inline]
     fn real_stored_len(&self) -> usize {
-        self.pages.read().stored_len(Self::PER_PAGE)
+        self.pages.read().stored_len(self.per_page)
     }
 
     fn flush(&mut self) -> Result<()> {
-        self.inner.write_header_if_needed()?;
-
-        let stored_len = self.stored_len();
-        let pushed_len = self.pushed_len();
-        let real_stored_len = self.real_stored_len();
-        assert!(stored_len <= real_stored_len);
-        let truncated = stored_len != real_stored_len;
-        let has_new_data = pushed_len != 0;
-
-        if !has_new_data && !truncated {
-            // info!("Nothing to push {}", self.region_index());
-            return Ok(());
-        }
-
-        let mut pages = self.pages.write();
-        let pages_len = pages.len();
-        let starting_page_index = Self::index_to_page_index(stored_len);
-        assert!(starting_page_index <= pages_len);
-
-        let mut values = vec![];
-
-        let offset = HEADER_OFFSET;
-
-        let truncate_at = if starting_page_index < pages_len {
-            let len = stored_len % Self::PER_PAGE;
-
-            if len != 0 {
-                let mut page_values = Self::decode_page_(
-                    stored_len,
-                    starting_page_index,
-                    &self.create_static_reader(),
-                    &pages,
-                )?;
-                page_values.truncate(len);
-                values = page_values;
-            }
-
-            pages.truncate(starting_page_index).unwrap().start
-        } else {
-            pages
-                .last()
-                .map_or(offset, |page| page.start + page.bytes as u64)
-        };
-
-        values.append(&mut mem::take(self.inner.mut_pushed()));
-
-        let compressed = values
-            .chunks(Self::PER_PAGE)
-            .map(|chunk| (Self::compress_page(chunk), chunk.len()))
-            .collect::<Vec<_>>();
-
-        compressed.iter().enumerate().for_each(|(i, (bytes, len))| {
-            let page_index = starting_page_index + i;
-
-            let start = if page_index != 0 {
-                let prev = pages.get(page_index - 1).unwrap();
-                prev.start + prev.bytes as u64
-            } else {
-                offset
-            };
-
-            let page = Page::new(start, bytes.len() as u32, *len as u32);
-
-            pages.checked_push(page_index, page);
-        });
-
-        let buf = compressed
-            .into_iter()
-            .flat_map(|(v, _)| v)
-            .collect::<Vec<_>>();
-
-        self.region().truncate_write_all(truncate_at, &buf)?;
-
-        self.update_stored_len(stored_len + pushed_len);
-
-        pages.flush()?;
-
+        let mut batch = self.db().begin_batch();
+        self.flush_in(&mut batch)?;
+        batch.apply()?;
         Ok(())
     }
 
@@ -390,15 +610,52 @@ where
 {
     #[inline]
     fn unchecked_read_at(&self, index: usize, reader: &Reader) -> Result<T> {
-        let page_index = Self::index_to_page_index(index);
-        let decoded_index = index % Self::PER_PAGE;
+        let page_index = self.index_to_page_index(index);
+        let decoded_index = index % self.per_page;
         Ok(unsafe {
             *self
-                .decode_page(page_index, reader)?
+                .decode_page_cached(page_index, reader)?
                 .get_unchecked(decoded_index)
         })
     }
 
+    /// Keeps the last decoded page around, since the final probes tend to land in the same one.
+    fn binary_search_by<F>(
+        &self,
+        f: F,
+        reader: &Reader,
+    ) -> Result<std::result::Result<usize, usize>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let mut last_page: Option<(usize, Arc<Vec<T>>)> = None;
+        self.binary_search_by_with(f, |index| {
+            let page_index = self.index_to_page_index(index);
+            let page = match &last_page {
+                Some((last_index, page)) if *last_index == page_index => page,
+                _ => {
+                    let page = self.decode_page_cached(page_index, reader)?;
+                    &last_page.insert((page_index, page)).1
+                }
+            };
+            Ok(page[index % self.per_page])
+        })
+    }
+
+    fn unchecked_read_range_at(&self, from: usize, to: usize, reader: &Reader) -> Result<Vec<T>> {
+        let mut values = Vec::with_capacity(to - from);
+        let mut index = from;
+        while index < to {
+            let page_index = self.index_to_page_index(index);
+            let page = self.decode_page_cached(page_index, reader)?;
+            let page_start = self.page_index_to_index(page_index);
+            let end = (to - page_start).min(page.len());
+            values.extend_from_slice(&page[index - page_start..end]);
+            index = page_start + end;
+        }
+        Ok(values)
+    }
+
     #[inline]
     fn pushed(&self) -> &[T] {
         self.inner.pushed()
@@ -421,7 +678,7 @@ where
     }
     #[inline]
     fn mut_holes(&mut self) -> &mut BTreeSet<usize> {
-        panic!("unsupported for now")
+        self.inner.mut_holes()
     }
     #[inline]
     fn prev_holes(&self) -> &BTreeSet<usize> {
@@ -429,7 +686,7 @@ where
     }
     #[inline]
     fn mut_prev_holes(&mut self) -> &mut BTreeSet<usize> {
-        panic!("unsupported for now")
+        self.inner.mut_prev_holes()
     }
     #[inline]
     fn updated(&self) -> &BTreeMap<usize, T> {
@@ -437,7 +694,7 @@ where
     }
     #[inline]
     fn mut_updated(&mut self) -> &mut BTreeMap<usize, T> {
-        panic!("unsupported for now")
+        self.inner.mut_updated()
     }
     #[inline]
     fn prev_updated(&self) -> &BTreeMap<usize, T> {
@@ -445,7 +702,7 @@ where
     }
     #[inline]
     fn mut_prev_updated(&mut self) -> &mut BTreeMap<usize, T> {
-        panic!("unsupported for now")
+        self.inner.mut_prev_updated()
     }
 
     #[inline]
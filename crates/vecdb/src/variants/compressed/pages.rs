@@ -1,14 +1,17 @@
-use rawdb::{Database, Region};
+use std::sync::OnceLock;
+
+use rawdb::{Batch, Database, Region};
 use zerocopy::{FromBytes, IntoBytes};
 
-use crate::Result;
+use crate::{Error, Result};
 
 use super::Page;
 
 #[derive(Debug, Clone)]
 pub struct Pages {
     region: Region,
-    vec: Vec<Page>,
+    /// Read from the region the first time a page is needed, so opening a vec doesn't
+    vec: OnceLock<Vec<Page>>,
     change_at: Option<usize>,
 }
 
@@ -18,54 +21,82 @@ impl Pages {
     pub fn import(db: &Database, name: &str) -> Result<Self> {
         let region = db.create_region_if_needed(name)?;
 
-        let vec = region
-            .create_reader()
-            .read_all()
-            .chunks(Self::SIZE_OF_PAGE)
-            .map(|b| Page::read_from_bytes(b).map_err(|e| e.into()))
-            .collect::<Result<_>>()?;
+        if !region
+            .meta()
+            .read()
+            .len()
+            .is_multiple_of(Self::SIZE_OF_PAGE as u64)
+        {
+            return Err(Error::WrongLength);
+        }
 
         Ok(Self {
             region,
-            vec,
+            vec: OnceLock::new(),
             change_at: None,
         })
     }
 
-    pub fn flush(&mut self) -> Result<()> {
-        if self.change_at.is_none() {
-            return Ok(());
-        }
+    fn vec(&self) -> &Vec<Page> {
+        self.vec.get_or_init(|| {
+            self.region
+                .create_reader()
+                .read_all()
+                .chunks_exact(Self::SIZE_OF_PAGE)
+                .map(|bytes| Page::read_from_bytes(bytes).unwrap())
+                .collect()
+        })
+    }
 
-        let change_at = self.change_at.take().unwrap();
-        let at = (change_at * Self::SIZE_OF_PAGE) as u64;
+    fn vec_mut(&mut self) -> &mut Vec<Page> {
+        self.vec();
+        self.vec.get_mut().unwrap()
+    }
 
-        self.region
-            .truncate_write_all(at, self.vec[change_at..].as_bytes())?;
+    pub fn flush_in(&mut self, batch: &mut Batch) {
+        let Some(change_at) = self.change_at.take() else {
+            return;
+        };
 
-        Ok(())
+        let at = (change_at * Self::SIZE_OF_PAGE) as u64;
+
+        batch.truncate_write_all_to_region(&self.region, at, self.vec()[change_at..].as_bytes());
     }
 
+    /// Taken from the region's length until the pages are read.
     pub fn len(&self) -> usize {
-        self.vec.len()
+        self.vec.get().map_or_else(
+            || (self.region.meta().read().len() / Self::SIZE_OF_PAGE as u64) as usize,
+            Vec::len,
+        )
     }
 
     pub fn get(&self, page_index: usize) -> Option<&Page> {
-        self.vec.get(page_index)
+        self.vec().get(page_index)
     }
 
-    pub fn last(&self) -> Option<&Page> {
-        self.vec.last()
+    pub fn iter(&self) -> impl Iterator<Item = &Page> {
+        self.vec().iter()
     }
 
     pub fn checked_push(&mut self, page_index: usize, page: Page) {
-        if page_index != self.vec.len() {
+        if page_index != self.len() {
             panic!();
         }
 
         self.set_changed_at(page_index);
 
-        self.vec.push(page);
+        self.vec_mut().push(page);
+    }
+
+    pub fn set(&mut self, page_index: usize, page: Page) {
+        self.set_changed_at(page_index);
+        self.vec_mut()[page_index] = page;
+    }
+
+    /// End of the furthest page's bytes, pages rewritten in place or moved leave gaps before it.
+    pub fn data_end(&self) -> Option<u64> {
+        self.iter().map(|page| page.start + page.bytes as u64).max()
     }
 
     fn set_changed_at(&mut self, page_index: usize) {
@@ -80,17 +111,29 @@ impl Pages {
 
     pub fn truncate(&mut self, page_index: usize) -> Option<Page> {
         let page = self.get(page_index).cloned();
-        self.vec.truncate(page_index);
+        self.vec_mut().truncate(page_index);
         self.set_changed_at(page_index);
         page
     }
 
+    /// Only reads the last page if they weren't read yet.
     pub fn stored_len(&self, per_page: usize) -> usize {
-        if let Some(last) = self.last() {
-            (self.len() - 1) * per_page + last.values as usize
-        } else {
-            0
+        let len = self.len();
+        if len == 0 {
+            return 0;
         }
+        let last_values = match self.vec.get() {
+            Some(vec) => vec[len - 1].values,
+            None => {
+                let reader = self.region.create_reader();
+                let bytes = reader.unchecked_read(
+                    ((len - 1) * Self::SIZE_OF_PAGE) as u64,
+                    Self::SIZE_OF_PAGE as u64,
+                );
+                Page::read_from_bytes(bytes).unwrap().values
+            }
+        };
+        (len - 1) * per_page + last_values as usize
     }
 
     pub fn remove(self) -> Result<()> {
@@ -105,12 +105,12 @@ where
                 vec: EagerVec::forced_import_with(options, format)?,
                 deps: Dependencies::From1(source, compute),
             },
-            Computation::Lazy => Self::LazyFrom1(LazyVecFrom1::init(
+            Computation::Lazy => Self::LazyFrom1(LazyVecFrom1::try_init(
                 options.name,
                 options.version,
                 source,
                 compute,
-            )),
+            )?),
         })
     }
 
@@ -149,13 +149,13 @@ where
                 vec: EagerVec::forced_import_with(options, format)?,
                 deps: Dependencies::From2((source1, source2), compute),
             },
-            Computation::Lazy => Self::LazyFrom2(LazyVecFrom2::init(
+            Computation::Lazy => Self::LazyFrom2(LazyVecFrom2::try_init(
                 options.name,
                 options.version,
                 source1,
                 source2,
                 compute,
-            )),
+            )?),
         })
     }
 
@@ -197,14 +197,14 @@ where
                 vec: EagerVec::forced_import_with(options, format)?,
                 deps: Dependencies::From3((source1, source2, source3), compute),
             },
-            Computation::Lazy => Self::LazyFrom3(LazyVecFrom3::init(
+            Computation::Lazy => Self::LazyFrom3(LazyVecFrom3::try_init(
                 options.name,
                 options.version,
                 source1,
                 source2,
                 source3,
                 compute,
-            )),
+            )?),
         })
     }
 
@@ -259,6 +259,26 @@ where
         }
     }
 
+    fn sources_version_breakdown(&self) -> Vec<(String, Version)> {
+        let ComputedVec::Eager { deps, .. } = self else {
+            return vec![];
+        };
+        match deps {
+            Dependencies::From1(source, _) => source.version_breakdown(),
+            Dependencies::From2((source1, source2), _) => {
+                let mut v = source1.version_breakdown();
+                v.extend(source2.version_breakdown());
+                v
+            }
+            Dependencies::From3((source1, source2, source3), _) => {
+                let mut v = source1.version_breakdown();
+                v.extend(source2.version_breakdown());
+                v.extend(source3.version_breakdown());
+                v
+            }
+        }
+    }
+
     /// Removes the stored data if this is an Eager computed vector.
     /// For Lazy vectors (which have no stored data), this is a no-op.
     pub fn remove_if_stored(self) -> Result<()> {
@@ -291,6 +311,19 @@ where
         }
     }
 
+    fn version_breakdown(&self) -> Vec<(String, Version)> {
+        match self {
+            ComputedVec::Eager { vec, .. } => {
+                let mut v = vec.version_breakdown();
+                v.extend(self.sources_version_breakdown());
+                v
+            }
+            ComputedVec::LazyFrom1(v) => v.version_breakdown(),
+            ComputedVec::LazyFrom2(v) => v.version_breakdown(),
+            ComputedVec::LazyFrom3(v) => v.version_breakdown(),
+        }
+    }
+
     fn name(&self) -> &str {
         match self {
             ComputedVec::Eager { vec, .. } => vec.name(),
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, BTreeSet, VecDeque},
     f32,
     fmt::Debug,
@@ -10,10 +11,14 @@ use std::{
 use rawdb::{Database, Reader, Region};
 
 mod checked_sub;
+mod rolling_moments;
 mod saturating_add;
+mod sliding_median;
 
 pub use checked_sub::*;
+use rolling_moments::*;
 pub use saturating_add::*;
+use sliding_median::*;
 
 use crate::{
     AnyStoredVec, AnyVec, BoxedVecIterator, CollectableVec, Compressable, Exit, Format,
@@ -22,6 +27,9 @@ use crate::{
     variants::{Header, ImportOptions},
 };
 
+/// How many values long compute loops push between two checks of `Exit`.
+pub const EXIT_CHECK_INTERVAL: usize = 1 << 16;
+
 /// Stored vector with eager computation methods for deriving values from other vectors.
 ///
 /// Wraps a StoredVec and provides various computation methods (transform, arithmetic operations,
@@ -72,6 +80,19 @@ where
         self.0.header().vec_version()
     }
 
+    /// Every `EXIT_CHECK_INTERVAL` values, flushes what was computed before `i` and errors
+    /// with `Interrupted` if the exit was triggered.
+    ///
+    /// Loops calling it hold `Exit::checkpointing` so that Ctrl-C waits for that flush.
+    #[inline]
+    fn exit_checkpoint(&mut self, i: usize, exit: &Exit) -> Result<()> {
+        if i.is_multiple_of(EXIT_CHECK_INTERVAL) && exit.is_triggered() {
+            self.safe_flush(exit)?;
+            return exit.check();
+        }
+        Ok(())
+    }
+
     pub fn compute_to<F>(
         &mut self,
         max_from: I,
@@ -87,7 +108,9 @@ where
 
         let from = max_from.to_usize().min(self.len());
 
+        let _checkpointing = exit.checkpointing();
         (from..to).try_for_each(|i| {
+            self.exit_checkpoint(i, exit)?;
             let (i, v) = t(I::from(i));
             self.forced_push(i, v, exit)
         })?;
@@ -146,7 +169,9 @@ where
 
         let skip = max_from.to_usize().min(self.len());
 
+        let _checkpointing = exit.checkpointing();
         other.iter().enumerate().skip(skip).try_for_each(|(a, b)| {
+            self.exit_checkpoint(a, exit)?;
             let (i, v) = t((A::from(a), b, self));
             self.forced_push(i, v, exit)
         })?;
@@ -188,6 +213,45 @@ where
         self.safe_flush(exit)
     }
 
+    /// Computes `f(i, b, c)` for each index, driving both sources forward in lockstep.
+    ///
+    /// Requires both sources to have the same length and to share this vec's index type.
+    /// Neither source is ever seeked, which keeps compressed sources from re-decoding pages.
+    pub fn compute_zip2<B, C, F>(
+        &mut self,
+        max_from: I,
+        other1: &impl IterableVec<I, B>,
+        other2: &impl IterableVec<I, C>,
+        mut f: F,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        B: VecValue,
+        C: VecValue,
+        F: FnMut(I, B, C) -> T,
+    {
+        debug_assert_eq!(other1.len(), other2.len());
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + other1.version() + other2.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        other1
+            .iter()
+            .skip(skip)
+            .zip(other2.iter().skip(skip))
+            .enumerate()
+            .try_for_each(|(i, (b, c))| {
+                let i = I::from(skip + i);
+                let v = f(i, b, c);
+                self.forced_push(i, v, exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_transform3<A, B, C, D, F>(
         &mut self,
         max_from: A,
@@ -235,6 +299,33 @@ where
         self.safe_flush(exit)
     }
 
+    /// Clamps each value of `source` between the values of `lower` and `upper` at the same index.
+    ///
+    /// When bands cross (`lower > upper`), `lower` wins.
+    pub fn compute_clamp<T2, T3, T4>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        lower: &impl IterableVec<I, T3>,
+        upper: &impl IterableVec<I, T4>,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<T2> + From<T3> + From<T4> + Ord,
+        T2: VecValue,
+        T3: VecValue,
+        T4: VecValue,
+    {
+        self.compute_transform3(
+            max_from,
+            source,
+            lower,
+            upper,
+            |(i, v, lower, upper, ..)| (i, T::from(v).min(T::from(upper)).max(T::from(lower))),
+            exit,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn compute_transform4<A, B, C, D, E, F>(
         &mut self,
@@ -288,6 +379,110 @@ where
         self.safe_flush(exit)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn compute_transform5<A, B, C, D, E, G, F>(
+        &mut self,
+        max_from: A,
+        other1: &impl IterableVec<A, B>,
+        other2: &impl IterableVec<A, C>,
+        other3: &impl IterableVec<A, D>,
+        other4: &impl IterableVec<A, E>,
+        other5: &impl IterableVec<A, G>,
+        mut t: F,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        A: VecIndex,
+        B: VecValue,
+        C: VecValue,
+        D: VecValue,
+        E: VecValue,
+        G: VecValue,
+        F: FnMut((A, B, C, D, E, G, &Self)) -> (I, T),
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO
+                + self.inner_version()
+                + other1.version()
+                + other2.version()
+                + other3.version()
+                + other4.version()
+                + other5.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut iter2 = other2.iter().skip(skip);
+        let mut iter3 = other3.iter().skip(skip);
+        let mut iter4 = other4.iter().skip(skip);
+        let mut iter5 = other5.iter().skip(skip);
+
+        other1
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(a, b)| {
+                let (i, v) = t((
+                    A::from(a),
+                    b,
+                    iter2.next().unwrap(),
+                    iter3.next().unwrap(),
+                    iter4.next().unwrap(),
+                    iter5.next().unwrap(),
+                    self,
+                ));
+                self.forced_push(i, v, exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Like `compute_transform5` for any number of sources sharing a value type.
+    ///
+    /// `t` gets the values of all sources at each index, in the order of `others`. The first
+    /// source drives the iteration, like `other1` in the fixed-arity versions.
+    pub fn compute_transform_n<A, B, F>(
+        &mut self,
+        max_from: A,
+        others: &[&dyn IterableVec<A, B>],
+        mut t: F,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        A: VecIndex,
+        B: VecValue,
+        F: FnMut(A, &[B], &Self) -> (I, T),
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + others.iter().map(|v| v.version()).sum(),
+        )?;
+
+        if others.is_empty() {
+            unreachable!("others should've length of 1 at least");
+        }
+
+        let skip = max_from.to_usize().min(self.len());
+        let mut others_iter = others[1..]
+            .iter()
+            .map(|v| v.iter().skip(skip))
+            .collect::<Vec<_>>();
+        let mut values = Vec::with_capacity(others.len());
+
+        others[0]
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(a, b)| {
+                values.clear();
+                values.push(b);
+                values.extend(others_iter.iter_mut().map(|iter| iter.next().unwrap()));
+                let (i, v) = t(A::from(a), &values, self);
+                self.forced_push(i, v, exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_add(
         &mut self,
         max_from: I,
@@ -368,6 +563,36 @@ where
         )
     }
 
+    /// Same as `compute_divide` but pushes `default` when the divider is zero (`T3::default()`).
+    pub fn compute_divide_or<T2, T3>(
+        &mut self,
+        max_from: I,
+        divided: &impl IterableVec<I, T2>,
+        divider: &impl IterableVec<I, T3>,
+        default: T,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T2: VecValue,
+        T3: VecValue + Default + PartialEq,
+        T: From<T2> + Div<T3, Output = T>,
+    {
+        let zero = T3::default();
+        self.compute_transform2(
+            max_from,
+            divided,
+            divider,
+            |(i, v1, v2, ..)| {
+                if v2 == zero {
+                    (i, default)
+                } else {
+                    (i, T::from(v1) / v2)
+                }
+            },
+            exit,
+        )
+    }
+
     pub fn compute_all_time_high<T2>(
         &mut self,
         max_from: I,
@@ -526,9 +751,10 @@ where
             Version::ZERO + self.inner_version() + other.version(),
         )?;
 
-        let skip = max_from
-            .to_usize()
-            .min(self.into_iter().last().map_or(0_usize, |v| v.to_usize()));
+        let skip = max_from.to_usize().min(
+            self.last(&self.create_reader())?
+                .map_or(0_usize, |v| v.to_usize()),
+        );
 
         let mut prev_i = None;
         other
@@ -553,6 +779,180 @@ where
         self.safe_flush(exit)
     }
 
+    /// Aggregates a fine grained `source` into this coarser vec.
+    ///
+    /// `fine_to_coarse` maps each fine index to its coarse index and must be non-decreasing.
+    /// Every bucket is reduced with `reducer` (sum, mean, last, ohlc, ...).
+    /// Empty buckets (no fine index mapping to them) are emitted as `reducer(&[])`,
+    /// so the reducer decides the fill value.
+    pub fn compute_resample<I2, T2, R>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I2, T2>,
+        fine_to_coarse: &impl IterableVec<I2, I>,
+        reducer: R,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        I: VecValue,
+        I2: VecIndex,
+        T2: VecValue,
+        R: Fn(&[T2]) -> T,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version() + fine_to_coarse.version(),
+        )?;
+
+        // The last bucket may have been emitted before all of its fine values existed
+        let skip = max_from.to_usize().min(self.len().saturating_sub(1));
+
+        // First fine index of the bucket at `skip`, `fine_to_coarse` being sorted
+        let mut coarse_iter = fine_to_coarse.iter();
+        let (mut low, mut high) = (0, fine_to_coarse.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            coarse_iter.set_position_to(mid);
+            if coarse_iter.next().unwrap().to_usize() < skip {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let fine_skip = low;
+        coarse_iter.set_position_to(fine_skip);
+
+        let mut source_iter = source.iter();
+        source_iter.set_position_to(fine_skip);
+
+        let mut bucket = vec![];
+        let mut bucket_i = skip;
+
+        coarse_iter
+            .zip(source_iter)
+            .try_for_each(|(coarse, v)| -> Result<()> {
+                let coarse = coarse.to_usize();
+                while bucket_i < coarse {
+                    self.forced_push_at(bucket_i, reducer(&bucket), exit)?;
+                    bucket.clear();
+                    bucket_i += 1;
+                }
+                bucket.push(v);
+                Ok(())
+            })?;
+
+        if !bucket.is_empty() {
+            self.forced_push_at(bucket_i, reducer(&bucket), exit)?;
+        }
+
+        self.safe_flush(exit)
+    }
+
+    /// Carries the last non-hole value of `value_source` forward over every index of `target_len_source`.
+    ///
+    /// Indexes before the first real value are emitted as `default`.
+    pub fn compute_forward_fill<A, T2>(
+        &mut self,
+        max_from: I,
+        target_len_source: &impl IterableVec<I, A>,
+        value_source: &impl GenericStoredVec<I, T2>,
+        default: T,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        A: VecValue,
+        T2: VecValue,
+        T: From<T2>,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO
+                + self.inner_version()
+                + target_len_source.version()
+                + value_source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut last = match skip.checked_sub(1) {
+            Some(prev_i) => self.get_pushed_or_read_at_once(prev_i)?,
+            None => None,
+        }
+        .unwrap_or(default);
+
+        let values = value_source.get_or_read_range_at(
+            skip,
+            target_len_source.len(),
+            &value_source.create_reader(),
+        )?;
+
+        (skip..target_len_source.len()).try_for_each(|i| -> Result<()> {
+            if let Some(v) = values.get(i - skip).cloned().flatten() {
+                last = T::from(v);
+            }
+            self.forced_push_at(i, last, exit)
+        })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Fills every index of `target_len_source` with the next non-hole value of `value_source`.
+    ///
+    /// Indexes after the last real value are emitted as `default`, they're recomputed
+    /// once a real value shows up after them.
+    pub fn compute_back_fill<A, T2>(
+        &mut self,
+        max_from: I,
+        target_len_source: &impl IterableVec<I, A>,
+        value_source: &impl GenericStoredVec<I, T2>,
+        default: T,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        A: VecValue,
+        T2: VecValue,
+        T: From<T2>,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO
+                + self.inner_version()
+                + target_len_source.version()
+                + value_source.version(),
+        )?;
+
+        // Trailing gaps were filled with the default, rewind to right after the last real value
+        let mut from = max_from.to_usize().min(self.len());
+        while from > 0 && (from > value_source.len() || value_source.holes().contains(&(from - 1)))
+        {
+            from -= 1;
+        }
+
+        let mut values = value_source.get_or_read_range_at(
+            from,
+            target_len_source.len(),
+            &value_source.create_reader(),
+        )?;
+        values.resize(target_len_source.len().saturating_sub(from), None);
+
+        let mut next = default;
+        let mut filled = values
+            .into_iter()
+            .rev()
+            .map(|v| {
+                if let Some(v) = v {
+                    next = T::from(v);
+                }
+                next
+            })
+            .collect::<Vec<_>>();
+        filled.reverse();
+
+        filled
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(i, v)| self.forced_push_at(from + i, v, exit))?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_count_from_indexes<T2, T3>(
         &mut self,
         max_from: I,
@@ -628,6 +1028,54 @@ where
         self.safe_flush(exit)
     }
 
+    /// Like `compute_resample`, but with buckets defined by `first_indexes`: each one covers
+    /// `source` from its first index to the next one (or to the end of `source` for the last one).
+    ///
+    /// Generalizes `compute_sum_from_indexes` to any aggregation (mean, last, ohlc, ...).
+    pub fn compute_resample_from_indexes<A, T2, R>(
+        &mut self,
+        max_from: I,
+        first_indexes: &impl IterableVec<I, A>,
+        source: &impl IterableVec<A, T2>,
+        reducer: R,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        A: VecIndex + VecValue,
+        T2: VecValue,
+        R: Fn(&[T2]) -> T,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + first_indexes.version() + source.version(),
+        )?;
+
+        let mut other_iter = first_indexes.iter();
+        let mut source_iter = source.iter();
+        let mut bucket = vec![];
+        let skip = max_from.to_usize().min(self.len());
+        first_indexes
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, first_index)| {
+                let end = other_iter
+                    .get_at(i + 1)
+                    .map(|v| v.to_usize())
+                    .unwrap_or_else(|| source.len());
+
+                let start = first_index.to_usize();
+                bucket.clear();
+                if start < end {
+                    source_iter.set_position_to(start);
+                    bucket.extend((&mut source_iter).take(end - start));
+                }
+
+                self.forced_push_at(i, reducer(&bucket), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_is_first_ordered<A>(
         &mut self,
         max_from: I,
@@ -815,11 +1263,14 @@ where
             });
         }
 
+        let _checkpointing = exit.checkpointing();
         source
             .iter()
             .enumerate()
             .skip(skip)
             .try_for_each(|(i, value)| {
+                self.exit_checkpoint(i, exit)?;
+
                 let value = T::from(value);
 
                 let processed_values_count = i.to_usize() + 1;
@@ -847,7 +1298,164 @@ where
         self.safe_flush(exit)
     }
 
-    pub fn compute_sum_from_indexes<T2, T3>(
+    /// Computes the running total of `source`, same as `compute_sum` with an unbounded window.
+    ///
+    /// Keeps a single accumulator, resumed from the last kept value, instead of a window.
+    pub fn compute_cumulative<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: Add<T, Output = T> + From<T2> + Default,
+        T2: VecValue,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+        let mut total = skip
+            .checked_sub(1)
+            .and_then(|prev_i| self.into_iter().get(I::from(prev_i)))
+            .unwrap_or_default();
+
+        let _checkpointing = exit.checkpointing();
+        source
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, value)| {
+                self.exit_checkpoint(i, exit)?;
+
+                total = total + T::from(value);
+                self.forced_push_at(i, total, exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Computes the product of the last `window` values (or fewer at the start).
+    ///
+    /// Keeps a running product, dividing out the value leaving the window. A zero can't be
+    /// divided out, so when one leaves the window the product is recomputed from scratch over
+    /// the window instead. Float products accumulate rounding errors like any running product.
+    pub fn compute_product<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: Mul<T, Output = T> + Div<T, Output = T> + From<T2> + Default + PartialEq,
+        T2: VecValue,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+        let mut prev = skip
+            .checked_sub(1)
+            .and_then(|prev_i| self.into_iter().get(I::from(prev_i)));
+
+        let mut window_values = VecDeque::with_capacity(window.min(1 << 16) + 1);
+
+        if skip > 0 {
+            let start = skip.saturating_sub(window);
+            source.iter().skip(start).take(skip - start).for_each(|v| {
+                window_values.push_back(T::from(v));
+            });
+        }
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, value)| {
+                let value = T::from(value);
+
+                let leaving = if window_values.len() == window {
+                    window_values.pop_front()
+                } else {
+                    None
+                };
+                window_values.push_back(value);
+
+                let product = match (prev, leaving) {
+                    (Some(prev), None) => prev * value,
+                    (Some(prev), Some(leaving)) if leaving != T::default() => {
+                        prev / leaving * value
+                    }
+                    _ => window_values
+                        .iter()
+                        .copied()
+                        .reduce(|acc, v| acc * v)
+                        .unwrap(),
+                };
+
+                prev.replace(product);
+                self.forced_push_at(i, product, exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Counts how many of the last `window` values (or fewer at the start) satisfy `pred`.
+    pub fn compute_count_if<T2, F>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        mut pred: F,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<usize>,
+        T2: VecValue,
+        F: FnMut(&T2) -> bool,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut window_matches = VecDeque::with_capacity(window.min(1 << 16) + 1);
+        let mut count = 0;
+
+        let start = skip.saturating_sub(window);
+        source.iter().skip(start).take(skip - start).for_each(|v| {
+            let matches = pred(&v);
+            count += matches as usize;
+            window_matches.push_back(matches);
+        });
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, value)| {
+                if window_matches.len() == window && window_matches.pop_front().unwrap() {
+                    count -= 1;
+                }
+                let matches = pred(&value);
+                count += matches as usize;
+                window_matches.push_back(matches);
+
+                self.forced_push_at(i, T::from(count), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    pub fn compute_sum_from_indexes<T2, T3>(
         &mut self,
         max_from: I,
         first_indexes: &impl IterableVec<I, T2>,
@@ -1037,6 +1645,49 @@ where
         self.safe_flush(exit)
     }
 
+    /// Computes `others[0] - others[1] - ... - others[n]` at each index.
+    ///
+    /// Panics if a subtraction underflows, like `compute_subtract`.
+    pub fn compute_subtract_of_others(
+        &mut self,
+        max_from: I,
+        others: &[&impl IterableVec<I, T>],
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: CheckedSub,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + others.iter().map(|v| v.version()).sum(),
+        )?;
+
+        if others.is_empty() {
+            unreachable!("others should've length of 1 at least");
+        }
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut others_iter = others[1..]
+            .iter()
+            .map(|v| v.iter().skip(skip))
+            .collect::<Vec<_>>();
+
+        others
+            .first()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, v)| {
+                let diff = others_iter.iter_mut().fold(v, |diff, iter| {
+                    diff.checked_sub(iter.next().unwrap()).unwrap()
+                });
+                self.forced_push_at(i, diff, exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_sma<T2>(
         &mut self,
         max_from: I,
@@ -1134,6 +1785,64 @@ where
         self.safe_flush(exit)
     }
 
+    /// Computes the linearly weighted moving average of the last `window` values.
+    ///
+    /// The newest value weighs `window`, the oldest 1. At the start of the series the partial
+    /// window of `len` values uses weights `1..=len`. Running sums make each step `O(1)`.
+    pub fn compute_wma<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue,
+        f32: From<T2>,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut window_values = VecDeque::with_capacity(window);
+        let mut sum = 0.0_f64;
+        let mut weighted_sum = 0.0_f64;
+
+        let mut add = |window_values: &mut VecDeque<f64>, value: f64| {
+            if window_values.len() == window {
+                // Every value loses one weight, which drops the oldest one entirely
+                weighted_sum -= sum;
+                sum -= window_values.pop_front().unwrap();
+            }
+            window_values.push_back(value);
+            let len = window_values.len() as f64;
+            sum += value;
+            weighted_sum += len * value;
+            (weighted_sum / (len * (len + 1.0) / 2.0)) as f32
+        };
+
+        let start = skip.saturating_sub(window);
+        source.iter().skip(start).take(skip - start).for_each(|v| {
+            add(&mut window_values, f32::from(v) as f64);
+        });
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, value)| {
+                let wma = add(&mut window_values, f32::from(value) as f64);
+                self.forced_push_at(i, T::from(wma), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_ema<T2>(
         &mut self,
         max_from: I,
@@ -1252,6 +1961,94 @@ where
         self.safe_flush(exit)
     }
 
+    /// Shifts `source` by `offset`: negative offsets lag (`result[i] = source[i - n]`), positive
+    /// ones lead (`result[i] = source[i + n]`). Indexes shifted out of the source get `fill`.
+    pub fn compute_shift<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        offset: isize,
+        fill: T,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<T2>,
+        T2: VecValue,
+    {
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        if offset < 0 {
+            let len = offset.unsigned_abs();
+
+            let mut lookback = source.create_lookback(skip, len, 0);
+
+            source
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .try_for_each(|(i, value)| {
+                    let lagged = lookback.get_and_push(i, value.clone(), value);
+                    let lagged = if i < len { fill } else { T::from(lagged) };
+
+                    self.forced_push_at(i, lagged, exit)
+                })?;
+        } else {
+            // The last `offset` values were filled because the source hadn't reached them yet
+            let skip = skip.saturating_sub(offset as usize);
+
+            let mut ahead = source.iter();
+            ahead.set_position_to(skip.saturating_add(offset as usize));
+
+            source
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .try_for_each(|(i, _)| {
+                    let led = ahead.next().map_or(fill, T::from);
+
+                    self.forced_push_at(i, led, exit)
+                })?;
+        }
+
+        self.safe_flush(exit)
+    }
+
+    /// `compute_shift` by `-len`.
+    pub fn compute_lag<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        len: usize,
+        fill: T,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<T2>,
+        T2: VecValue,
+    {
+        self.compute_shift(max_from, source, -(len as isize), fill, exit)
+    }
+
+    /// `compute_shift` by `len`.
+    pub fn compute_lead<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        len: usize,
+        fill: T,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<T2>,
+        T2: VecValue,
+    {
+        self.compute_shift(max_from, source, len as isize, fill, exit)
+    }
+
     pub fn compute_change(
         &mut self,
         max_from: I,
@@ -1361,6 +2158,180 @@ where
         self.safe_flush(exit)
     }
 
+    /// Computes the population standard deviation of the last `window` values.
+    ///
+    /// Keeps a running sum and sum of squares over the window. Like `compute_sma`, the first
+    /// values use the partial window available so far, so the first one is always 0.
+    /// A `NaN` input isn't added to the sums, every output is `NaN` while it's in the window.
+    pub fn compute_std_dev<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue,
+        f32: From<T2>,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut values = VecDeque::with_capacity(window);
+        let mut sum = 0.0_f64;
+        let mut sum_sq = 0.0_f64;
+        let mut nans = 0_usize;
+
+        let mut add = |values: &mut VecDeque<f64>, value: f64| {
+            if values.len() == window {
+                let old: f64 = values.pop_front().unwrap();
+                if old.is_nan() {
+                    nans -= 1;
+                } else {
+                    sum -= old;
+                    sum_sq -= old * old;
+                }
+            }
+            values.push_back(value);
+            if value.is_nan() {
+                nans += 1;
+            } else {
+                sum += value;
+                sum_sq += value * value;
+            }
+            if nans > 0 {
+                return f32::NAN;
+            }
+            let n = values.len() as f64;
+            let mean = sum / n;
+            ((sum_sq / n - mean * mean).max(0.0)).sqrt() as f32
+        };
+
+        let start = skip.saturating_sub(window);
+        source.iter().skip(start).take(skip - start).for_each(|v| {
+            add(&mut values, f32::from(v) as f64);
+        });
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, value)| {
+                let std_dev = add(&mut values, f32::from(value) as f64);
+                self.forced_push_at(i, T::from(std_dev), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Computes the median of the last `window` values (or fewer at the start).
+    ///
+    /// The window is kept as two ordered halves, so each step is `O(log window)`.
+    /// For an even number of values, the two middle ones are averaged.
+    pub fn compute_median<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue + Ord,
+        f32: From<T2>,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut values = VecDeque::with_capacity(window);
+        let mut median = SlidingMedian::new();
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip.saturating_sub(window))
+            .try_for_each(|(i, value)| {
+                if values.len() == window {
+                    let (old_i, old) = values.pop_front().unwrap();
+                    median.remove(old_i, old);
+                }
+                values.push_back((i, value.clone()));
+                median.insert(i, value);
+
+                if i < skip {
+                    return Ok(());
+                }
+
+                let median = match median.middle().unwrap() {
+                    (low, Some(high)) => (f32::from(low.clone()) + f32::from(high.clone())) / 2.0,
+                    (middle, None) => f32::from(middle.clone()),
+                };
+
+                self.forced_push_at(i, T::from(median), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Computes the percentile rank of each value among the last `window` values, itself included.
+    ///
+    /// The rank is the share of the window that's lower or equal, so ties count as below
+    /// and the first value of the series is always 100. Each step is `O(window)`.
+    pub fn compute_percentile_rank<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue + PartialOrd,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut window_values = VecDeque::with_capacity(window);
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip.saturating_sub(window - 1))
+            .try_for_each(|(i, value)| {
+                if window_values.len() == window {
+                    window_values.pop_front();
+                }
+                window_values.push_back(value.clone());
+
+                if i < skip {
+                    return Ok(());
+                }
+
+                let count = window_values.iter().filter(|v| **v <= value).count();
+                let rank = count as f32 / window_values.len() as f32 * 100.0;
+
+                self.forced_push_at(i, T::from(rank), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     pub fn compute_zscore<T2, T3, T4>(
         &mut self,
         max_from: I,
@@ -1392,6 +2363,191 @@ where
         )
     }
 
+    /// Computes `(value - mean) / std` over a rolling window in a single pass.
+    ///
+    /// Mean and population std are maintained inline with Welford's algorithm, so no
+    /// intermediate SMA or SD vecs are needed. Like `compute_sma`, the first values use
+    /// the partial window available so far. Pushes `NaN` when the std is zero.
+    pub fn compute_rolling_zscore<T2>(
+        &mut self,
+        max_from: I,
+        source: &impl IterableVec<I, T2>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue,
+        f32: From<T2>,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut values = VecDeque::with_capacity(window);
+        let mut mean = 0.0_f64;
+        let mut m2 = 0.0_f64;
+
+        let mut add = |values: &mut VecDeque<f64>, value: f64| {
+            if values.len() == window {
+                let old = values.pop_front().unwrap();
+                let n = values.len() as f64;
+                if n == 0.0 {
+                    mean = 0.0;
+                    m2 = 0.0;
+                } else {
+                    let delta = old - mean;
+                    mean -= delta / n;
+                    m2 -= delta * (old - mean);
+                }
+            }
+            values.push_back(value);
+            let delta = value - mean;
+            mean += delta / values.len() as f64;
+            m2 += delta * (value - mean);
+            (mean, (m2.max(0.0) / values.len() as f64).sqrt())
+        };
+
+        let start = skip.saturating_sub(window);
+        source.iter().skip(start).take(skip - start).for_each(|v| {
+            add(&mut values, f32::from(v) as f64);
+        });
+
+        source
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, value)| {
+                let value = f32::from(value) as f64;
+                let (mean, std) = add(&mut values, value);
+                let zscore = if std == 0.0 {
+                    f32::NAN
+                } else {
+                    ((value - mean) / std) as f32
+                };
+                self.forced_push_at(i, T::from(zscore), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Computes the Pearson correlation of `source_a` and `source_b` over the last `window`
+    /// values of each.
+    ///
+    /// Keeps running sums of x, y, x², y² and xy over the window. Like `compute_sma`, the
+    /// first values use the partial window available so far. Pushes `NaN` when either side
+    /// has zero variance, which includes the first value.
+    pub fn compute_correlation<T2, T3>(
+        &mut self,
+        max_from: I,
+        source_a: &impl IterableVec<I, T2>,
+        source_b: &impl IterableVec<I, T3>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue,
+        T3: VecValue,
+        f32: From<T2> + From<T3>,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO + self.inner_version() + source_a.version() + source_b.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut moments = RollingMoments::new(window);
+        let mut add = |a: T2, b: T3| {
+            moments.push(f32::from(a) as f64, f32::from(b) as f64);
+            moments.correlation().map_or(f32::NAN, |c| c as f32)
+        };
+
+        let start = skip.saturating_sub(window);
+        source_a
+            .iter()
+            .zip(source_b.iter())
+            .skip(start)
+            .take(skip - start)
+            .for_each(|(a, b)| {
+                add(a, b);
+            });
+
+        source_a
+            .iter()
+            .zip(source_b.iter())
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, (a, b))| self.forced_push_at(i, T::from(add(a, b)), exit))?;
+
+        self.safe_flush(exit)
+    }
+
+    /// Computes the beta of `asset_returns` against `market_returns` over the last `window`
+    /// values of each, `cov(asset, market) / var(market)`.
+    ///
+    /// Uses the same running sums as `compute_correlation`, and like it the first values use
+    /// the partial window available so far. Pushes `NaN` when the market has zero variance,
+    /// which includes the first value.
+    pub fn compute_beta<T2, T3>(
+        &mut self,
+        max_from: I,
+        asset_returns: &impl IterableVec<I, T2>,
+        market_returns: &impl IterableVec<I, T3>,
+        window: usize,
+        exit: &Exit,
+    ) -> Result<()>
+    where
+        T: From<f32>,
+        T2: VecValue,
+        T3: VecValue,
+        f32: From<T2> + From<T3>,
+    {
+        assert!(window > 0, "Window can't be empty");
+
+        self.validate_computed_version_or_reset(
+            Version::ZERO
+                + self.inner_version()
+                + asset_returns.version()
+                + market_returns.version(),
+        )?;
+
+        let skip = max_from.to_usize().min(self.len());
+
+        let mut moments = RollingMoments::new(window);
+        let mut add = |asset: T2, market: T3| {
+            moments.push(f32::from(asset) as f64, f32::from(market) as f64);
+            moments.beta().map_or(f32::NAN, |beta| beta as f32)
+        };
+
+        let start = skip.saturating_sub(window);
+        asset_returns
+            .iter()
+            .zip(market_returns.iter())
+            .skip(start)
+            .take(skip - start)
+            .for_each(|(asset, market)| {
+                add(asset, market);
+            });
+
+        asset_returns
+            .iter()
+            .zip(market_returns.iter())
+            .enumerate()
+            .skip(skip)
+            .try_for_each(|(i, (asset, market))| {
+                self.forced_push_at(i, T::from(add(asset, market)), exit)
+            })?;
+
+        self.safe_flush(exit)
+    }
+
     /// Removes this vector and all its associated regions from the database
     pub fn remove(self) -> Result<()> {
         self.0.remove()
@@ -1500,6 +2656,23 @@ where
         self.0.unchecked_read_at(index, reader)
     }
 
+    #[inline]
+    fn unchecked_read_range_at(&self, from: usize, to: usize, reader: &Reader) -> Result<Vec<T>> {
+        self.0.unchecked_read_range_at(from, to, reader)
+    }
+
+    #[inline]
+    fn binary_search_by<F>(
+        &self,
+        f: F,
+        reader: &Reader,
+    ) -> Result<std::result::Result<usize, usize>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        self.0.binary_search_by(f, reader)
+    }
+
     #[inline]
     fn pushed(&self) -> &[T] {
         self.0.pushed()
@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+/// Running sums of two series over a sliding window, for rolling covariance based stats.
+///
+/// Variances and covariance are population ones scaled by `n²`, which cancels out in the
+/// ratios built from them.
+pub(super) struct RollingMoments {
+    window: usize,
+    values: VecDeque<(f64, f64)>,
+    sx: f64,
+    sy: f64,
+    sxx: f64,
+    syy: f64,
+    sxy: f64,
+}
+
+impl RollingMoments {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+            sx: 0.0,
+            sy: 0.0,
+            sxx: 0.0,
+            syy: 0.0,
+            sxy: 0.0,
+        }
+    }
+
+    /// Adds a pair, evicting the oldest one once the window is full.
+    pub fn push(&mut self, x: f64, y: f64) {
+        if self.values.len() == self.window {
+            let (x, y) = self.values.pop_front().unwrap();
+            self.sx -= x;
+            self.sy -= y;
+            self.sxx -= x * x;
+            self.syy -= y * y;
+            self.sxy -= x * y;
+        }
+        self.values.push_back((x, y));
+        self.sx += x;
+        self.sy += y;
+        self.sxx += x * x;
+        self.syy += y * y;
+        self.sxy += x * y;
+    }
+
+    fn n(&self) -> f64 {
+        self.values.len() as f64
+    }
+
+    fn var_x(&self) -> f64 {
+        self.n() * self.sxx - self.sx * self.sx
+    }
+
+    fn var_y(&self) -> f64 {
+        self.n() * self.syy - self.sy * self.sy
+    }
+
+    fn cov(&self) -> f64 {
+        self.n() * self.sxy - self.sx * self.sy
+    }
+
+    /// Pearson correlation, `None` if either series has no variance.
+    pub fn correlation(&self) -> Option<f64> {
+        let (var_x, var_y) = (self.var_x(), self.var_y());
+        (var_x > 0.0 && var_y > 0.0).then(|| (self.cov() / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+    }
+
+    /// Slope of x regressed on y, `None` if y has no variance.
+    pub fn beta(&self) -> Option<f64> {
+        let var_y = self.var_y();
+        (var_y > 0.0).then(|| self.cov() / var_y)
+    }
+}
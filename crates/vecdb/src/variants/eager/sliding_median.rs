@@ -0,0 +1,59 @@
+use std::collections::BTreeSet;
+
+/// Median of a sliding window, kept as two ordered halves.
+///
+/// `low` holds the smaller half and is never smaller than `high`, by at most one value.
+/// Entries are keyed by their index so equal values can be removed individually.
+pub(super) struct SlidingMedian<T> {
+    low: BTreeSet<(T, usize)>,
+    high: BTreeSet<(T, usize)>,
+}
+
+impl<T> SlidingMedian<T>
+where
+    T: Ord + Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            low: BTreeSet::new(),
+            high: BTreeSet::new(),
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        if self.low.last().is_none_or(|(max, _)| value <= *max) {
+            self.low.insert((value, index));
+        } else {
+            self.high.insert((value, index));
+        }
+        self.rebalance();
+    }
+
+    pub fn remove(&mut self, index: usize, value: T) {
+        let entry = (value, index);
+        if !self.low.remove(&entry) {
+            self.high.remove(&entry);
+        }
+        self.rebalance();
+    }
+
+    fn rebalance(&mut self) {
+        if self.low.len() > self.high.len() + 1 {
+            let entry = self.low.pop_last().unwrap();
+            self.high.insert(entry);
+        } else if self.high.len() > self.low.len() {
+            let entry = self.high.pop_first().unwrap();
+            self.low.insert(entry);
+        }
+    }
+
+    /// The middle value, or both middle values for an even number of values.
+    pub fn middle(&self) -> Option<(&T, Option<&T>)> {
+        let (low, _) = self.low.last()?;
+        if self.low.len() == self.high.len() {
+            Some((low, self.high.first().map(|(high, _)| high)))
+        } else {
+            Some((low, None))
+        }
+    }
+}
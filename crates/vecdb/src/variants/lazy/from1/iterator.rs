@@ -21,6 +21,7 @@ where
 {
     #[inline]
     pub fn new(lazy: &'a LazyVecFrom1<I, T, S1I, S1T>) -> Self {
+        debug_assert_eq!(I::to_string(), S1I::to_string());
         let len = lazy.source.len();
         LazyVecFrom1Iterator {
             lazy,
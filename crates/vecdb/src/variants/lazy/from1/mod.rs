@@ -1,6 +1,6 @@
 use crate::{
-    AnyVec, BoxedVecIterator, IterableBoxedVec, IterableVec, TypedVec, TypedVecIterator, VecIndex,
-    VecValue, Version,
+    AnyVec, BoxedVecIterator, Error, IterableBoxedVec, IterableVec, Result, TypedVec,
+    TypedVecIterator, VecIndex, VecValue, Version,
 };
 
 mod iterator;
@@ -32,22 +32,36 @@ where
     S1I: VecIndex,
     S1T: VecValue,
 {
+    /// Panics if the source isn't indexed by `I`, see `try_init`.
     pub fn init(
         name: &str,
         version: Version,
         source: IterableBoxedVec<S1I, S1T>,
         compute: ComputeFrom1<I, T, S1I, S1T>,
     ) -> Self {
+        Self::try_init(name, version, source, compute).unwrap_or_else(|e| panic!("{name}: {e}"))
+    }
+
+    /// Errors with `IndexTypeMismatch` if the source isn't indexed by `I`.
+    pub fn try_init(
+        name: &str,
+        version: Version,
+        source: IterableBoxedVec<S1I, S1T>,
+        compute: ComputeFrom1<I, T, S1I, S1T>,
+    ) -> Result<Self> {
         if I::to_string() != S1I::to_string() {
-            unreachable!()
+            return Err(Error::IndexTypeMismatch {
+                expected: I::to_string(),
+                found: S1I::to_string(),
+            });
         }
 
-        Self {
+        Ok(Self {
             name: name.to_string(),
             version,
             source,
             compute,
-        }
+        })
     }
 
     fn version(&self) -> Version {
@@ -81,6 +95,12 @@ where
         self.version()
     }
 
+    fn version_breakdown(&self) -> Vec<(String, Version)> {
+        let mut v = vec![(self.name.clone(), self.version)];
+        v.extend(self.source.version_breakdown());
+        v
+    }
+
     fn name(&self) -> &str {
         self.name.as_str()
     }
@@ -1,6 +1,6 @@
 use crate::{
-    AnyVec, BoxedVecIterator, IterableBoxedVec, IterableVec, TypedVec, TypedVecIterator, VecIndex,
-    VecValue, Version,
+    AnyVec, BoxedVecIterator, Error, IterableBoxedVec, IterableVec, Result, TypedVec,
+    TypedVecIterator, VecIndex, VecValue, Version,
 };
 
 mod iterator;
@@ -39,6 +39,7 @@ where
     S2I: VecIndex,
     S2T: VecValue,
 {
+    /// Panics if no source is indexed by `I`, see `try_init`.
     pub fn init(
         name: &str,
         version: Version,
@@ -46,6 +47,18 @@ where
         source2: IterableBoxedVec<S2I, S2T>,
         compute: ComputeFrom2<I, T, S1I, S1T, S2I, S2T>,
     ) -> Self {
+        Self::try_init(name, version, source1, source2, compute)
+            .unwrap_or_else(|e| panic!("{name}: {e}"))
+    }
+
+    /// Errors with `IndexTypeMismatch` if no source is indexed by `I`.
+    pub fn try_init(
+        name: &str,
+        version: Version,
+        source1: IterableBoxedVec<S1I, S1T>,
+        source2: IterableBoxedVec<S2I, S2T>,
+        compute: ComputeFrom2<I, T, S1I, S1T, S2I, S2T>,
+    ) -> Result<Self> {
         if ([
             source1.index_type_to_string(),
             source2.index_type_to_string(),
@@ -55,16 +68,19 @@ where
         .count()
             == 0
         {
-            panic!("At least one should have same index");
+            return Err(Error::IndexTypeMismatch {
+                expected: I::to_string(),
+                found: source1.index_type_to_string(),
+            });
         }
 
-        Self {
+        Ok(Self {
             name: name.to_string(),
             version,
             source1,
             source2,
             compute,
-        }
+        })
     }
 
     fn version(&self) -> Version {
@@ -102,6 +118,13 @@ where
         self.version()
     }
 
+    fn version_breakdown(&self) -> Vec<(String, Version)> {
+        let mut v = vec![(self.name.clone(), self.version)];
+        v.extend(self.source1.version_breakdown());
+        v.extend(self.source2.version_breakdown());
+        v
+    }
+
     fn name(&self) -> &str {
         self.name.as_str()
     }
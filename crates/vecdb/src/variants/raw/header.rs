@@ -1,40 +1,68 @@
 use std::sync::Arc;
 
 use parking_lot::RwLock;
-use rawdb::Region;
+use rawdb::{Batch, Region};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
-use crate::{Error, Result, Stamp, Version};
+use crate::{Error, Result, Stamp, VecIndex, Version};
 
 use super::Format;
 
 const HEADER_VERSION: Version = Version::ONE;
+const INDEX_TYPE_LEN: usize = 24;
 pub(crate) const HEADER_OFFSET: u64 = size_of::<HeaderInner>() as u64;
 
 #[derive(Debug, Clone)]
 pub struct Header {
     inner: Arc<RwLock<HeaderInner>>,
     modified: bool,
+    foreign_endian: bool,
 }
 
 impl Header {
-    pub fn create_and_write(region: &Region, vec_version: Version, format: Format) -> Result<Self> {
-        let inner = HeaderInner::create_and_write(region, vec_version, format)?;
+    pub fn create_and_write<I: VecIndex, T>(
+        region: &Region,
+        vec_version: Version,
+        format: Format,
+    ) -> Result<Self> {
+        let inner = HeaderInner::create_and_write::<I, T>(region, vec_version, format)?;
+        Ok(Self {
+            inner: Arc::new(RwLock::new(inner)),
+            modified: false,
+            foreign_endian: false,
+        })
+    }
+
+    pub fn import_and_verify<I: VecIndex, T>(
+        region: &Region,
+        name: &str,
+        vec_version: Version,
+        format: Format,
+    ) -> Result<Self> {
+        let (inner, _) =
+            HeaderInner::import_and_verify::<I, T>(region, name, vec_version, format, false)?;
         Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
             modified: false,
+            foreign_endian: false,
         })
     }
 
-    pub fn import_and_verify(
+    /// Same as `import_and_verify` but accepts a header written with the opposite endianness.
+    ///
+    /// The in-memory header is converted to native endianness, the region is left untouched.
+    pub fn import_and_verify_any_endian<I: VecIndex, T>(
         region: &Region,
+        name: &str,
         vec_version: Version,
         format: Format,
     ) -> Result<Self> {
-        let inner = HeaderInner::import_and_verify(region, vec_version, format)?;
+        let (inner, foreign_endian) =
+            HeaderInner::import_and_verify::<I, T>(region, name, vec_version, format, true)?;
         Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
             modified: false,
+            foreign_endian,
         })
     }
 
@@ -48,6 +76,31 @@ impl Header {
         self.inner.write().compressed = ZeroCopyBool::from(format);
     }
 
+    /// Compression level used for new pages, if recorded.
+    pub fn pco_level(&self) -> Option<usize> {
+        self.inner
+            .read()
+            .pco_level
+            .checked_sub(1)
+            .map(|level| level as usize)
+    }
+
+    pub fn update_pco_level(&mut self, level: usize) {
+        self.modified = true;
+        self.inner.write().pco_level = level as u8 + 1;
+    }
+
+    /// Number of values per compressed page, if recorded.
+    pub fn page_values(&self) -> Option<usize> {
+        let values = u16::from_ne_bytes(self.inner.read().page_values);
+        (values != 0).then_some(values as usize)
+    }
+
+    pub fn update_page_values(&mut self, values: usize) {
+        self.modified = true;
+        self.inner.write().page_values = (values as u16).to_ne_bytes();
+    }
+
     pub fn update_computed_version(&mut self, computed_version: Version) {
         self.modified = true;
         self.inner.write().computed_version = computed_version;
@@ -57,6 +110,17 @@ impl Header {
         self.modified
     }
 
+    /// Whether the data on disk was written with the opposite endianness.
+    pub fn is_foreign_endian(&self) -> bool {
+        self.foreign_endian
+    }
+
+    /// Marks the data as native, the header will be rewritten on next write.
+    pub fn clear_foreign_endian(&mut self) {
+        self.modified = true;
+        self.foreign_endian = false;
+    }
+
     pub fn vec_version(&self) -> Version {
         self.inner.read().vec_version
     }
@@ -74,6 +138,11 @@ impl Header {
         self.modified = false;
         Ok(())
     }
+
+    pub fn write_in(&mut self, batch: &mut Batch, region: &Region) {
+        batch.write_all_to_region_at(region, self.inner.read().as_bytes(), 0);
+        self.modified = false;
+    }
 }
 
 #[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout)]
@@ -84,18 +153,32 @@ struct HeaderInner {
     pub computed_version: Version,
     pub stamp: Stamp,
     pub compressed: ZeroCopyBool,
-    pub padding: [u8; 31],
+    /// Size of the value type, zero if written before it was recorded
+    pub value_size: [u8; 4],
+    /// Name of the index type, zero-padded and truncated, all zeros if not recorded
+    pub index_type: [u8; INDEX_TYPE_LEN],
+    /// Pco compression level plus one, zero if written before it was recorded
+    pub pco_level: u8,
+    /// Number of values per compressed page, zero if written before it was recorded
+    pub page_values: [u8; 2],
 }
 
 impl HeaderInner {
-    pub fn create_and_write(region: &Region, vec_version: Version, format: Format) -> Result<Self> {
+    pub fn create_and_write<I: VecIndex, T>(
+        region: &Region,
+        vec_version: Version,
+        format: Format,
+    ) -> Result<Self> {
         let header = Self {
             header_version: HEADER_VERSION,
             vec_version,
             computed_version: Version::default(),
             stamp: Stamp::default(),
             compressed: ZeroCopyBool::from(format),
-            padding: Default::default(),
+            value_size: (size_of::<T>() as u32).to_ne_bytes(),
+            index_type: Self::index_type_bytes::<I>(),
+            pco_level: 0,
+            page_values: [0; 2],
         };
         header.write(region)?;
         Ok(header)
@@ -106,11 +189,13 @@ impl HeaderInner {
         Ok(())
     }
 
-    pub fn import_and_verify(
+    pub fn import_and_verify<I: VecIndex, T>(
         region: &Region,
+        name: &str,
         vec_version: Version,
         format: Format,
-    ) -> Result<Self> {
+        allow_foreign_endian: bool,
+    ) -> Result<(Self, bool)> {
         let len = region.meta().read().len();
 
         if len < HEADER_OFFSET {
@@ -119,30 +204,87 @@ impl HeaderInner {
 
         let reader = region.create_reader();
         let vec = reader.unchecked_read(0, HEADER_OFFSET);
-        let header = HeaderInner::read_from_bytes(vec)?;
+        let mut header = HeaderInner::read_from_bytes(vec)?;
+        drop(reader);
+
+        let foreign_endian = header.header_version != HEADER_VERSION
+            && header.header_version.swap_bytes() == HEADER_VERSION;
+        if foreign_endian {
+            if !allow_foreign_endian {
+                return Err(Error::WrongEndian);
+            }
+            header.swap_bytes();
+        }
 
         if header.header_version != HEADER_VERSION {
             return Err(Error::DifferentVersion {
+                name: name.to_string(),
                 found: header.header_version,
                 expected: HEADER_VERSION,
             });
         }
         if header.vec_version != vec_version {
             return Err(Error::DifferentVersion {
+                name: name.to_string(),
                 found: header.vec_version,
                 expected: vec_version,
             });
         }
         if header.compressed.is_broken() {
-            return Err(Error::WrongEndian);
+            return Err(Error::CorruptedHeader);
         }
         if (header.compressed.is_true() && format.is_raw())
             || (header.compressed.is_false() && format.is_compressed())
         {
             return Err(Error::DifferentCompressionMode);
         }
+        header.verify_types::<I, T>()?;
 
-        Ok(header)
+        Ok((header, foreign_endian))
+    }
+
+    fn index_type_bytes<I: VecIndex>() -> [u8; INDEX_TYPE_LEN] {
+        let mut bytes = [0; INDEX_TYPE_LEN];
+        let name = I::to_string().as_bytes();
+        let len = name.len().min(INDEX_TYPE_LEN);
+        bytes[..len].copy_from_slice(&name[..len]);
+        bytes
+    }
+
+    /// Rejects data written with other index or value types, headers without them are accepted.
+    fn verify_types<I: VecIndex, T>(&self) -> Result<()> {
+        let value_size = u32::from_ne_bytes(self.value_size) as usize;
+        if value_size != 0 && value_size != size_of::<T>() {
+            return Err(Error::TypeMismatch {
+                expected: format!("value of {} bytes", size_of::<T>()),
+                found: format!("value of {value_size} bytes"),
+            });
+        }
+
+        if self.index_type != [0; INDEX_TYPE_LEN]
+            && self.index_type != Self::index_type_bytes::<I>()
+        {
+            let found = self
+                .index_type
+                .split(|&b| b == 0)
+                .next()
+                .unwrap_or_default();
+            return Err(Error::TypeMismatch {
+                expected: format!("index {}", I::to_string()),
+                found: format!("index {}", String::from_utf8_lossy(found)),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn swap_bytes(&mut self) {
+        self.header_version = self.header_version.swap_bytes();
+        self.vec_version = self.vec_version.swap_bytes();
+        self.computed_version = self.computed_version.swap_bytes();
+        self.stamp = self.stamp.swap_bytes();
+        self.value_size.reverse();
+        self.page_values.reverse();
     }
 }
 
@@ -18,6 +18,9 @@ pub struct CleanRawVecIterator<'a, I, T> {
     buffer: Vec<u8>,
     pub(crate) buffer_pos: usize,
     buffer_len: usize,
+    /// Holds the bytes read by `next_back`, `back_start` is the file offset of its first byte
+    back_buffer: Vec<u8>,
+    back_start: u64,
     file_offset: u64,
     end_offset: u64,
     start_offset: u64,
@@ -51,6 +54,8 @@ where
             buffer: vec![0; Self::NORMAL_BUFFER_SIZE],
             buffer_pos: 0,
             buffer_len: 0,
+            back_buffer: vec![],
+            back_start: 0,
             file_offset: start_offset,
             end_offset,
             start_offset,
@@ -134,6 +139,24 @@ where
         self.buffer_pos = 0;
     }
 
+    /// Reads the chunk of the file that ends at `end_offset` into the back buffer, leaving the
+    /// cursor where the forward reads expect it.
+    fn refill_back_buffer(&mut self) {
+        let back_start = self.file_offset.max(
+            self.end_offset
+                .saturating_sub(Self::NORMAL_BUFFER_SIZE as u64),
+        );
+        let len = (self.end_offset - back_start) as usize;
+
+        self.back_buffer.resize(len, 0);
+        self.file
+            .seek(SeekFrom::Start(back_start))
+            .and_then(|_| self.file.read_exact(&mut self.back_buffer))
+            .and_then(|_| self.file.seek(SeekFrom::Start(self.file_offset)))
+            .expect("Failed to read from the end");
+        self.back_start = back_start;
+    }
+
     #[inline(always)]
     fn index_to_bytes(index: usize) -> u64 {
         index.saturating_mul(Self::SIZE_OF_T) as u64
@@ -224,6 +247,42 @@ where
     }
 }
 
+impl<I, T> DoubleEndedIterator for CleanRawVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.can_read_file() {
+            let offset = self.end_offset - Self::SIZE_OF_T as u64;
+            if offset < self.back_start
+                || self.end_offset > self.back_start + self.back_buffer.len() as u64
+            {
+                self.refill_back_buffer();
+            }
+            self.end_offset = offset;
+            let pos = (offset - self.back_start) as usize;
+            return Some(unsafe {
+                std::ptr::read_unaligned(self.back_buffer.as_ptr().add(pos) as *const T)
+            });
+        }
+
+        if self.cant_read_buffer() {
+            return None;
+        }
+
+        // The file is exhausted so the last value sits at the end of the buffer, shrink it along
+        // with the offsets so that forward reads stop right before it
+        self.buffer_len -= Self::SIZE_OF_T;
+        self.file_offset -= Self::SIZE_OF_T as u64;
+        self.end_offset = self.file_offset;
+        Some(unsafe {
+            std::ptr::read_unaligned(self.buffer.as_ptr().add(self.buffer_len) as *const T)
+        })
+    }
+}
+
 impl<I, T> VecIterator for CleanRawVecIterator<'_, I, T>
 where
     I: VecIndex,
@@ -554,4 +613,45 @@ mod tests {
             iter.next();
         }
     }
+
+    #[test]
+    fn test_clean_iter_double_ended() {
+        let (_temp, _db, mut vec) = setup();
+
+        // Spans a few buffers so both ends refill from the file
+        let len = 300_000;
+        for i in 0..len {
+            vec.push(i);
+        }
+        vec.flush().unwrap();
+
+        assert_eq!(
+            vec.clean_iter().unwrap().rev().take(3).collect::<Vec<_>>(),
+            vec![len - 1, len - 2, len - 3]
+        );
+
+        let mut iter = vec.clean_iter().unwrap();
+        let mut front = vec![];
+        let mut back = vec![];
+        let mut step = 0;
+        loop {
+            assert_eq!(iter.len(), (len as usize) - front.len() - back.len());
+            // Uneven strides so the cursors meet at varying buffer positions
+            let value = if step % 3 == 0 {
+                iter.next_back().inspect(|&v| back.push(v))
+            } else {
+                iter.next().inspect(|&v| front.push(v))
+            };
+            if value.is_none() {
+                break;
+            }
+            step += 1;
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, (0..len).collect::<Vec<_>>());
+    }
 }
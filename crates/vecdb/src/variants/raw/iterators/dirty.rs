@@ -69,8 +69,9 @@ where
         let new_pushed_len = new_total_len.saturating_sub(self.stored_len);
         self.pushed_len = new_pushed_len;
 
-        // Cap inner iterator if new end is within stored range
+        // Cap inner iterator and stored length if new end is within stored range
         if absolute_end <= self.stored_len {
+            self.stored_len = absolute_end;
             self.inner.set_end_to(absolute_end);
         }
     }
@@ -86,6 +87,9 @@ where
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         let index = self.index;
+        if unlikely(index >= self.vec_len()) {
+            return None;
+        }
         self.index += 1;
 
         if unlikely(self.holes) && self.inner._vec.holes().contains(&index) {
@@ -179,6 +183,46 @@ where
     }
 }
 
+impl<I, T> DoubleEndedIterator for DirtyRawVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    fn next_back(&mut self) -> Option<T> {
+        loop {
+            if self.index >= self.vec_len() {
+                return None;
+            }
+
+            // Pushed values go first, then the stored ones, shrinking the matching length
+            let index = self.vec_len() - 1;
+            let value = if index >= self.stored_len {
+                self.pushed_len -= 1;
+                self.inner
+                    ._vec
+                    .get_pushed_at(index, self.stored_len)
+                    .cloned()
+            } else {
+                self.stored_len -= 1;
+                let stored = self.inner.next_back();
+                if self.updated
+                    && let Some(updated) = self.inner._vec.updated().get(&index)
+                {
+                    Some(updated.clone())
+                } else {
+                    stored
+                }
+            };
+
+            if self.holes && self.inner._vec.holes().contains(&index) {
+                continue;
+            }
+
+            return value;
+        }
+    }
+}
+
 impl<I, T> VecIterator for DirtyRawVecIterator<'_, I, T>
 where
     I: VecIndex,
@@ -446,4 +490,54 @@ mod tests {
             assert_eq!(val, i as i32);
         }
     }
+
+    #[test]
+    fn test_dirty_iter_double_ended() {
+        let (_temp, _db, mut vec) = setup();
+
+        for i in 0..50 {
+            vec.push(i);
+        }
+        vec.flush().unwrap();
+
+        for i in 50..60 {
+            vec.push(i);
+        }
+        vec.update_at(10, 100).unwrap();
+        vec.update_at(45, 450).unwrap();
+        vec.delete_at(20);
+        vec.delete_at(48);
+
+        let expected = (0..60)
+            .filter(|&i| i != 20 && i != 48)
+            .map(|i| match i {
+                10 => 100,
+                45 => 450,
+                i => i,
+            })
+            .collect::<Vec<_>>();
+
+        let mut reversed = vec.dirty_iter().unwrap().rev().collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(reversed, expected);
+
+        let mut iter = vec.dirty_iter().unwrap();
+        let mut front = vec![];
+        let mut back = vec![];
+        for step in 0.. {
+            let value = if step % 2 == 0 {
+                iter.next().inspect(|&v| front.push(v))
+            } else {
+                iter.next_back().inspect(|&v| back.push(v))
+            };
+            if value.is_none() {
+                break;
+            }
+        }
+        assert_eq!(iter.next_back(), None);
+
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, expected);
+    }
 }
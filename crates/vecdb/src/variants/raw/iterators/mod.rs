@@ -84,6 +84,20 @@ where
     }
 }
 
+impl<I, T> DoubleEndedIterator for RawVecIterator<'_, I, T>
+where
+    I: VecIndex,
+    T: VecValue,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        match self {
+            Self::Clean(iter) => iter.next_back(),
+            Self::Dirty(iter) => iter.next_back(),
+        }
+    }
+}
+
 impl<I, T> VecIterator for RawVecIterator<'_, I, T>
 where
     I: VecIndex,
@@ -1,7 +1,9 @@
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, BTreeSet},
     marker::PhantomData,
     mem,
+    ops::Range,
     path::PathBuf,
     sync::{
         Arc,
@@ -10,12 +12,12 @@ use std::{
 };
 
 use log::info;
-use rawdb::{Database, Reader, Region};
+use rawdb::{Batch, Database, Reader, Region};
 use zerocopy::{FromBytes, IntoBytes};
 
 use crate::{
-    AnyStoredVec, AnyVec, BUFFER_SIZE, BoxedVecIterator, Error, GenericStoredVec, IterableVec,
-    Result, TypedVec, VecIndex, VecValue, Version,
+    AnyStoredVec, AnyVec, BUFFER_SIZE, BoxedVecIterator, Compressable, Error, Exit,
+    GenericStoredVec, IterableVec, Result, TypedVec, VecIndex, VecValue, Version,
 };
 
 use super::Format;
@@ -67,26 +69,36 @@ where
     }
 
     /// Same as import but will reset the vec under certain errors, so be careful !
+    ///
+    /// A vec written with the opposite endianness errors with `WrongEndian` instead, see
+    /// `forced_import_migrating_with`.
     pub fn forced_import_with(options: ImportOptions) -> Result<Self> {
         let res = Self::import_with(options);
         match res {
-            Err(Error::DifferentCompressionMode)
-            | Err(Error::WrongEndian)
-            | Err(Error::WrongLength)
-            | Err(Error::DifferentVersion { .. }) => {
-                info!("Resetting {}...", options.name);
-                let _ = options
-                    .db
-                    .remove_region_with_id(&Self::vec_region_name_with(options.name));
-                let _ = options
-                    .db
-                    .remove_region_with_id(&Self::holes_region_name_with(options.name));
-                Self::import_with(options)
+            Err(
+                ref e @ (Error::DifferentCompressionMode
+                | Error::CorruptedHeader
+                | Error::TypeMismatch { .. }
+                | Error::WrongLength
+                | Error::DifferentVersion { .. }),
+            ) => {
+                info!("Resetting {}: {e}", options.name);
+                Self::reset_with(options)
             }
             _ => res,
         }
     }
 
+    fn reset_with(options: ImportOptions) -> Result<Self> {
+        let _ = options
+            .db
+            .remove_region_with_id(&Self::vec_region_name_with(options.name));
+        let _ = options
+            .db
+            .remove_region_with_id(&Self::holes_region_name_with(options.name));
+        Self::import_with(options)
+    }
+
     pub fn import(db: &Database, name: &str, version: Version) -> Result<Self> {
         Self::import_with((db, name, version).into())
     }
@@ -96,41 +108,73 @@ where
         Self::import_(options, Format::Raw)
     }
 
+    /// Imports a vec whose data was written on a platform with the opposite endianness.
+    ///
+    /// Values are read as-is until `migrate_endianness` is called.
+    pub fn import_foreign_endian_with(mut options: ImportOptions) -> Result<Self> {
+        options.version = options.version + VERSION;
+        Self::import_any_endian_(options, Format::Raw, true)
+    }
+
     #[doc(hidden)]
-    pub fn import_(
+    pub fn import_(options: ImportOptions, format: Format) -> Result<Self> {
+        Self::import_any_endian_(options, format, false)
+    }
+
+    fn import_any_endian_(
         ImportOptions {
             db,
             name,
             version,
             saved_stamped_changes,
+            ..
         }: ImportOptions,
         format: Format,
+        allow_foreign_endian: bool,
     ) -> Result<Self> {
         let region = db.create_region_if_needed(&Self::vec_region_name_with(name))?;
 
         let region_len = region.meta().read().len() as usize;
-        if region_len > 0
-            && (region_len < HEADER_OFFSET as usize
-                || (format.is_raw()
-                    && !(region_len - HEADER_OFFSET as usize).is_multiple_of(Self::SIZE_OF_T)))
-        {
-            dbg!(region_len, region_len, HEADER_OFFSET);
-            return Err(Error::Str("Region was saved incorrectly"));
+        if region_len > 0 && region_len < HEADER_OFFSET as usize {
+            return Err(Error::RegionMisaligned {
+                len: region_len,
+                header: HEADER_OFFSET as usize,
+            });
         }
 
         let header = if region_len == 0 {
-            Header::create_and_write(&region, version, format)?
+            Header::create_and_write::<I, T>(&region, version, format)?
+        } else if allow_foreign_endian {
+            Header::import_and_verify_any_endian::<I, T>(&region, name, version, format)?
         } else {
-            Header::import_and_verify(&region, version, format)?
+            Header::import_and_verify::<I, T>(&region, name, version, format)?
         };
 
+        if region_len > 0
+            && format.is_raw()
+            && !(region_len - HEADER_OFFSET as usize).is_multiple_of(Self::SIZE_OF_T)
+        {
+            return Err(Error::RegionMisaligned {
+                len: region_len,
+                header: HEADER_OFFSET as usize,
+            });
+        }
+        let foreign_endian = header.is_foreign_endian();
+
         let holes = if let Some(holes) = db.get_region(&Self::holes_region_name_with(name)) {
             Some(
                 holes
                     .create_reader()
                     .read_all()
                     .chunks(size_of::<usize>())
-                    .map(|b| -> Result<usize> { usize::read_from_bytes(b).map_err(|e| e.into()) })
+                    .map(|b| -> Result<usize> {
+                        let hole = usize::read_from_bytes(b)?;
+                        Ok(if foreign_endian {
+                            hole.swap_bytes()
+                        } else {
+                            hole
+                        })
+                    })
                     .collect::<Result<BTreeSet<usize>>>()?,
             )
         } else {
@@ -188,6 +232,40 @@ where
         Ok(())
     }
 
+    pub(crate) fn write_header_if_needed_in(&mut self, batch: &mut Batch) {
+        if self.header.modified() {
+            self.header.write_in(batch, &self.region);
+        }
+    }
+
+    /// Persists the holes to their own region, removing it once there are none left.
+    pub(crate) fn write_holes(&mut self) -> Result<()> {
+        let mut batch = self.region.db().begin_batch();
+        self.write_holes_in(&mut batch)?;
+        batch.apply()?;
+        Ok(())
+    }
+
+    pub(crate) fn write_holes_in(&mut self, batch: &mut Batch) -> Result<()> {
+        if !self.holes.is_empty() {
+            self.has_stored_holes = true;
+            let holes = self
+                .region
+                .db()
+                .create_region_if_needed(&self.holes_region_name())?;
+            let bytes = self
+                .holes
+                .iter()
+                .flat_map(|i| i.to_ne_bytes())
+                .collect::<Vec<_>>();
+            batch.truncate_write_all_to_region(&holes, 0, &bytes);
+        } else if self.has_stored_holes {
+            self.has_stored_holes = false;
+            batch.remove_region_with_id(&self.holes_region_name());
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn prev_holes(&self) -> &BTreeSet<usize> {
         &self.prev_holes
@@ -198,12 +276,65 @@ where
         !self.is_pushed_empty() || !self.holes.is_empty() || !self.updated.is_empty()
     }
 
+    /// Reads the stored value at `index`, borrowing it straight from the mmap when aligned.
+    ///
+    /// Falls back to a copy if the value's address isn't aligned for `T`. Only stored values
+    /// are visible: pushed, updated and holes are ignored, like `unchecked_read_at`.
+    #[inline]
+    pub fn read_ref_at<'a>(&self, index: usize, reader: &'a Reader) -> Result<Cow<'a, T>> {
+        let offset = (index * Self::SIZE_OF_T) as u64 + HEADER_OFFSET;
+        if offset + Self::SIZE_OF_T as u64 > reader.region_meta().len() {
+            return Err(Error::IndexTooHigh);
+        }
+        let bytes = reader.unchecked_read(offset, Self::SIZE_OF_T as u64);
+        match T::ref_from_bytes(bytes) {
+            Ok(value) => Ok(Cow::Borrowed(value)),
+            Err(_) => Ok(Cow::Owned(T::read_from_bytes(bytes)?)),
+        }
+    }
+
+    /// Borrows the stored values in `range` straight from the mmap, without per-value copies.
+    ///
+    /// Like `read_ref_at`, only stored values are visible. Panics if `range` ends past the
+    /// stored length.
+    pub fn as_slice_range<'a>(&self, range: Range<usize>, reader: &'a Reader) -> &'a [T] {
+        assert!(
+            range.start <= range.end && range.end <= self.stored_len(),
+            "Range {range:?} is out of the {} stored values",
+            self.stored_len()
+        );
+        let offset = (range.start * Self::SIZE_OF_T) as u64 + HEADER_OFFSET;
+        reader.read_typed_slice(offset, range.len())
+    }
+
     /// Calculate optimal buffer size aligned to SIZE_OF_T
     #[inline]
     const fn aligned_buffer_size() -> usize {
         (BUFFER_SIZE / Self::SIZE_OF_T) * Self::SIZE_OF_T
     }
 
+    /// Rewrites the vec with only its live values, dropping the holes and shifting
+    /// every value after the first hole down, then flushes.
+    ///
+    /// Indexes change, so only use it on vecs used as dense value columns, never as index maps.
+    pub fn compact(&mut self, exit: &Exit) -> Result<()> {
+        let Some(&first_hole) = self.holes.first() else {
+            return self.safe_flush(exit);
+        };
+
+        let reader = self.create_reader();
+        let values = (first_hole..self.len())
+            .map(|i| self.get_or_read_at(i, &reader))
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>>>()?;
+        drop(reader);
+
+        self.truncate_if_needed_at(first_hole)?;
+        self.pushed.extend(values);
+
+        self.safe_flush(exit)
+    }
+
     /// Removes this vector and all its associated regions from the database
     pub fn remove(self) -> Result<()> {
         let db = self.region.db();
@@ -222,6 +353,69 @@ where
     }
 }
 
+impl<I, T> RawVec<I, T>
+where
+    I: VecIndex,
+    T: Compressable,
+{
+    /// Same as `forced_import_with` but a vec written with the opposite endianness is migrated
+    /// with `migrate_endianness` instead of erroring, and only reset if that fails.
+    pub fn forced_import_migrating_with(options: ImportOptions) -> Result<Self> {
+        match Self::forced_import_with(options) {
+            Err(Error::WrongEndian) => match Self::import_foreign_endian_with(options) {
+                Ok(mut vec) => {
+                    info!("Migrating endianness of {}...", options.name);
+                    vec.migrate_endianness_()?;
+                    Ok(vec)
+                }
+                Err(e) => {
+                    info!("Resetting {}: {e}", options.name);
+                    Self::reset_with(options)
+                }
+            },
+            res => res,
+        }
+    }
+
+    /// Byte-swaps every stored value in place and rewrites the header as native.
+    ///
+    /// Does nothing if the vec wasn't imported with `import_foreign_endian_with`
+    /// or was already migrated.
+    pub fn migrate_endianness(&mut self, exit: &Exit) -> Result<()> {
+        // Half swapped values can't be told apart from the others
+        let _lock = exit.lock();
+        self.migrate_endianness_()
+    }
+
+    fn migrate_endianness_(&mut self) -> Result<()> {
+        if !self.header.is_foreign_endian() {
+            return Ok(());
+        }
+
+        let stored_bytes = (self.real_stored_len() * Self::SIZE_OF_T) as u64;
+        let buffer_size = Self::aligned_buffer_size() as u64;
+
+        let mut offset = 0;
+        while offset < stored_bytes {
+            let len = buffer_size.min(stored_bytes - offset);
+            let mut bytes = self
+                .create_reader()
+                .read(HEADER_OFFSET + offset, len)
+                .to_vec();
+            bytes
+                .chunks_exact_mut(Self::SIZE_OF_T)
+                .for_each(|value| value.reverse());
+            self.region.write_all_at(&bytes, HEADER_OFFSET + offset)?;
+            offset += len;
+        }
+
+        info!("Migrated endianness of {}", self.name);
+
+        self.header.clear_foreign_endian();
+        self.flush()
+    }
+}
+
 impl<I, T> Clone for RawVec<I, T> {
     fn clone(&self) -> Self {
         Self {
@@ -329,11 +523,9 @@ where
         let expanded = stored_len > real_stored_len;
         let has_new_data = pushed_len != 0;
         let has_updated_data = !self.updated.is_empty();
-        let has_holes = !self.holes.is_empty();
-        let had_holes = self.has_stored_holes;
+        let has_holes = !self.holes.is_empty() || self.has_stored_holes;
 
-        if !truncated && !expanded && !has_new_data && !has_updated_data && !has_holes && !had_holes
-        {
+        if !truncated && !expanded && !has_new_data && !has_updated_data && !has_holes {
             return Ok(());
         }
 
@@ -349,33 +541,26 @@ where
 
         if has_updated_data {
             let updated = mem::take(&mut self.updated);
+            // Coalesce runs of consecutive indexes into a single write each
+            let mut run_start = 0;
+            let mut run = vec![];
             updated.into_iter().try_for_each(|(i, v)| -> Result<()> {
-                let bytes = v.as_bytes();
-                let at = (i * Self::SIZE_OF_T) as u64 + HEADER_OFFSET;
-                self.region.write_all_at(bytes, at)?;
+                if !run.is_empty() && i != run_start + run.len() / Self::SIZE_OF_T {
+                    let at = (run_start * Self::SIZE_OF_T) as u64 + HEADER_OFFSET;
+                    self.region.write_all_at(&run, at)?;
+                    run.clear();
+                }
+                if run.is_empty() {
+                    run_start = i;
+                }
+                run.extend_from_slice(v.as_bytes());
                 Ok(())
             })?;
+            let at = (run_start * Self::SIZE_OF_T) as u64 + HEADER_OFFSET;
+            self.region.write_all_at(&run, at)?;
         }
 
-        if has_holes {
-            self.has_stored_holes = true;
-            let holes = self
-                .region
-                .db()
-                .create_region_if_needed(&self.holes_region_name())?;
-            let bytes = self
-                .holes
-                .iter()
-                .flat_map(|i| i.to_ne_bytes())
-                .collect::<Vec<_>>();
-            holes.truncate_write_all(0, &bytes)?;
-        } else if had_holes {
-            self.has_stored_holes = false;
-            let _ = self
-                .region
-                .db()
-                .remove_region_with_id(&self.holes_region_name());
-        }
+        self.write_holes()?;
 
         Ok(())
     }
@@ -470,6 +655,16 @@ where
             .map_err(Error::from)
     }
 
+    fn unchecked_read_range_at(&self, from: usize, to: usize, reader: &Reader) -> Result<Vec<T>> {
+        let mut values = vec![T::new_zeroed(); to - from];
+        let bytes = reader.unchecked_read(
+            (from * Self::SIZE_OF_T) as u64 + HEADER_OFFSET,
+            ((to - from) * Self::SIZE_OF_T) as u64,
+        );
+        values.as_mut_bytes().copy_from_slice(bytes);
+        Ok(values)
+    }
+
     #[inline]
     fn pushed(&self) -> &[T] {
         self.pushed.as_slice()
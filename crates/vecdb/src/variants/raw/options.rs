@@ -13,6 +13,14 @@ pub struct ImportOptions<'a> {
     pub version: Version,
     /// Number of stamped change files to keep for rollback support (0 to disable).
     pub saved_stamped_changes: u16,
+    /// Pco compression level (0 to 12) for compressed vecs, persisted in the header.
+    /// When unset, the persisted level is kept, or 4 for new vecs.
+    pub pco_level: Option<usize>,
+    /// Uncompressed size in bytes of the pages of compressed vecs, rounded down to a multiple
+    /// of the value size and persisted in the header. Larger pages compress better but make
+    /// random reads decode more. Only applies to vecs without any stored page, others keep
+    /// the size they were written with. When unset, 16 KiB.
+    pub page_size: Option<usize>,
 }
 
 impl<'a> ImportOptions<'a> {
@@ -22,6 +30,8 @@ impl<'a> ImportOptions<'a> {
             name,
             version,
             saved_stamped_changes: 0,
+            pco_level: None,
+            page_size: None,
         }
     }
 
@@ -29,6 +39,16 @@ impl<'a> ImportOptions<'a> {
         self.saved_stamped_changes = num;
         self
     }
+
+    pub fn with_pco_level(mut self, level: usize) -> Self {
+        self.pco_level = Some(level);
+        self
+    }
+
+    pub fn with_page_size(mut self, bytes: usize) -> Self {
+        self.page_size = Some(bytes);
+        self
+    }
 }
 
 impl<'a> From<(&'a Database, &'a str, Version)> for ImportOptions<'a> {
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, BTreeSet},
     path::PathBuf,
 };
@@ -6,8 +7,8 @@ use std::{
 use rawdb::{Database, Reader, Region};
 
 use crate::{
-    AnyStoredVec, AnyVec, BoxedVecIterator, Compressable, GenericStoredVec, Header, IterableVec,
-    Result, TypedVec, VecIndex, Version, variants::ImportOptions,
+    AnyStoredVec, AnyVec, BoxedVecIterator, Compressable, EagerVec, Exit, GenericStoredVec, Header,
+    IterableVec, Result, TypedVec, VecIndex, Version, variants::ImportOptions,
 };
 
 use super::{CompressedVec, RawVec};
@@ -53,10 +54,42 @@ where
                 options,
             )?))
         } else {
-            Ok(Self::Raw(RawVec::forced_import_with(options)?))
+            Ok(Self::Raw(RawVec::forced_import_migrating_with(options)?))
         }
     }
 
+    /// Rewrites the vec without its holes, see `RawVec::compact`.
+    ///
+    /// Indexes change, so only use it on vecs used as dense value columns, never as index maps.
+    pub fn compact(&mut self, exit: &Exit) -> Result<()> {
+        match self {
+            StoredVec::Raw(v) => v.compact(exit),
+            StoredVec::Compressed(v) => v.compact(exit),
+        }
+    }
+
+    /// Imports the vec `name` and fills it with `f` applied to every value, see
+    /// `EagerVec::compute_transform`.
+    ///
+    /// Picks up where a previous call left off, values past this vec's length are dropped.
+    pub fn map_into<U>(
+        &self,
+        db: &Database,
+        name: &str,
+        version: Version,
+        format: Format,
+        f: impl Fn(T) -> U,
+        exit: &Exit,
+    ) -> Result<EagerVec<I, U>>
+    where
+        U: Compressable,
+    {
+        let mut vec = EagerVec::forced_import(db, name, version, format)?;
+        vec.truncate_if_needed_at(self.len())?;
+        vec.compute_transform(I::from(vec.len()), self, |(i, v, _)| (i, f(v)), exit)?;
+        Ok(vec)
+    }
+
     /// Removes this vector and all its associated regions from the database
     pub fn remove(self) -> Result<()> {
         match self {
@@ -207,6 +240,29 @@ where
         }
     }
 
+    #[inline]
+    fn binary_search_by<F>(
+        &self,
+        f: F,
+        reader: &Reader,
+    ) -> Result<std::result::Result<usize, usize>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        match self {
+            StoredVec::Raw(v) => v.binary_search_by(f, reader),
+            StoredVec::Compressed(v) => v.binary_search_by(f, reader),
+        }
+    }
+
+    #[inline]
+    fn unchecked_read_range_at(&self, from: usize, to: usize, reader: &Reader) -> Result<Vec<T>> {
+        match self {
+            StoredVec::Raw(v) => v.unchecked_read_range_at(from, to, reader),
+            StoredVec::Compressed(v) => v.unchecked_read_range_at(from, to, reader),
+        }
+    }
+
     #[inline]
     fn pushed(&self) -> &[T] {
         match self {
@@ -1,9 +1,10 @@
 use std::{
-    fs,
+    fmt, fs,
     io::{self, Read},
     iter::Sum,
     ops::Add,
     path::Path,
+    str::FromStr,
 };
 
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
@@ -60,6 +61,7 @@ impl Version {
                     return Err(Error::WrongEndian);
                 }
                 return Err(Error::DifferentVersion {
+                    name: path.display().to_string(),
                     found: prev_version,
                     expected: *self,
                 });
@@ -72,6 +74,34 @@ impl Version {
     }
 }
 
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+/// Parses the `Display` form (`v3`), the leading `v` being optional.
+impl FromStr for Version {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('v').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::String(format!("Invalid version: {s:?}")));
+        }
+        digits
+            .parse::<u64>()
+            .map(Self)
+            .map_err(|_| Error::String(format!("Version out of range: {s:?}")))
+    }
+}
+
+impl TryFrom<&str> for Version {
+    type Error = Error;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 impl From<Version> for u64 {
     fn from(value: Version) -> u64 {
         value.0
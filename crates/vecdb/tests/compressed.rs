@@ -2,8 +2,8 @@ use rawdb::Database;
 use std::collections::BTreeSet;
 use tempfile::TempDir;
 use vecdb::{
-    AnyStoredVec, AnyVec, CollectableVec, CompressedVec, GenericStoredVec, Result, Stamp,
-    TypedVecIterator, Version,
+    AnyStoredVec, AnyVec, CollectableVec, CompressedVec, GenericStoredVec, ImportOptions, Result,
+    Stamp, TypedVecIterator, Version,
 };
 
 #[allow(clippy::upper_case_acronyms)]
@@ -193,3 +193,380 @@ fn test_compressed_vec_operations() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_page_cache_reads() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.page_cache_capacity(), 0);
+
+    (0..20_000_u32).for_each(|v| vec.push(v));
+    vec.flush()?;
+
+    vec.set_page_cache_capacity(2);
+
+    let reader = vec.create_reader();
+    for i in [5, 6, 4100, 7, 4101, 12_000, 3, 19_999, 12_001] {
+        assert_eq!(vec.read_at(i, &reader)?, i as u32);
+    }
+    drop(reader);
+
+    // Flushing new data invalidates the cached pages
+    vec.truncate_if_needed_at(4)?;
+    (0..20_000_u32).for_each(|v| vec.push(v * 2));
+    vec.flush()?;
+
+    let reader = vec.create_reader();
+    assert_eq!(vec.read_at(3, &reader)?, 3);
+    assert_eq!(vec.read_at(5, &reader)?, 2);
+    assert_eq!(vec.read_at(4101, &reader)?, 2 * 4097);
+    drop(reader);
+
+    Ok(())
+}
+
+#[test]
+fn test_pco_level() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    // Skewed values, where more bins pay off
+    let values = (0..3_000_u32)
+        .map(|i| {
+            let x = (i.wrapping_mul(2654435761) >> 16) % 1000;
+            x * x / 1000
+        })
+        .collect::<Vec<_>>();
+
+    let mut sizes = vec![];
+    for (name, level) in [("fast", 0), ("small", 12)] {
+        let options =
+            vecdb::ImportOptions::new(&database, name, Version::ONE).with_pco_level(level);
+        let mut vec: VEC = CompressedVec::forced_import_with(options)?;
+        assert_eq!(vec.pco_level(), level);
+        values.iter().for_each(|v| vec.push(*v));
+        vec.flush()?;
+        assert_eq!(vec.collect(), values);
+        sizes.push(vec.region().meta().read().len());
+    }
+    assert_ne!(sizes[0], sizes[1]);
+
+    // Persisted level is kept when reopening without one
+    let vec: VEC = CompressedVec::forced_import(&database, "small", Version::ONE)?;
+    assert_eq!(vec.pco_level(), 12);
+    assert_eq!(vec.collect(), values);
+
+    let options = vecdb::ImportOptions::new(&database, "bad", Version::ONE).with_pco_level(13);
+    assert!(VEC::forced_import_with(options).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_updates_and_holes() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut expected = (0..10_000_u32).map(Some).collect::<Vec<_>>();
+
+    {
+        let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+        (0..10_000_u32).for_each(|v| vec.push(v));
+        vec.flush()?;
+
+        // Overwrite values in the first, middle and last pages
+        for (i, v) in [(3, 30), (5_000, 50), (9_999, 90)] {
+            vec.update_at(i, v)?;
+            expected[i] = Some(v);
+        }
+        for i in [7, 4_100] {
+            vec.delete_at(i);
+            expected[i] = None;
+        }
+        vec.push(10_000);
+        expected.push(Some(10_000));
+
+        assert_eq!(vec.collect_holed()?, expected);
+        vec.flush()?;
+        assert_eq!(vec.collect_holed()?, expected);
+    }
+
+    {
+        let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+        assert_eq!(vec.holes(), &BTreeSet::from([7, 4_100]));
+        assert_eq!(vec.collect_holed()?, expected);
+        assert_eq!(
+            vec.collect(),
+            expected.iter().flatten().copied().collect::<Vec<_>>()
+        );
+
+        let reader = vec.create_static_reader();
+        assert_eq!(vec.get_or_read_at(5_000, &reader)?, Some(50));
+        assert_eq!(vec.get_or_read_at(4_100, &reader)?, None);
+        drop(reader);
+
+        // Filling every hole removes the holes region
+        assert_eq!(vec.fill_first_hole_or_push(1)?, 7);
+        vec.update_at(4_100, 2)?;
+        expected[7] = Some(1);
+        expected[4_100] = Some(2);
+        vec.flush()?;
+    }
+
+    let vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert!(vec.holes().is_empty());
+    assert!(database.get_region(&vec.holes_region_name()).is_none());
+    assert_eq!(vec.collect_holed()?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_update_rewrites_only_dirty_pages() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut expected = (0..4096 * 3).collect::<Vec<u32>>();
+    let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    vec.extend_from_slice(&expected);
+    vec.flush()?;
+    let before = vec.page_stats();
+
+    // Breaks the first page's sequence so it compresses worse and moves after the last page
+    vec.update_at(1, 7)?;
+    expected[1] = 7;
+    vec.flush()?;
+    let after = vec.page_stats();
+    assert!(after[0].0 > before[2].0);
+    assert_eq!(after[1..], before[1..]);
+    assert_eq!(vec.collect(), expected);
+
+    // Restoring it shrinks it back, rewritten where it now lives
+    vec.update_at(1, 1)?;
+    expected[1] = 1;
+    vec.push(4096 * 3);
+    expected.push(4096 * 3);
+    vec.flush()?;
+    let restored = vec.page_stats();
+    assert_eq!(restored[0].0, after[0].0);
+    assert_eq!(restored[1..3], before[1..]);
+    assert!(restored[3].0 >= after[0].0 + after[0].1 as u64);
+    drop(vec);
+
+    let vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.collect(), expected);
+    let reader = vec.create_static_reader();
+    assert_eq!(vec.get_or_read_at(1, &reader)?, Some(1));
+
+    Ok(())
+}
+
+#[test]
+fn test_compact() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = vecdb::Exit::new();
+
+    let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    vec.extend_from_slice(&(0..10_000).collect::<Vec<_>>());
+    vec.flush()?;
+    vec.delete_at(3);
+    vec.delete_at(5_000);
+    vec.push(10_000);
+
+    vec.compact(&exit)?;
+    assert!(vec.holes().is_empty());
+    let expected = (0..=10_000)
+        .filter(|v| ![3, 5_000].contains(v))
+        .collect::<Vec<_>>();
+    assert_eq!(vec.collect(), expected);
+    drop(vec);
+
+    let vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.collect(), expected);
+    assert!(database.get_region(&vec.holes_region_name()).is_none());
+
+    // Pages rewritten elsewhere leave dead bytes behind, reclaimed even without holes
+    let mut expected = (0..4096 * 3).collect::<Vec<u32>>();
+    let mut vec: VEC = CompressedVec::forced_import(&database, "dead", Version::ONE)?;
+    vec.extend_from_slice(&expected);
+    vec.flush()?;
+    assert_eq!(vec.dead_bytes(), 0);
+    vec.update_at(1, 7)?;
+    expected[1] = 7;
+    vec.flush()?;
+    let dead = vec.dead_bytes();
+    assert!(dead > 0);
+    let len = vec.region().meta().read().len();
+
+    vec.compact(&exit)?;
+    assert_eq!(vec.dead_bytes(), 0);
+    let stats = vec.page_stats();
+    assert!(stats.windows(2).all(|w| w[0].0 + w[0].1 as u64 == w[1].0));
+    assert_eq!(vec.region().meta().read().len(), len - dead);
+    assert_eq!(vec.collect(), expected);
+    drop(vec);
+
+    let vec: VEC = CompressedVec::forced_import(&database, "dead", Version::ONE)?;
+    assert_eq!(vec.collect(), expected);
+    assert_eq!(vec.dead_bytes(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_binary_search() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+
+    // Spans several pages, with duplicates
+    let values = (0..50_000_u32).map(|i| i / 3 * 3).collect::<Vec<_>>();
+    values.iter().for_each(|v| vec.push(*v));
+    vec.flush()?;
+
+    let reader = vec.create_reader();
+    for target in [0, 1, 3, 4, 29_997, 30_000, 49_998, 49_999, 100_000] {
+        let found = vec.binary_search(&target, &reader)?;
+        match (found, values.binary_search(&target)) {
+            (Ok(i), Ok(_)) => assert_eq!(values[i], target),
+            (found, expected) => assert_eq!(found, expected),
+        }
+    }
+    assert_eq!(vec.binary_search(&4, &reader)?, Err(6));
+    assert_eq!(vec.binary_search(&100_000, &reader)?, Err(values.len()));
+
+    Ok(())
+}
+
+#[test]
+fn test_stored_len_from_pages() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.len(), 0);
+    vec.extend_from_slice(&(0..1_000_000).collect::<Vec<_>>());
+    vec.flush()?;
+
+    vec.truncate_if_needed_at(600_000)?;
+    vec.push(7);
+    vec.flush()?;
+    drop(vec);
+
+    // Reopening only reads the last page's entry to get the length
+    let vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.len(), 600_001);
+    assert_eq!(vec.page_count(), 600_001_usize.div_ceil(4096));
+    assert_eq!(vec.last(&vec.create_reader())?, Some(7));
+
+    Ok(())
+}
+
+#[test]
+fn test_page_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.page_count(), 0);
+
+    // 16 KiB pages hold 4096 u32s
+    let len = 4096 * 2 + 100;
+    vec.extend_from_slice(&(0..len).collect::<Vec<_>>());
+    assert_eq!(vec.page_count(), 0);
+    vec.flush()?;
+    assert_eq!(vec.page_count(), 3);
+
+    let stats = vec.page_stats();
+    assert_eq!(stats.len(), 3);
+    assert_eq!(
+        stats.iter().map(|(.., values)| *values).collect::<Vec<_>>(),
+        vec![4096, 4096, 100]
+    );
+    assert_eq!(
+        stats
+            .iter()
+            .map(|(.., values)| *values as usize)
+            .sum::<usize>(),
+        vec.len()
+    );
+    // Pages are laid out back to back
+    stats.windows(2).for_each(|w| {
+        assert_eq!(w[0].0 + w[0].1 as u64, w[1].0);
+    });
+
+    Ok(())
+}
+
+#[test]
+fn test_page_size() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let values = (0..20_000).collect::<Vec<u32>>();
+
+    for (name, page_size, per_page) in [("small", 1023, 255), ("large", 64 * 1024, 16384)] {
+        let options = ImportOptions::new(&database, name, Version::ONE).with_page_size(page_size);
+        let mut vec: VEC = CompressedVec::forced_import_with(options)?;
+        assert_eq!(vec.page_size(), per_page * 4);
+        vec.extend_from_slice(&values);
+        vec.flush()?;
+        assert_eq!(vec.page_count(), values.len().div_ceil(per_page));
+        drop(vec);
+
+        // The geometry comes from the header once pages are stored
+        let vec: VEC = CompressedVec::forced_import(&database, name, Version::ONE)?;
+        assert_eq!(vec.page_size(), per_page * 4);
+        assert_eq!(vec.collect(), values);
+        assert_eq!(vec.read_at_once(per_page + 1)?, per_page as u32 + 1);
+    }
+
+    for page_size in [3, 64 * 1024 + 1] {
+        let options =
+            ImportOptions::new(&database, "invalid", Version::ONE).with_page_size(page_size);
+        assert!(VEC::forced_import_with(options).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_flush_in_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    let values = (0..5_000).collect::<Vec<u32>>();
+
+    let modify = |vec: &mut VEC| -> Result<()> {
+        vec.update(10, 1_000_000)?;
+        vec.update(4_500, 2_000_000)?;
+        vec.extend_from_slice(&[7; 1_000]);
+        Ok(())
+    };
+    let mut modified = values.clone();
+    modified[10] = 1_000_000;
+    modified[4_500] = 2_000_000;
+    modified.extend([7; 1_000]);
+
+    {
+        let database = Database::open(temp.path())?;
+        let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+        vec.extend_from_slice(&values);
+        vec.flush()?;
+        database.flush()?;
+
+        // Crash before the batch is committed, none of the regions may change
+        modify(&mut vec)?;
+        let mut batch = database.begin_batch();
+        vec.flush_in(&mut batch)?;
+        assert!(!batch.is_empty());
+        drop(batch);
+    }
+
+    {
+        let database = Database::open(temp.path())?;
+        let mut vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+        assert_eq!(vec.collect(), values);
+
+        modify(&mut vec)?;
+        database.batch(|tx| vec.flush_in(tx))?;
+    }
+
+    let database = Database::open(temp.path())?;
+    let vec: VEC = CompressedVec::forced_import(&database, "vec", Version::ONE)?;
+    assert_eq!(vec.collect(), modified);
+
+    Ok(())
+}
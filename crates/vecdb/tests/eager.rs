@@ -0,0 +1,1126 @@
+use rawdb::Database;
+use tempfile::TempDir;
+use vecdb::{
+    AnyStoredVec, AnyVec, CollectableVec, EagerVec, Exit, Format, GenericStoredVec,
+    IterableCloneableVec, LazyVecFrom1, RawVec, Result, StoredVec, Version,
+};
+
+/// Helper to create a temporary test database
+pub fn setup_test_db() -> Result<(Database, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    let db = Database::open(temp_dir.path())?;
+    Ok((db, temp_dir))
+}
+
+#[test]
+fn test_compute_divide_or() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut divided: RawVec<usize, _> = RawVec::forced_import(&database, "divided", Version::ONE)?;
+    [10.0_f32, 9.0, 8.0, 7.0]
+        .iter()
+        .for_each(|v| divided.push(*v));
+    divided.flush()?;
+    let mut divider: RawVec<usize, _> = RawVec::forced_import(&database, "divider", Version::ONE)?;
+    [2.0_f32, 0.0, 4.0, 0.0]
+        .iter()
+        .for_each(|v| divider.push(*v));
+    divider.flush()?;
+
+    let mut vec: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "ratio", Version::ONE)?;
+    vec.compute_divide_or(0, &divided, &divider, -1.0, &exit)?;
+
+    assert_eq!(vec.collect(), vec![5.0, -1.0, 2.0, -1.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_resample() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut values: RawVec<usize, _> = RawVec::forced_import(&database, "values", Version::ONE)?;
+    [1_u64, 2, 3, 4, 5, 6].iter().for_each(|v| values.push(*v));
+    values.flush()?;
+    // Coarse bucket 2 is empty
+    let mut fine_to_coarse: RawVec<usize, _> =
+        RawVec::forced_import(&database, "fine_to_coarse", Version::ONE)?;
+    [0_usize, 0, 1, 1, 3, 3]
+        .iter()
+        .for_each(|v| fine_to_coarse.push(*v));
+    fine_to_coarse.flush()?;
+
+    let mut sum: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "sum", Version::ONE)?;
+    sum.compute_resample(
+        0,
+        &values,
+        &fine_to_coarse,
+        |bucket| bucket.iter().sum(),
+        &exit,
+    )?;
+    assert_eq!(sum.collect(), vec![3, 7, 0, 11]);
+
+    let mut last: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "last", Version::ONE)?;
+    last.compute_resample(
+        0,
+        &values,
+        &fine_to_coarse,
+        |bucket| bucket.last().copied().unwrap_or_default(),
+        &exit,
+    )?;
+    assert_eq!(last.collect(), vec![2, 4, 0, 6]);
+
+    // Resuming from a coarse index recomputes from there
+    last.compute_resample(
+        1,
+        &values,
+        &fine_to_coarse,
+        |bucket| bucket.first().copied().unwrap_or_default(),
+        &exit,
+    )?;
+    assert_eq!(last.collect(), vec![2, 3, 0, 5]);
+
+    // The partial last bucket is recomputed once more fine values land in it
+    values.push(7);
+    values.push(8);
+    fine_to_coarse.push(3);
+    fine_to_coarse.push(4);
+    values.flush()?;
+    fine_to_coarse.flush()?;
+    sum.compute_resample(
+        sum.len(),
+        &values,
+        &fine_to_coarse,
+        |bucket| bucket.iter().sum(),
+        &exit,
+    )?;
+    assert_eq!(sum.collect(), vec![3, 7, 0, 18, 8]);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_forward_and_back_fill() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut target: RawVec<usize, _> = RawVec::forced_import(&database, "target", Version::ONE)?;
+    [0_u32; 8].iter().for_each(|v| target.push(*v));
+    target.flush()?;
+    let mut values: RawVec<usize, _> = RawVec::forced_import(&database, "values", Version::ONE)?;
+    [0_u64, 10, 0, 0, 40, 0]
+        .iter()
+        .for_each(|v| values.push(*v));
+    values.flush()?;
+    [0, 2, 3, 5].into_iter().for_each(|i| values.delete_at(i));
+
+    let mut forward: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "forward", Version::ONE)?;
+    forward.compute_forward_fill(0, &target, &values, 99, &exit)?;
+    assert_eq!(forward.collect(), vec![99, 10, 10, 10, 40, 40, 40, 40]);
+
+    let mut back: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "back", Version::ONE)?;
+    back.compute_back_fill(0, &target, &values, 99, &exit)?;
+    assert_eq!(back.collect(), vec![10, 10, 40, 40, 40, 99, 99, 99]);
+
+    // A new real value at the end fixes the trailing gap
+    values.push(70);
+    back.compute_back_fill(6, &target, &values, 99, &exit)?;
+    assert_eq!(back.collect(), vec![10, 10, 40, 40, 40, 70, 70, 99]);
+
+    Ok(())
+}
+
+#[test]
+fn test_version_breakdown() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [1_u32, 2, 3].iter().for_each(|v| src.push(*v));
+    src.flush()?;
+    assert_eq!(
+        src.version_breakdown(),
+        vec![("src".to_string(), src.version())]
+    );
+
+    let lazy: LazyVecFrom1<usize, u32, usize, u32> =
+        LazyVecFrom1::init("double", Version::TWO, src.boxed_clone(), |i, iter| {
+            iter.get(i).map(|v| v * 2)
+        });
+
+    assert_eq!(
+        lazy.version_breakdown(),
+        vec![
+            ("double".to_string(), Version::TWO),
+            ("src".to_string(), src.version()),
+        ]
+    );
+    assert_eq!(Version::TWO.to_string(), "v2");
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_zip2() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut a: RawVec<usize, _> = RawVec::forced_import(&database, "a", Version::ONE)?;
+    [1_u32, 2, 3, 4].iter().for_each(|v| a.push(*v));
+    a.flush()?;
+    let mut b: RawVec<usize, _> = RawVec::forced_import(&database, "b", Version::ONE)?;
+    [10_u64, 20, 30, 40].iter().for_each(|v| b.push(*v));
+    b.flush()?;
+
+    let mut vec: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "zip", Version::ONE)?;
+    vec.compute_zip2(0, &a, &b, |i, a, b| i as u64 + a as u64 * b, &exit)?;
+    assert_eq!(vec.collect(), vec![10, 41, 92, 163]);
+
+    // Recomputing from an index only rewrites the tail
+    vec.compute_zip2(2, &a, &b, |_, a, b| a as u64 + b, &exit)?;
+    assert_eq!(vec.collect(), vec![10, 41, 33, 44]);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_std_dev() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [2.0_f32, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]
+        .iter()
+        .for_each(|v| src.push(*v));
+    src.flush()?;
+    let expected = [
+        0.0, 1.0, 0.942809, 0.0, 0.471405, 0.471405, 0.942809, 1.632993,
+    ];
+
+    let mut std_dev: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "std_dev", Version::ONE)?;
+    let check = |std_dev: &EagerVec<usize, f32>| {
+        let std_dev = std_dev.collect();
+        assert_eq!(std_dev.len(), expected.len());
+        std_dev.iter().zip(&expected).for_each(|(a, b)| {
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        });
+    };
+
+    std_dev.compute_std_dev(0, &src, 3, &exit)?;
+    check(&std_dev);
+
+    std_dev.truncate_if_needed_at(5)?;
+    assert_eq!(std_dev.len(), 5);
+    std_dev.compute_std_dev(5, &src, 3, &exit)?;
+    check(&std_dev);
+    // Over 4.0, 5.0 and 5.0, the first two from before the resume point
+    assert!((std_dev.read_at_once(5)? - 0.471405).abs() < 1e-5);
+
+    let mut with_nan: RawVec<usize, _> =
+        RawVec::forced_import(&database, "with_nan", Version::ONE)?;
+    [1.0_f32, 3.0, f32::NAN, 3.0, 5.0, 7.0]
+        .iter()
+        .for_each(|v| with_nan.push(*v));
+    with_nan.flush()?;
+    let mut std_dev: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "std_dev_nan", Version::ONE)?;
+    std_dev.compute_std_dev(0, &with_nan, 2, &exit)?;
+    let std_dev = std_dev.collect();
+    assert_eq!(&std_dev[..2], [0.0, 1.0]);
+    assert!(std_dev[2].is_nan() && std_dev[3].is_nan());
+    assert_eq!(&std_dev[4..], [1.0, 1.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_median() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut state = 42_u32;
+    let values = (0..200)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 24) as u16
+        })
+        .collect::<Vec<_>>();
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    values.iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    for window in [1, 4, 7] {
+        let expected = (0..values.len())
+            .map(|i| {
+                let mut w = values[(i + 1).saturating_sub(window)..=i].to_vec();
+                w.sort_unstable();
+                let mid = w.len() / 2;
+                if w.len().is_multiple_of(2) {
+                    (w[mid - 1] as f32 + w[mid] as f32) / 2.0
+                } else {
+                    w[mid] as f32
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut median: EagerVec<usize, f32> =
+            EagerVec::forced_import_raw(&database, &format!("median_{window}"), Version::ONE)?;
+        median.compute_median(0, &src, window, &exit)?;
+        assert_eq!(median.collect(), expected);
+
+        median.truncate_if_needed_at(123)?;
+        assert_eq!(median.len(), 123);
+        median.compute_median(123, &src, window, &exit)?;
+        assert_eq!(median.collect(), expected);
+        assert_eq!(median.stored_len(), values.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_wma() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [6.0_f32, 3.0, 12.0, 0.0, 6.0, 9.0]
+        .iter()
+        .for_each(|v| src.push(*v));
+    src.flush()?;
+    let expected = [
+        // Partial windows weigh (1) then (1, 2)
+        6.0,
+        (6.0 + 2.0 * 3.0) / 3.0,
+        // Full windows weigh (1, 2, 3) / 6
+        (6.0 + 2.0 * 3.0 + 3.0 * 12.0) / 6.0,
+        (3.0 + 2.0 * 12.0 + 3.0 * 0.0) / 6.0,
+        (12.0 + 2.0 * 0.0 + 3.0 * 6.0) / 6.0,
+        (0.0 + 2.0 * 6.0 + 3.0 * 9.0) / 6.0,
+    ];
+
+    let mut wma: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "wma", Version::ONE)?;
+    let check = |wma: &EagerVec<usize, f32>| {
+        let wma = wma.collect();
+        assert_eq!(wma.len(), expected.len());
+        wma.iter().zip(&expected).for_each(|(a, b)| {
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        });
+    };
+
+    wma.compute_wma(0, &src, 3, &exit)?;
+    check(&wma);
+
+    wma.truncate_if_needed_at(4)?;
+    wma.compute_wma(4, &src, 3, &exit)?;
+    check(&wma);
+    // 12.0 and 0.0 weigh 1 and 2 at index 4 although they're before the resume point
+    assert_eq!(wma.read_at_once(4)?, 5.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_percentile_rank() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [5_u32, 3, 8, 8, 1, 9, 4].iter().for_each(|v| src.push(*v));
+    src.flush()?;
+    let expected = vec![
+        100.0, 50.0, 100.0, // The tie counts as lower or equal
+        100.0, 25.0, 100.0, 50.0,
+    ];
+
+    let mut rank: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "rank", Version::ONE)?;
+    rank.compute_percentile_rank(0, &src, 4, &exit)?;
+    assert_eq!(rank.collect(), expected);
+
+    rank.truncate_if_needed_at(5)?;
+    rank.compute_percentile_rank(5, &src, 4, &exit)?;
+    assert_eq!(rank.collect(), expected);
+    // 4 is above 1 but below 8 and 9, all three from before the resume point
+    assert_eq!(rank.read_at_once(6)?, 50.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_rolling_zscore() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+    let window = 4;
+
+    let values = [3.0_f32, 7.0, 1.0, 9.0, 4.0, 4.5, 12.0, 2.0, 8.0, 5.0];
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    values.iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    // compute_sma needs a value type dividable by usize, so both inputs are built by hand
+    let (smas, sds): (Vec<f32>, Vec<f32>) = (0..values.len())
+        .map(|i| {
+            let w = &values[(i + 1).saturating_sub(window)..=i];
+            let mean = w.iter().sum::<f32>() / w.len() as f32;
+            let sd = (w.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / w.len() as f32).sqrt();
+            (mean, sd)
+        })
+        .unzip();
+    let mut sma: RawVec<usize, _> = RawVec::forced_import(&database, "sma", Version::ONE)?;
+    smas.iter().for_each(|v| sma.push(*v));
+    sma.flush()?;
+    let mut sd: RawVec<usize, _> = RawVec::forced_import(&database, "sd", Version::ONE)?;
+    sds.iter().for_each(|v| sd.push(*v));
+    sd.flush()?;
+
+    let mut decomposed: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "decomposed", Version::ONE)?;
+    decomposed.compute_zscore(0, &src, &sma, &sd, &exit)?;
+
+    let mut fused: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "fused", Version::ONE)?;
+    fused.compute_rolling_zscore(0, &src, window, &exit)?;
+
+    let expected = decomposed.collect();
+    let check = |fused: &EagerVec<usize, f32>| {
+        let fused = fused.collect();
+        assert_eq!(fused.len(), expected.len());
+        // Single value window has no deviation
+        assert!(fused[0].is_nan());
+        fused.iter().zip(&expected).skip(1).for_each(|(a, b)| {
+            assert!((a - b).abs() < 1e-4, "{a} != {b}");
+        });
+    };
+    check(&fused);
+
+    fused.truncate_if_needed_at(6)?;
+    fused.compute_rolling_zscore(6, &src, window, &exit)?;
+    check(&fused);
+    // 12.0 against 9.0, 4.0 and 4.5, which were computed before the resume point
+    assert!((fused.read_at_once(6)? - 1.39947).abs() < 1e-4);
+
+    let mut flat: RawVec<usize, _> = RawVec::forced_import(&database, "flat", Version::ONE)?;
+    [2.0_f32; 5].iter().for_each(|v| flat.push(*v));
+    flat.flush()?;
+    fused.compute_rolling_zscore(0, &flat, window, &exit)?;
+    assert!(fused.collect().iter().all(|v| v.is_nan()));
+
+    Ok(())
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+)]
+#[repr(transparent)]
+struct Height(usize);
+
+impl From<usize> for Height {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Height> for usize {
+    fn from(value: Height) -> Self {
+        value.0
+    }
+}
+
+impl std::ops::Add<usize> for Height {
+    type Output = Self;
+    fn add(self, rhs: usize) -> Self {
+        Self(self.0 + rhs)
+    }
+}
+
+impl vecdb::PrintableIndex for Height {
+    fn to_string() -> &'static str {
+        "height"
+    }
+
+    fn to_possible_strings() -> &'static [&'static str] {
+        &["height"]
+    }
+}
+
+#[test]
+fn test_lazy_index_type_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [1_u32, 2, 3].iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    let res: vecdb::Result<LazyVecFrom1<Height, u32, usize, u32>> =
+        LazyVecFrom1::try_init("wrong", Version::ONE, src.boxed_clone(), |i, iter| {
+            iter.get(i.into())
+        });
+    assert!(matches!(
+        res,
+        Err(vecdb::Error::IndexTypeMismatch {
+            expected: "height",
+            found: "usize"
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_product() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [2_u64, 3, 0, 4, 5, 1, 2].iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    let mut vec: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "product", Version::ONE)?;
+    vec.compute_product(0, &src, 3, &exit)?;
+
+    // The zero leaves the window at index 5, forcing a full recompute
+    assert_eq!(vec.collect(), vec![2, 6, 0, 0, 0, 20, 10]);
+
+    // Resuming right after the zero left still picks up the right window
+    vec.truncate_if_needed_at(5)?;
+    vec.compute_product(5, &src, 3, &exit)?;
+    assert_eq!(vec.collect(), vec![2, 6, 0, 0, 0, 20, 10]);
+
+    // Growth factors, where the running product divides out the leaving factor
+    let factors = [1.01_f32, 0.98, 1.05, 1.2, 0.9, 1.001, 0.75, 1.1];
+    let mut growth: RawVec<usize, _> = RawVec::forced_import(&database, "growth", Version::ONE)?;
+    factors.iter().for_each(|v| growth.push(*v));
+    growth.flush()?;
+    let mut returns: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "returns", Version::ONE)?;
+    returns.compute_product(0, &growth, 4, &exit)?;
+    let naive = (0..factors.len())
+        .map(|i| {
+            factors[(i + 1).saturating_sub(4)..=i]
+                .iter()
+                .product::<f32>()
+        })
+        .collect::<Vec<_>>();
+    returns
+        .collect()
+        .iter()
+        .zip(&naive)
+        .for_each(|(a, b)| assert!((a - b).abs() < 1e-5, "{a} != {b}"));
+
+    Ok(())
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    serde_derive::Serialize,
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+)]
+#[repr(transparent)]
+struct Count(u64);
+
+impl vecdb::TransparentCompressable<u64> for Count {}
+
+impl vecdb::Compressable for Count {
+    type NumberType = u64;
+}
+
+impl From<usize> for Count {
+    fn from(value: usize) -> Self {
+        Self(value as u64)
+    }
+}
+
+#[test]
+fn test_compute_count_if() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+    let window = 10;
+    let threshold = 50;
+
+    let values = (0..100_u32)
+        .map(|i| (i * 37 + 11) % 100)
+        .collect::<Vec<_>>();
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    values.iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    let expected = (0..values.len())
+        .map(|i| {
+            values[(i + 1).saturating_sub(window)..=i]
+                .iter()
+                .filter(|v| **v > threshold)
+                .count()
+                .into()
+        })
+        .collect::<Vec<Count>>();
+
+    let mut vec: EagerVec<usize, Count> =
+        EagerVec::forced_import_raw(&database, "count", Version::ONE)?;
+    vec.compute_count_if(0, &src, window, |v| *v > threshold, &exit)?;
+    assert_eq!(vec.collect(), expected);
+
+    vec.truncate_if_needed_at(42)?;
+    vec.compute_count_if(42, &src, window, |v| *v > threshold, &exit)?;
+    assert_eq!(vec.collect(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_interrupted_by_exit() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let len = vecdb::EXIT_CHECK_INTERVAL * 3;
+    let values = (0..len as u32).collect::<Vec<_>>();
+    let mut source: RawVec<usize, _> = RawVec::forced_import(&database, "source", Version::ONE)?;
+    values.iter().for_each(|v| source.push(*v));
+    source.flush()?;
+
+    let mut vec: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "doubled", Version::ONE)?;
+    let trigger_at = vecdb::EXIT_CHECK_INTERVAL + 10;
+    let res = vec.compute_transform(
+        0,
+        &source,
+        |(i, v, _)| {
+            if i == trigger_at {
+                exit.trigger();
+            }
+            (i, v * 2)
+        },
+        &exit,
+    );
+    assert!(matches!(res, Err(vecdb::Error::Interrupted)));
+
+    // Everything up to the next checkpoint was flushed
+    let flushed = vecdb::EXIT_CHECK_INTERVAL * 2;
+    assert_eq!(vec.stored_len(), flushed);
+    assert!(vec.pushed().is_empty());
+    drop(vec);
+
+    let mut vec: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "doubled", Version::ONE)?;
+    assert_eq!(
+        vec.collect(),
+        values[..flushed].iter().map(|v| v * 2).collect::<Vec<_>>()
+    );
+
+    // Resumes where it stopped with a fresh exit
+    let exit = Exit::new();
+    vec.compute_transform(0, &source, |(i, v, _)| (i, v * 2), &exit)?;
+    assert_eq!(
+        vec.collect(),
+        values.iter().map(|v| v * 2).collect::<Vec<_>>()
+    );
+
+    // An already triggered exit stops before the first value
+    let mut indexes: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "indexes", Version::ONE)?;
+    exit.trigger();
+    assert!(matches!(
+        indexes.compute_to(0, 10, Version::ZERO, |i| (i, i as u32), &exit),
+        Err(vecdb::Error::Interrupted)
+    ));
+    assert_eq!(indexes.len(), 0);
+
+    drop(temp);
+    Ok(())
+}
+
+#[test]
+fn test_shutdown_waits_for_checkpoint() -> Result<(), Box<dyn std::error::Error>> {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
+        thread,
+    };
+
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut vec: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "indexes", Version::ONE)?;
+    let computed = Arc::new(AtomicUsize::new(0));
+    let (started, wait_started) = mpsc::channel();
+
+    let compute = thread::spawn({
+        let exit = exit.clone();
+        let computed = computed.clone();
+        move || {
+            let res = vec.compute_to(
+                0,
+                vecdb::EXIT_CHECK_INTERVAL * 3,
+                Version::ZERO,
+                |i| {
+                    if i == 10 {
+                        started.send(()).unwrap();
+                        while !exit.is_triggered() {
+                            thread::yield_now();
+                        }
+                    }
+                    computed.fetch_add(1, Ordering::Relaxed);
+                    (i, i as u32)
+                },
+                &exit,
+            );
+            (vec, res)
+        }
+    });
+
+    wait_started.recv()?;
+    // Same path as the Ctrl-C handler, minus the process exit
+    let lock = exit.shutdown();
+    // Returns only once the computation stopped at its checkpoint, after flushing
+    assert_eq!(computed.load(Ordering::Relaxed), vecdb::EXIT_CHECK_INTERVAL);
+    drop(lock);
+
+    let (vec, res) = compute.join().unwrap();
+    assert!(matches!(res, Err(vecdb::Error::Interrupted)));
+    assert_eq!(vec.stored_len(), vecdb::EXIT_CHECK_INTERVAL);
+    assert!(vec.pushed().is_empty());
+    assert_eq!(vec.collect()[..3], [0, 1, 2]);
+
+    Ok(())
+}
+
+impl std::ops::Add for Count {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl vecdb::CheckedSub for Count {
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+}
+
+impl From<u32> for Count {
+    fn from(value: u32) -> Self {
+        Self(value as u64)
+    }
+}
+
+#[test]
+fn test_compute_cumulative() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let values = (0..1000_u32)
+        .map(|i| (i * 37 + 11) % 100)
+        .collect::<Vec<_>>();
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    values.iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    let mut sum: EagerVec<usize, Count> =
+        EagerVec::forced_import_raw(&database, "sum", Version::ONE)?;
+    sum.compute_sum(0, &src, usize::MAX, &exit)?;
+
+    let mut cumulative: EagerVec<usize, Count> =
+        EagerVec::forced_import_compressed(&database, "cumulative", Version::ONE)?;
+    cumulative.compute_cumulative(0, &src, &exit)?;
+    assert_eq!(cumulative.collect(), sum.collect());
+    assert_eq!(
+        cumulative.collect().last(),
+        Some(&Count(values.iter().map(|v| *v as u64).sum()))
+    );
+
+    // Resumes from the last kept value
+    for from in [1, 500, 999] {
+        cumulative.truncate_if_needed_at(from)?;
+        cumulative.compute_cumulative(from, &src, &exit)?;
+        assert_eq!(cumulative.collect(), sum.collect());
+
+        sum.truncate_if_needed_at(from)?;
+        sum.compute_sum(from, &src, usize::MAX, &exit)?;
+        assert_eq!(cumulative.collect(), sum.collect());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_subtract_of_others() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let to_count = |values: &[u64]| values.iter().map(|v| Count(*v)).collect::<Vec<_>>();
+    let mut a: RawVec<usize, _> = RawVec::forced_import(&database, "a", Version::ONE)?;
+    to_count(&[100, 50, 30, 10, 7])
+        .iter()
+        .for_each(|v| a.push(*v));
+    a.flush()?;
+    let mut b: RawVec<usize, _> = RawVec::forced_import(&database, "b", Version::ONE)?;
+    to_count(&[10, 20, 5, 0, 3]).iter().for_each(|v| b.push(*v));
+    b.flush()?;
+    let mut c: RawVec<usize, _> = RawVec::forced_import(&database, "c", Version::ONE)?;
+    to_count(&[1, 10, 5, 2, 1]).iter().for_each(|v| c.push(*v));
+    c.flush()?;
+
+    let mut vec: EagerVec<usize, Count> =
+        EagerVec::forced_import_raw(&database, "diff", Version::ONE)?;
+    vec.compute_subtract_of_others(0, &[&a, &b, &c], &exit)?;
+    assert_eq!(vec.collect(), to_count(&[89, 20, 20, 8, 3]));
+
+    vec.truncate_if_needed_at(2)?;
+    vec.compute_subtract_of_others(2, &[&a, &b, &c], &exit)?;
+    assert_eq!(vec.collect(), to_count(&[89, 20, 20, 8, 3]));
+
+    // A single vec is copied as is
+    let mut single: EagerVec<usize, Count> =
+        EagerVec::forced_import_raw(&database, "single", Version::ONE)?;
+    single.compute_subtract_of_others(0, &[&a], &exit)?;
+    assert_eq!(single.collect(), to_count(&[100, 50, 30, 10, 7]));
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_clamp() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    [0_u32, 50, 100, 40, 5, 95, 60, 20]
+        .iter()
+        .for_each(|v| src.push(*v));
+    src.flush()?;
+    let mut lower: RawVec<usize, _> = RawVec::forced_import(&database, "lower", Version::ONE)?;
+    [10_u32, 10, 20, 20, 20, 30, 70, 30]
+        .iter()
+        .for_each(|v| lower.push(*v));
+    lower.flush()?;
+    let mut upper: RawVec<usize, _> = RawVec::forced_import(&database, "upper", Version::ONE)?;
+    [90_u32, 90, 80, 80, 80, 70, 60, 25]
+        .iter()
+        .for_each(|v| upper.push(*v));
+    upper.flush()?;
+
+    let mut vec: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "clamped", Version::ONE)?;
+    vec.compute_clamp(0, &src, &lower, &upper, &exit)?;
+    // Bands cross at the last two indexes, where the lower one wins
+    let expected = vec![10, 50, 80, 40, 20, 70, 70, 30];
+    assert_eq!(vec.collect(), expected);
+
+    vec.truncate_if_needed_at(3)?;
+    vec.compute_clamp(3, &src, &lower, &upper, &exit)?;
+    assert_eq!(vec.collect(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_shift() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let values: Vec<u32> = (1..=6).collect();
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    values.iter().for_each(|v| src.push(*v));
+    src.flush()?;
+
+    for (offset, expected) in [
+        (-2, vec![0, 0, 1, 2, 3, 4]),
+        (0, vec![1, 2, 3, 4, 5, 6]),
+        (2, vec![3, 4, 5, 6, 0, 0]),
+    ] {
+        let mut vec: EagerVec<usize, u32> =
+            EagerVec::forced_import_raw(&database, &format!("shift_{offset}"), Version::ONE)?;
+        vec.compute_shift(0, &src, offset, 0, &exit)?;
+        assert_eq!(vec.collect(), expected, "offset {offset}");
+
+        vec.truncate_if_needed_at(3)?;
+        vec.compute_shift(3, &src, offset, 0, &exit)?;
+        assert_eq!(vec.collect(), expected, "offset {offset} resumed");
+    }
+
+    // Leads refill their tail once the source has grown
+    let mut lead: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "shift_2", Version::ONE)?;
+    [7, 8].into_iter().for_each(|v| src.push(v));
+    src.flush()?;
+    lead.compute_shift(lead.len(), &src, 2, 0, &exit)?;
+    assert_eq!(lead.collect(), vec![3, 4, 5, 6, 7, 8, 0, 0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_resample_from_indexes() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let values = [5_u32, 3, 8, 2, 6, 9, 4, 4, 7];
+    let mut src: RawVec<usize, _> = RawVec::forced_import(&database, "src", Version::ONE)?;
+    values.iter().for_each(|v| src.push(*v));
+    src.flush()?;
+    let mut first_indexes: RawVec<usize, _> =
+        RawVec::forced_import(&database, "first_indexes", Version::ONE)?;
+    [0_usize, 2, 5, 6]
+        .iter()
+        .for_each(|v| first_indexes.push(*v));
+    first_indexes.flush()?;
+
+    let mut min: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "min", Version::ONE)?;
+    min.compute_resample_from_indexes(
+        0,
+        &first_indexes,
+        &src,
+        |v| v.iter().copied().min().unwrap(),
+        &exit,
+    )?;
+    assert_eq!(min.collect(), vec![3, 2, 9, 4]);
+
+    let mean = |v: &[u32]| v.iter().sum::<u32>() / v.len() as u32;
+    let mut means: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "mean", Version::ONE)?;
+    means.compute_resample_from_indexes(0, &first_indexes, &src, mean, &exit)?;
+    assert_eq!(means.collect(), vec![4, 5, 9, 5]);
+
+    means.truncate_if_needed_at(2)?;
+    means.compute_resample_from_indexes(2, &first_indexes, &src, mean, &exit)?;
+    assert_eq!(means.collect(), vec![4, 5, 9, 5]);
+
+    Ok(())
+}
+
+#[test]
+fn test_map_into() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut values: StoredVec<usize, u32> =
+        StoredVec::forced_import(&database, "values", Version::ONE, Format::Compressed)?;
+    (0..10).for_each(|v| values.push(v));
+    values.flush()?;
+
+    let squares = values.map_into(
+        &database,
+        "squares",
+        Version::ONE,
+        Format::Raw,
+        |v| v as u64 * v as u64,
+        &exit,
+    )?;
+    assert_eq!(
+        squares.collect(),
+        (0..10_u64).map(|v| v * v).collect::<Vec<_>>()
+    );
+    drop(squares);
+
+    // Resumes once the source grows, and reads back from disk
+    values.push(10);
+    values.flush()?;
+    values.map_into(
+        &database,
+        "squares",
+        Version::ONE,
+        Format::Raw,
+        |v| v as u64 * v as u64,
+        &exit,
+    )?;
+    let squares: EagerVec<usize, u64> =
+        EagerVec::forced_import_raw(&database, "squares", Version::ONE)?;
+    assert_eq!(squares.len(), 11);
+    assert_eq!(squares.read_at_once(10)?, 100);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_correlation() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+    let window = 3;
+
+    let xs = [1.0_f32, 2.0, 3.0, 5.0, 4.0, 4.0, 4.0, 1.0];
+    let ys = [2.0_f32, 4.0, 7.0, 1.0, 3.0, 6.0, 6.0, 9.0];
+    let mut a: RawVec<usize, _> = RawVec::forced_import(&database, "a", Version::ONE)?;
+    xs.iter().for_each(|v| a.push(*v));
+    a.flush()?;
+    let mut b: RawVec<usize, _> = RawVec::forced_import(&database, "b", Version::ONE)?;
+    ys.iter().for_each(|v| b.push(*v));
+    b.flush()?;
+
+    let expected = (0..xs.len())
+        .map(|i| {
+            let from = (i + 1).saturating_sub(window);
+            let (x, y) = (&xs[from..=i], &ys[from..=i]);
+            let n = x.len() as f32;
+            let (mx, my) = (x.iter().sum::<f32>() / n, y.iter().sum::<f32>() / n);
+            let cov = x
+                .iter()
+                .zip(y)
+                .map(|(x, y)| (x - mx) * (y - my))
+                .sum::<f32>();
+            let vx = x.iter().map(|x| (x - mx).powi(2)).sum::<f32>();
+            let vy = y.iter().map(|y| (y - my).powi(2)).sum::<f32>();
+            cov / (vx * vy).sqrt()
+        })
+        .collect::<Vec<_>>();
+
+    let mut vec: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "correlation", Version::ONE)?;
+    let check = |vec: &EagerVec<usize, f32>| {
+        let correlations = vec.collect();
+        assert_eq!(correlations.len(), xs.len());
+        correlations.iter().zip(&expected).for_each(|(a, b)| {
+            assert!(
+                (a.is_nan() && b.is_nan()) || (a - b).abs() < 1e-4,
+                "{a} != {b}"
+            );
+        });
+    };
+
+    vec.compute_correlation(0, &a, &b, window, &exit)?;
+    check(&vec);
+    // Single value window and a flat x window have no variance
+    assert!(vec.read_at_once(0)?.is_nan());
+    assert!(vec.read_at_once(6)?.is_nan());
+    assert!((vec.read_at_once(1)? - 1.0).abs() < 1e-6);
+
+    vec.truncate_if_needed_at(4)?;
+    vec.compute_correlation(4, &a, &b, window, &exit)?;
+    check(&vec);
+    // Over x = 3, 5, 4 and y = 7, 1, 3, the first two pairs from before the resume point
+    assert!((vec.read_at_once(4)? + 0.981981).abs() < 1e-5);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_beta() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    // Returns in percent
+    let mut asset: RawVec<usize, _> = RawVec::forced_import(&database, "asset", Version::ONE)?;
+    [1.0_f32, 3.0, -2.0, 4.0, 0.0, 2.0, 5.0, -1.0]
+        .iter()
+        .for_each(|v| asset.push(*v));
+    asset.flush()?;
+    let mut market: RawVec<usize, _> = RawVec::forced_import(&database, "market", Version::ONE)?;
+    [2.0_f32, 1.0, -1.0, 3.0, 1.0, 1.0, 1.0, -2.0]
+        .iter()
+        .for_each(|v| market.push(*v));
+    market.flush()?;
+
+    // np.cov(a, m, bias=True)[0][1] / np.var(m) over each trailing window of 3
+    let expected = [f32::NAN, -2.0, 1.214_285_7, 1.5, 1.5, 1.5, f32::NAN, 1.5];
+
+    let mut vec: EagerVec<usize, f32> =
+        EagerVec::forced_import_raw(&database, "beta", Version::ONE)?;
+    let check = |vec: &EagerVec<usize, f32>| {
+        let betas = vec.collect();
+        assert_eq!(betas.len(), expected.len());
+        betas.iter().zip(&expected).for_each(|(a, b)| {
+            assert!(
+                (a.is_nan() && b.is_nan()) || (a - b).abs() < 1e-5,
+                "{a} != {b}"
+            );
+        });
+    };
+
+    vec.compute_beta(0, &asset, &market, 3, &exit)?;
+    check(&vec);
+
+    vec.truncate_if_needed_at(5)?;
+    vec.compute_beta(5, &asset, &market, 3, &exit)?;
+    check(&vec);
+    // Asset 4, 0, 2 against market 3, 1, 1, the first two from before the resume point
+    assert_eq!(vec.read_at_once(5)?, 1.5);
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_transform5_and_n() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut s1: RawVec<usize, _> = RawVec::forced_import(&database, "s1", Version::ONE)?;
+    [1_u32, 2, 3, 4].iter().for_each(|v| s1.push(*v));
+    s1.flush()?;
+    let mut s2: RawVec<usize, _> = RawVec::forced_import(&database, "s2", Version::ONE)?;
+    [10_u32, 20, 30, 40].iter().for_each(|v| s2.push(*v));
+    s2.flush()?;
+    let mut s3: RawVec<usize, _> = RawVec::forced_import(&database, "s3", Version::ONE)?;
+    [100_u32, 200, 300, 400].iter().for_each(|v| s3.push(*v));
+    s3.flush()?;
+    let mut s4: RawVec<usize, _> = RawVec::forced_import(&database, "s4", Version::ONE)?;
+    [0_u32, 1, 0, 1].iter().for_each(|v| s4.push(*v));
+    s4.flush()?;
+    let mut s5: RawVec<usize, _> = RawVec::forced_import(&database, "s5", Version::ONE)?;
+    [5_u32, 5, 5, 5].iter().for_each(|v| s5.push(*v));
+    s5.flush()?;
+    let expected = vec![111, 232, 333, 454];
+
+    let mut fixed: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "fixed", Version::ONE)?;
+    fixed.compute_transform5(
+        0,
+        &s1,
+        &s2,
+        &s3,
+        &s4,
+        &s5,
+        |(i, a, b, c, d, e, ..)| (i, a + b + c + d * 5 + e * (i as u32 % 2)),
+        &exit,
+    )?;
+    assert_eq!(fixed.collect(), expected);
+
+    let mut variadic: EagerVec<usize, u32> =
+        EagerVec::forced_import_raw(&database, "variadic", Version::ONE)?;
+    let weighted = |i: usize, values: &[u32], _: &EagerVec<usize, u32>| {
+        let [a, b, c, d, e] = values else {
+            unreachable!()
+        };
+        (i, a + b + c + d * 5 + e * (i as u32 % 2))
+    };
+    variadic.compute_transform_n(0, &[&s1, &s2, &s3, &s4, &s5], weighted, &exit)?;
+    assert_eq!(variadic.collect(), expected);
+    assert_eq!(variadic.version(), fixed.version());
+
+    variadic.truncate_if_needed_at(2)?;
+    variadic.compute_transform_n(2, &[&s1, &s2, &s3, &s4, &s5], weighted, &exit)?;
+    assert_eq!(variadic.collect(), expected);
+
+    Ok(())
+}
@@ -2,8 +2,9 @@ use rawdb::Database;
 use std::collections::BTreeSet;
 use tempfile::TempDir;
 use vecdb::{
-    AnyStoredVec, AnyVec, CollectableVec, GenericStoredVec, RawVec, Result, Stamp,
-    TypedVecIterator, Version,
+    AnyStoredVec, AnyVec, CollectableVec, Error, Exit, Format, GenericStoredVec, ImportOptions,
+    IterableVec, RawVec, Registry, Result, Stamp, StoredVec, TypedVecIterator, VecIterator,
+    Version,
 };
 
 #[allow(clippy::upper_case_acronyms)]
@@ -585,3 +586,713 @@ fn test_raw_vec_comprehensive() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_registry_flush_all_dirty() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let registry = Registry::new();
+
+    let a = registry.register(VEC::forced_import(&database, "a", Version::ONE)?);
+    let b = registry.register(VEC::forced_import(&database, "b", Version::ONE)?);
+    let c = registry.register(VEC::forced_import(&database, "c", Version::ONE)?);
+    // Not registered, so left alone
+    let mut d = VEC::forced_import(&database, "d", Version::ONE)?;
+
+    a.write().push(1);
+    b.write().push(2);
+    d.push(4);
+
+    assert_eq!(registry.flush_all_dirty()?, 2);
+    assert_eq!(a.read().stored_len(), 1);
+    assert_eq!(b.read().stored_len(), 1);
+    assert_eq!(d.stored_len(), 0);
+    assert_eq!(registry.flush_all_dirty()?, 0);
+
+    c.write().push(3);
+    drop(a);
+    drop(b);
+
+    assert_eq!(registry.len(), 3);
+    assert_eq!(registry.flush_all_dirty()?, 1);
+    assert_eq!(registry.len(), 1);
+    assert_eq!(c.read().stored_len(), 1);
+
+    Ok(())
+}
+
+/// Simulates a vec written on a platform with the opposite endianness
+fn write_foreign_endian(database: &Database, options: ImportOptions) -> Result<()> {
+    let mut vec: VEC = RawVec::forced_import_with(options)?;
+    (0..1000_u32).for_each(|v| vec.push(v));
+    vec.flush()?;
+
+    let mut bytes = vec.region().create_reader().read_all().to_vec();
+    let (header, values) = bytes.split_at_mut(64);
+    header[..32]
+        .chunks_exact_mut(8)
+        .for_each(|field| field.reverse());
+    header[33..37].reverse();
+    values.chunks_exact_mut(4).for_each(|value| value.reverse());
+    database
+        .get_region(&VEC::vec_region_name_with(options.name))
+        .unwrap()
+        .write_all_at(&bytes, 0)?;
+    Ok(())
+}
+
+#[test]
+fn test_migrate_endianness() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let options = (&database, "vec", Version::ONE).into();
+
+    write_foreign_endian(&database, options)?;
+
+    assert!(matches!(
+        VEC::import_with(options),
+        Err(vecdb::Error::WrongEndian)
+    ));
+    assert!(matches!(
+        VEC::forced_import_with(options),
+        Err(vecdb::Error::WrongEndian)
+    ));
+
+    let mut vec = VEC::import_foreign_endian_with(options)?;
+    assert!(vec.header().is_foreign_endian());
+    vec.migrate_endianness(&Exit::new())?;
+    assert!(!vec.header().is_foreign_endian());
+    assert_eq!(vec.collect(), (0..1000).collect::<Vec<_>>());
+    drop(vec);
+
+    let vec: VEC = RawVec::import_with(options)?;
+    assert_eq!(vec.collect(), (0..1000).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn test_stored_vec_compact() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut vec: StoredVec<usize, u32> =
+        StoredVec::forced_import(&database, "vec", Version::ONE, Format::Raw)?;
+    (0..1000_u32).for_each(|v| vec.push(v));
+    vec.flush()?;
+
+    (0..1000).step_by(2).for_each(|i| vec.delete_at(i));
+    vec.flush()?;
+
+    let len_before = vec.region().meta().read().len();
+    assert_eq!(vec.stored_len(), 1000);
+
+    vec.compact(&exit)?;
+
+    let len_after = vec.region().meta().read().len();
+    assert_eq!(len_before - len_after, 500 * size_of::<u32>() as u64);
+    assert_eq!(vec.stored_len(), 500);
+    assert!(vec.holes().is_empty());
+    assert_eq!(vec.collect(), (1..1000).step_by(2).collect::<Vec<_>>());
+
+    let vec: StoredVec<usize, u32> =
+        StoredVec::forced_import(&database, "vec", Version::ONE, Format::Raw)?;
+    assert!(vec.holes().is_empty());
+    assert_eq!(vec.collect(), (1..1000).step_by(2).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn test_type_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let version = Version::TWO;
+
+    let mut vec: RawVec<usize, u32> = RawVec::forced_import(&database, "vec", version)?;
+    vec.push(1);
+    vec.push(2);
+    vec.flush()?;
+    drop(vec);
+
+    let res = RawVec::<usize, u64>::import(&database, "vec", version);
+    assert!(matches!(res, Err(vecdb::Error::TypeMismatch { .. })));
+
+    // Forced import resets instead
+    let vec: RawVec<usize, u64> = RawVec::forced_import(&database, "vec", version)?;
+    assert_eq!(vec.len(), 0);
+    drop(vec);
+
+    let vec: RawVec<usize, u64> = RawVec::import(&database, "vec", version)?;
+    assert_eq!(vec.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_region_misaligned() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let version = Version::TWO;
+
+    let mut vec: RawVec<usize, u32> = RawVec::forced_import(&database, "vec", version)?;
+    vec.push(1);
+    vec.flush()?;
+    let region = vec.region().clone();
+    let len = region.meta().read().len() as usize;
+    drop(vec);
+
+    // Half a value past the last one
+    database.write_all_to_region(&region, &[0; 2])?;
+    drop(region);
+
+    let res = RawVec::<usize, u32>::import(&database, "vec", version);
+    assert!(matches!(
+        res,
+        Err(vecdb::Error::RegionMisaligned { len: found, header })
+            if found == len + 2 && header == len - size_of::<u32>()
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_read_ref_at() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    (0..10_u32).for_each(|v| vec.push(v));
+    vec.flush()?;
+    vec.push(10);
+
+    let reader = vec.create_reader();
+    for i in 0..10 {
+        let value = vec.read_ref_at(i, &reader)?;
+        assert!(matches!(value, std::borrow::Cow::Borrowed(_)));
+        assert_eq!(*value, i as u32);
+    }
+    // Pushed values aren't stored yet
+    assert!(vec.read_ref_at(10, &reader).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_extend_from_slice() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let values = (0..100_000_u32).collect::<Vec<_>>();
+
+    for format in [Format::Raw, Format::Compressed] {
+        let mut vec: StoredVec<usize, u32> =
+            StoredVec::forced_import(&database, &format!("{format:?}"), Version::ONE, format)?;
+        vec.push(7);
+        vec.extend_from_slice(&values);
+        assert_eq!(vec.len(), 100_001);
+
+        vec.flush()?;
+        let collected = vec.collect();
+        assert_eq!(collected[0], 7);
+        assert_eq!(&collected[1..], values);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_as_slice_range() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: RawVec<usize, u64> = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    (0..1_000_u64).for_each(|v| vec.push(v * 3));
+    vec.flush()?;
+    // Pushed values aren't stored yet
+    vec.push(0);
+
+    let reader = vec.create_reader();
+    let slice = vec.as_slice_range(100..350, &reader);
+    assert_eq!(slice.len(), 250);
+    slice.iter().enumerate().for_each(|(i, v)| {
+        assert_eq!(*v, vec.read_at(100 + i, &reader).unwrap());
+    });
+    assert!(vec.as_slice_range(1_000..1_000, &reader).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_get_range() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    for format in [Format::Raw, Format::Compressed] {
+        let mut vec: StoredVec<usize, u32> =
+            StoredVec::forced_import(&database, &format!("{format:?}"), Version::ONE, format)?;
+        (0..20_000_u32).for_each(|v| vec.push(v * 2));
+        vec.flush()?;
+        (20_000..21_000_u32).for_each(|v| vec.push(v * 2));
+        vec.update(10, 1)?;
+
+        let reader = vec.create_reader();
+        for (from, to) in [
+            (0, 100),
+            (4_000, 12_345),
+            (19_990, 20_010),
+            (20_500, 30_000),
+        ] {
+            assert_eq!(
+                vec.get_range(from, to, &reader)?,
+                vec.collect_range(Some(from), Some(to))
+            );
+        }
+        assert_eq!(vec.get_range(9, 12, &reader)?, [18, 1, 22]);
+        assert!(vec.get_range(5, 5, &reader)?.is_empty());
+        assert!(vec.get_range(30_000, 40_000, &reader)?.is_empty());
+        drop(reader);
+    }
+
+    // Holes are skipped
+    let mut vec: RawVec<usize, u32> = RawVec::forced_import(&database, "holed", Version::ONE)?;
+    (0..10_u32).for_each(|v| vec.push(v));
+    vec.flush()?;
+    vec.delete(3);
+    let reader = vec.create_reader();
+    assert_eq!(vec.get_range(2, 5, &reader)?, [2, 4]);
+
+    Ok(())
+}
+
+#[test]
+fn test_collect_range_csv() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    (0..100_u32).for_each(|v| vec.push(v * 10));
+    vec.flush()?;
+
+    let csv = vec.collect_range_csv(Some(20), Some(50))?;
+    assert_eq!(csv.lines().count(), 30);
+    assert!(csv.starts_with("200\n210\n"));
+    assert!(csv.ends_with("490\n"));
+
+    let any: &dyn vecdb::AnyCollectableVec = &vec;
+    assert_eq!(any.collect_range_csv(None, None)?.lines().count(), 100);
+    assert!(any.collect_range_csv(Some(100), None)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_for_each_in_range() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    (0..10_000_u32).for_each(|v| vec.push(v % 97));
+    vec.flush()?;
+    (10_000..10_500_u32).for_each(|v| vec.push(v % 97));
+
+    let mut sum = 0_u64;
+    let mut indexes = vec![];
+    vec.for_each_in_range(Some(1_234), Some(10_321), |i, v| {
+        sum += *v as u64;
+        indexes.push(i);
+    });
+    assert_eq!(sum, (1_234..10_321_u64).map(|v| v % 97).sum::<u64>());
+    assert_eq!(indexes, (1_234..10_321).collect::<Vec<_>>());
+
+    vec.for_each_in_range(Some(20_000), None, |_, _| panic!());
+
+    assert_eq!(
+        vec.collect_range_json_bytes(Some(9_990), Some(10_010)),
+        serde_json::to_vec(&vec.collect_range(Some(9_990), Some(10_010)))?
+    );
+    assert_eq!(vec.collect_range_json_bytes(Some(5), Some(5)), b"[]");
+
+    Ok(())
+}
+
+#[test]
+fn test_version_string_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    for v in [0, 1, 2, 9, 10, 255, u32::MAX as u64, u64::MAX - 1, u64::MAX] {
+        let version = Version::new(v);
+        let s = version.to_string();
+        assert_eq!(s, format!("v{v}"));
+        assert_eq!(s.parse::<Version>()?, version);
+        assert_eq!(Version::try_from(s.as_str())?, version);
+        assert_eq!(v.to_string().parse::<Version>()?, version);
+    }
+
+    assert_eq!((Version::ONE + Version::TWO).to_string(), "v3");
+
+    for s in [
+        "",
+        "v",
+        "vv1",
+        "v-1",
+        "+1",
+        "v1.0",
+        " v1",
+        "V1",
+        "v18446744073709551616",
+    ] {
+        assert!(s.parse::<Version>().is_err(), "{s:?} should not parse");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_binary_search() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+
+    // Even numbers, with the last ones only pushed
+    (0..1000).for_each(|i| vec.push(i * 2));
+    vec.flush()?;
+    (1000..1010).for_each(|i| vec.push(i * 2));
+
+    let reader = vec.create_reader();
+    assert_eq!(vec.binary_search(&0, &reader)?, Ok(0));
+    assert_eq!(vec.binary_search(&500, &reader)?, Ok(250));
+    assert_eq!(vec.binary_search(&1998, &reader)?, Ok(999));
+    assert_eq!(vec.binary_search(&2010, &reader)?, Ok(1005));
+    assert_eq!(vec.binary_search(&501, &reader)?, Err(251));
+    assert_eq!(vec.binary_search(&5000, &reader)?, Err(1010));
+    assert_eq!(vec.binary_search_by(|v| v.cmp(&3), &reader)?, Err(2));
+    drop(reader);
+
+    // Updates are seen, holes aren't supported
+    vec.update(250, 501)?;
+    let reader = vec.create_reader();
+    assert_eq!(vec.binary_search(&501, &reader)?, Ok(250));
+    drop(reader);
+    vec.delete(10);
+    assert!(vec.binary_search(&0, &vec.create_reader()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_flush_coalesces_updates() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let writes = |db: &Database| db.in_place_writes() + db.relocations();
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    vec.extend_from_slice(&(0..300).collect::<Vec<_>>());
+    vec.flush()?;
+
+    for i in 100..200 {
+        vec.update(i, i as u32 * 10)?;
+    }
+    vec.update(250, 7)?;
+
+    let before = writes(&database);
+    vec.flush()?;
+    // One write per run of consecutive indexes instead of one per updated value
+    assert_eq!(writes(&database) - before, 2);
+
+    let vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    let expected = (0..300)
+        .map(|i| match i {
+            100..200 => i * 10,
+            250 => 7,
+            _ => i,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(vec.collect(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_first_last() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    let reader = vec.create_static_reader();
+    assert_eq!(vec.first(&reader)?, None);
+    assert_eq!(vec.last(&reader)?, None);
+    drop(reader);
+
+    // Pushed only
+    vec.extend_from_slice(&[3, 4, 5]);
+    let reader = vec.create_static_reader();
+    assert_eq!(vec.first(&reader)?, Some(3));
+    assert_eq!(vec.last(&reader)?, Some(5));
+    drop(reader);
+
+    // Flushed only
+    vec.flush()?;
+    let reader = vec.create_static_reader();
+    assert_eq!(vec.first(&reader)?, Some(3));
+    assert_eq!(vec.last(&reader)?, Some(5));
+    drop(reader);
+
+    // Both
+    vec.push(6);
+    let reader = vec.create_static_reader();
+    assert_eq!(vec.first(&reader)?, Some(3));
+    assert_eq!(vec.last(&reader)?, Some(6));
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_scattered_holes() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let exit = Exit::new();
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    vec.extend_from_slice(&(0..20).collect::<Vec<_>>());
+    vec.flush()?;
+
+    let holes = [0, 3, 4, 11, 19];
+    holes.iter().for_each(|&i| vec.delete_at(i));
+    vec.flush()?;
+    assert!(database.get_region(&vec.holes_region_name()).is_some());
+
+    vec.compact(&exit)?;
+
+    let expected = (0..20_u32)
+        .filter(|i| !holes.contains(&(*i as usize)))
+        .collect::<Vec<_>>();
+    assert_eq!(vec.stored_len(), expected.len());
+    assert_eq!(vec.collect(), expected);
+    assert!(database.get_region(&vec.holes_region_name()).is_none());
+
+    let vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    assert!(vec.holes().is_empty());
+    assert_eq!(vec.collect(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_iter_rev() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    for format in [Format::Raw, Format::Compressed] {
+        let mut vec: StoredVec<usize, u32> =
+            StoredVec::forced_import(&database, &format!("{format:?}"), Version::ONE, format)?;
+        assert_eq!(vec.iter_rev().next(), None);
+
+        // Spans several compressed pages, with some values only pushed
+        vec.extend_from_slice(&(0..10_000).collect::<Vec<_>>());
+        vec.flush()?;
+        vec.extend_from_slice(&(10_000..10_100).collect::<Vec<_>>());
+
+        let mut forward = vec.iter().enumerate().collect::<Vec<_>>();
+        forward.reverse();
+        assert_eq!(vec.iter_rev().len(), 10_100);
+        assert_eq!(vec.iter_rev().collect::<Vec<_>>(), forward);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_iter_at_range() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    for format in [Format::Raw, Format::Compressed] {
+        let mut vec: StoredVec<usize, u32> =
+            StoredVec::forced_import(&database, &format!("{format:?}"), Version::ONE, format)?;
+        vec.extend_from_slice(&(0..10_000).collect::<Vec<_>>());
+        vec.flush()?;
+        vec.extend_from_slice(&(10_000..10_100).collect::<Vec<_>>());
+
+        for (from, to) in [(0, 10), (5_000, 5_001), (9_990, 10_050), (10_050, 10_100)] {
+            let iter = vec.iter_at_range(from, to);
+            assert_eq!(iter.len(), to - from);
+            assert_eq!(
+                iter.collect::<Vec<_>>(),
+                (from as u32..to as u32).collect::<Vec<_>>()
+            );
+        }
+
+        assert_eq!(vec.iter_at_range(20, 20).next(), None);
+        assert_eq!(vec.iter_at_range(20, 10).next(), None);
+        assert_eq!(vec.iter_at_range(10_090, 20_000).count(), 10);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_iter_peekable() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    vec.extend_from_slice(&(0..10).collect::<Vec<_>>());
+    vec.flush()?;
+    vec.push(10);
+
+    let mut iter = vec.iter_peekable();
+    assert_eq!(iter.peek(), Some(0));
+    assert_eq!(iter.peek(), Some(0));
+    assert_eq!(iter.len(), 11);
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.peek(), Some(1));
+    assert_eq!(iter.position(), 1);
+
+    // Repositioning drops the peeked value
+    iter.set_position_to(5);
+    assert_eq!(iter.peek(), Some(5));
+    assert_eq!(iter.get_at(8), Some(8));
+    assert_eq!(iter.peek(), Some(9));
+    iter.set_end_to(10);
+    assert_eq!(iter.next(), Some(9));
+    assert_eq!(iter.peek(), None);
+    assert_eq!(iter.next(), None);
+
+    // Pushed values can be peeked too
+    let mut iter = vec.iter_peekable();
+    iter.set_position_to(10);
+    assert_eq!(iter.peek(), Some(10));
+    assert_eq!(iter.collect::<Vec<_>>(), vec![10]);
+
+    Ok(())
+}
+
+#[test]
+fn test_rollback_to() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    // Keeps the `prev_*` state serialize_changes relies on up to date across flushes
+    let options =
+        ImportOptions::from((&database, "vec", Version::ONE)).with_saved_stamped_changes(1);
+
+    let mut vec: VEC = RawVec::forced_import_with(options)?;
+    vec.extend_from_slice(&[0, 1, 2, 3, 4]);
+    vec.stamped_flush_with_changes(Stamp::new(1))?;
+
+    vec.extend_from_slice(&[5, 6]);
+    vec.update(1, 10)?;
+    vec.delete(3);
+    let changes = vec.serialize_changes()?;
+    vec.stamped_flush_with_changes(Stamp::new(2))?;
+    assert_eq!(
+        vec.collect_holed()?,
+        vec![Some(0), Some(10), Some(2), None, Some(4), Some(5), Some(6)]
+    );
+
+    assert!(vec.rollback_to(Stamp::new(2), &changes).is_err());
+    vec.rollback_to(Stamp::new(1), &changes)?;
+    assert_eq!(vec.stamp(), Stamp::new(1));
+    assert_eq!(vec.collect(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(vec.prev_stored_len(), 5);
+    assert!(vec.prev_pushed().is_empty());
+    assert!(vec.prev_holes().is_empty());
+
+    // Persists like any other change
+    vec.flush()?;
+    drop(vec);
+    let vec: VEC = RawVec::forced_import_with(options)?;
+    assert_eq!(vec.stamp(), Stamp::new(1));
+    assert_eq!(vec.collect(), vec![0, 1, 2, 3, 4]);
+
+    Ok(())
+}
+
+#[test]
+fn test_changes_since() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let (follower_database, _follower_temp) = setup_test_db()?;
+    let options =
+        ImportOptions::from((&database, "vec", Version::ONE)).with_saved_stamped_changes(10);
+
+    let mut vec: VEC = RawVec::forced_import_with(options)?;
+    let mut follower: VEC = RawVec::forced_import(&follower_database, "vec", Version::ONE)?;
+
+    vec.extend_from_slice(&(0..100).collect::<Vec<_>>());
+    vec.stamped_flush_with_changes(Stamp::new(1))?;
+
+    follower.apply_changes(&vec.changes_since(Stamp::new(0))?)?;
+    follower.flush()?;
+    assert_eq!(follower.stamp(), Stamp::new(1));
+    assert_eq!(follower.collect(), (0..100).collect::<Vec<_>>());
+
+    vec.update(10, 1_000)?;
+    vec.delete(20);
+    vec.stamped_flush_with_changes(Stamp::new(2))?;
+
+    vec.truncate_if_needed(90)?;
+    vec.extend_from_slice(&[7, 8, 9]);
+    vec.update(30, 3_000)?;
+    vec.stamped_flush_with_changes(Stamp::new(3))?;
+
+    // Replicates into the follower in a single delta, which only spans what changed
+    let changes = vec.changes_since(Stamp::new(1))?;
+    assert!(changes.len() < 100 * size_of::<u32>());
+    assert!(
+        follower
+            .apply_changes(&vec.changes_since(Stamp::new(2))?)
+            .is_err()
+    );
+    follower.apply_changes(&changes)?;
+    follower.flush()?;
+
+    let check = |follower: &VEC| -> Result<()> {
+        assert_eq!(follower.stamp(), Stamp::new(3));
+        assert_eq!(follower.collect_holed()?, vec.collect_holed()?);
+        assert_eq!(follower.holes(), &BTreeSet::from([20]));
+        Ok(())
+    };
+    check(&follower)?;
+    drop(follower);
+    check(&RawVec::forced_import(
+        &follower_database,
+        "vec",
+        Version::ONE,
+    )?)?;
+
+    // Nothing changed since the current stamp
+    let mut follower: VEC = RawVec::forced_import(&follower_database, "vec", Version::ONE)?;
+    follower.apply_changes(&vec.changes_since(Stamp::new(3))?)?;
+    check(&follower)?;
+    assert!(vec.changes_since(Stamp::new(4)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_different_version_error() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+
+    let mut vec: VEC = RawVec::forced_import(&database, "vec", Version::ONE)?;
+    vec.push(1);
+    vec.flush()?;
+    drop(vec);
+
+    // The raw format adds its own version on top of the vec's
+    match VEC::import(&database, "vec", Version::TWO) {
+        Err(Error::DifferentVersion {
+            name,
+            found,
+            expected,
+        }) => {
+            assert_eq!(name, "vec");
+            assert_eq!(found, Version::ONE + Version::ONE);
+            assert_eq!(expected, Version::TWO + Version::ONE);
+        }
+        res => panic!("Expected a version mismatch, got {:?}", res.map(|_| ())),
+    }
+
+    let vec: VEC = RawVec::forced_import(&database, "vec", Version::TWO)?;
+    assert_eq!(vec.len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_forced_import_migrates_endianness() -> Result<(), Box<dyn std::error::Error>> {
+    let (database, _temp) = setup_test_db()?;
+    let options = (&database, "vec", Version::ONE).into();
+
+    write_foreign_endian(&database, options)?;
+
+    // Migrated rather than reset
+    let vec: StoredVec<usize, u32> = StoredVec::forced_import_with(options, Format::Raw)?;
+    assert!(!vec.header().is_foreign_endian());
+    assert_eq!(vec.collect(), (0..1000).collect::<Vec<_>>());
+    drop(vec);
+
+    let vec: VEC = RawVec::import_with(options)?;
+    assert_eq!(vec.collect(), (0..1000).collect::<Vec<_>>());
+
+    Ok(())
+}
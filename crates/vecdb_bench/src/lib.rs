@@ -228,6 +228,11 @@ pub fn run(configs: &[BenchConfig]) -> Result<()> {
                     db_benchmarks
                         .push(Box::new(DbBenchmark::<VecDbCompressedBench>::new(&runner)?));
                 }
+                Database::VecDbCompressedPrefetch => {
+                    db_benchmarks.push(Box::new(
+                        DbBenchmark::<VecDbCompressedPrefetchBench>::new(&runner)?,
+                    ));
+                }
                 Database::VecDbRaw => {
                     db_benchmarks.push(Box::new(DbBenchmark::<VecDbRawBench>::new(&runner)?));
                 }
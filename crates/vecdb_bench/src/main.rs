@@ -21,6 +21,7 @@ fn main() {
                 // Database::VecDbRawOld,
                 Database::VecDbRaw,
                 Database::VecDbCompressed,
+                Database::VecDbCompressedPrefetch,
             ],
             ..Default::default()
         },
@@ -13,6 +13,7 @@ pub const NUM_ITERATIONS: usize = 10;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Database {
     VecDbCompressed,
+    VecDbCompressedPrefetch,
     VecDbRaw,
     // VecDbRawOld,
     Fjall2,
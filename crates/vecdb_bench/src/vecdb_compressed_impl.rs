@@ -5,14 +5,21 @@ use vecdb::{AnyStoredVec, CompressedVec, Database, GenericStoredVec, Version};
 
 use crate::database::DatabaseBenchmark;
 
-pub struct VecDbCompressedBench {
+/// Compressed vec benchmark, clean iterators prefetching `PREFETCH_PAGES` pages ahead.
+pub struct VecDbCompressedBench<const PREFETCH_PAGES: usize = 0> {
     db: Database,
     vec: CompressedVec<usize, u64>,
 }
 
-impl DatabaseBenchmark for VecDbCompressedBench {
+pub type VecDbCompressedPrefetchBench = VecDbCompressedBench<8>;
+
+impl<const PREFETCH_PAGES: usize> DatabaseBenchmark for VecDbCompressedBench<PREFETCH_PAGES> {
     fn name() -> &'static str {
-        "vecdb_compressed"
+        if PREFETCH_PAGES == 0 {
+            "vecdb_compressed"
+        } else {
+            "vecdb_compressed_prefetch"
+        }
     }
 
     fn create(path: &Path) -> Result<Self> {
@@ -25,7 +32,8 @@ impl DatabaseBenchmark for VecDbCompressedBench {
     fn open(path: &Path) -> Result<Self> {
         let db = Database::open(path)?;
         let options = (&db, "bench", Version::TWO).into();
-        let vec: CompressedVec<usize, u64> = CompressedVec::forced_import_with(options)?;
+        let mut vec: CompressedVec<usize, u64> = CompressedVec::forced_import_with(options)?;
+        vec.set_prefetch_pages(PREFETCH_PAGES);
         Ok(Self { db, vec })
     }
 
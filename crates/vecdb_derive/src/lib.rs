@@ -1,27 +1,29 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Data, DataStruct, DeriveInput, Fields, parse_macro_input};
+use syn::{Data, DataStruct, DeriveInput, Fields, Member, Type, parse_macro_input};
 
-#[proc_macro_derive(Compressable)]
+/// Implements `Compressable` by compressing a struct as one of its fields.
+///
+/// Single-field structs use their field. Structs with more fields pick it with
+/// `#[compressable(via = field)]`, a name or a tuple index. The other fields must be zero-sized,
+/// like `PhantomData`, since values are reinterpreted as the field's type.
+#[proc_macro_derive(Compressable, attributes(compressable))]
 pub fn derive_stored_compressed(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let inner_type = match &input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Unnamed(fields),
-            ..
-        }) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
-        _ => {
-            return syn::Error::new_spanned(
-                &input.ident,
-                "Compressable can only be derived for single-field tuple structs",
-            )
-            .to_compile_error()
-            .into();
-        }
+    let inner_type = match inner_type(&input) {
+        Ok(inner_type) => inner_type,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    let layout_check = quote! {
+        const LAYOUT_CHECK: () = assert!(
+            ::core::mem::size_of::<Self>() == ::core::mem::size_of::<#inner_type>(),
+            "Compressable fields other than the compressed one must be zero-sized",
+        );
     };
 
     // Check if we have generic parameters
@@ -34,22 +36,168 @@ pub fn derive_stored_compressed(input: TokenStream) -> TokenStream {
             quote! { where #inner_type: Compressable, }
         };
 
+        // Only checked once instantiated, when `LAYOUT_CHECK` is evaluated
         quote! {
             impl #impl_generics ::vecdb::TransparentCompressable<<#inner_type as Compressable>::NumberType> for #struct_name #ty_generics #where_clause {}
 
             impl #impl_generics Compressable for #struct_name #ty_generics #where_clause {
                 type NumberType = <#inner_type as Compressable>::NumberType;
+
+                #layout_check
             }
         }
     } else {
         quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#struct_name>() == ::core::mem::size_of::<#inner_type>(),
+                "Compressable fields other than the compressed one must be zero-sized",
+            );
+
             impl ::vecdb::TransparentCompressable<<#inner_type as Compressable>::NumberType> for #struct_name {}
 
             impl Compressable for #struct_name {
                 type NumberType = <#inner_type as Compressable>::NumberType;
+
+                #layout_check
             }
         }
     };
 
     TokenStream::from(expanded)
 }
+
+/// Serializes a `#[repr(transparent)]` newtype as its field, completing the `VecValue` bounds.
+///
+/// zerocopy only lets its own derives implement its traits, so `FromBytes`, `IntoBytes`,
+/// `Immutable` and `KnownLayout` still have to be derived next to this one. With
+/// `repr(transparent)` they give the newtype the same bytes, and size, as its field.
+#[proc_macro_derive(VecValue)]
+pub fn derive_vec_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let is_transparent = input.attrs.iter().any(|attr| {
+        attr.path().is_ident("repr")
+            && attr
+                .parse_nested_meta(|meta| {
+                    if meta.path.is_ident("transparent") {
+                        Ok(())
+                    } else {
+                        Err(meta.error("not transparent"))
+                    }
+                })
+                .is_ok()
+    });
+    if !is_transparent {
+        return syn::Error::new_spanned(
+            &input.ident,
+            "VecValue can only be derived for #[repr(transparent)] structs",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let (member, inner_type) = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) if fields.len() == 1 => {
+            let field = fields.iter().next().unwrap();
+            let member = field
+                .ident
+                .clone()
+                .map_or_else(|| Member::from(0), Member::Named);
+            (member, &field.ty)
+        }
+        _ => {
+            return syn::Error::new_spanned(
+                &input.ident,
+                "VecValue can only be derived for single-field structs",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let size_check = if input.generics.params.is_empty() {
+        quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#struct_name>() == ::core::mem::size_of::<#inner_type>(),
+            );
+        }
+    } else {
+        quote! {}
+    };
+
+    let where_clause = match where_clause {
+        Some(where_clause) => quote! { #where_clause #inner_type: ::vecdb::VecValue, },
+        None => quote! { where #inner_type: ::vecdb::VecValue, },
+    };
+
+    let expanded = quote! {
+        #size_check
+
+        impl #impl_generics ::vecdb::serde::Serialize for #struct_name #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: ::vecdb::serde::Serializer,
+            {
+                ::vecdb::serde::Serialize::serialize(&self.#member, serializer)
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Type of the field selected with `#[compressable(via = field)]`, or of the only field.
+fn inner_type(input: &DeriveInput) -> syn::Result<&Type> {
+    let Data::Struct(DataStruct { fields, .. }) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Compressable can only be derived for structs",
+        ));
+    };
+
+    let mut via = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("compressable"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("via") {
+                via = Some(meta.value()?.parse::<Member>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `via = field`"))
+            }
+        })?;
+    }
+
+    let Some(via) = via else {
+        return match fields {
+            Fields::Named(_) | Fields::Unnamed(_) if fields.len() == 1 => {
+                Ok(&fields.iter().next().unwrap().ty)
+            }
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "Compressable can only be derived for single-field structs, \
+                 select the field to compress with `#[compressable(via = field)]` otherwise",
+            )),
+        };
+    };
+
+    let field = match (&via, fields) {
+        (Member::Named(ident), Fields::Named(fields)) => fields
+            .named
+            .iter()
+            .find(|field| field.ident.as_ref() == Some(ident)),
+        (Member::Unnamed(index), Fields::Unnamed(fields)) => {
+            fields.unnamed.iter().nth(index.index as usize)
+        }
+        _ => None,
+    };
+
+    field
+        .map(|field| &field.ty)
+        .ok_or_else(|| syn::Error::new_spanned(&via, "no such field"))
+}
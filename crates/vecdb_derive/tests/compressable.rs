@@ -0,0 +1,17 @@
+#[test]
+fn test_derive_compressable() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/single_field.rs");
+    t.pass("tests/ui/via_field.rs");
+    t.compile_fail("tests/ui/multi_field_without_via.rs");
+    t.compile_fail("tests/ui/via_field_not_zero_sized.rs");
+    t.pass("tests/ui/generic_via_field.rs");
+    t.compile_fail("tests/ui/generic_via_field_not_zero_sized.rs");
+}
+
+#[test]
+fn test_derive_vec_value() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/vec_value.rs");
+    t.compile_fail("tests/ui/vec_value_not_transparent.rs");
+}
@@ -0,0 +1,28 @@
+use std::marker::PhantomData;
+
+use serde_derive::Serialize;
+use vecdb::{Compressable, FromInnerSlice};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+struct Usd;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(transparent)]
+#[compressable(via = value)]
+struct Amount<T> {
+    value: T,
+    _currency: PhantomData<Usd>,
+}
+
+fn main() {
+    assert_eq!(
+        Amount::<u64>::from_inner_slice(vec![250_u64]),
+        vec![Amount {
+            value: 250,
+            _currency: PhantomData
+        }]
+    );
+}
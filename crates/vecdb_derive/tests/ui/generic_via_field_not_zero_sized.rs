@@ -0,0 +1,18 @@
+use serde_derive::Serialize;
+use vecdb::{Compressable, FromInnerSlice};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+// Packed since zerocopy can't check a generic `repr(C)` struct for padding
+#[derive(
+    Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(C, packed)]
+#[compressable(via = value)]
+struct Tagged<T: Copy> {
+    value: T,
+    tag: u32,
+}
+
+fn main() {
+    Tagged::<u32>::from_inner_slice(vec![1_u32]);
+}
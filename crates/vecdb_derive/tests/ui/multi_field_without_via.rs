@@ -0,0 +1,11 @@
+use vecdb::Compressable;
+
+#[derive(Clone, Copy, Compressable)]
+struct Ohlc {
+    o: f32,
+    h: f32,
+    l: f32,
+    c: f32,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use serde_derive::Serialize;
+use vecdb::{Compressable, FromInnerSlice};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(transparent)]
+struct Height(u32);
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(transparent)]
+struct Dollars {
+    value: f64,
+}
+
+fn main() {
+    assert_eq!(Height::from_inner_slice(vec![1_u32]), vec![Height(1)]);
+    assert_eq!(
+        Dollars::from_inner_slice(vec![1.5_f64]),
+        vec![Dollars { value: 1.5 }]
+    );
+}
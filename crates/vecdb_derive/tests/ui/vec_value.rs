@@ -0,0 +1,25 @@
+use vecdb::{
+    AnyStoredVec, CollectableVec, Database, GenericStoredVec, RawVec, VecValue, Version,
+};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+#[derive(Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout, VecValue)]
+#[repr(transparent)]
+struct Height(u32);
+
+fn main() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db = Database::open(temp_dir.path()).unwrap();
+
+    let mut vec: RawVec<usize, Height> =
+        RawVec::forced_import(&db, "height", Version::ONE).unwrap();
+    (0..4).for_each(|i| vec.push(Height(i * 10)));
+    vec.flush().unwrap();
+
+    let vec: RawVec<usize, Height> = RawVec::forced_import(&db, "height", Version::ONE).unwrap();
+    assert_eq!(
+        vec.collect(),
+        vec![Height(0), Height(10), Height(20), Height(30)]
+    );
+    assert_eq!(vec.collect_range_json_bytes(None, None), b"[0,10,20,30]");
+}
@@ -0,0 +1,6 @@
+use vecdb::VecValue;
+
+#[derive(Debug, Clone, Copy, VecValue)]
+struct Height(u32);
+
+fn main() {}
@@ -0,0 +1,39 @@
+use std::marker::PhantomData;
+
+use serde_derive::Serialize;
+use vecdb::{Compressable, FromInnerSlice};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+struct Usd;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(transparent)]
+#[compressable(via = cents)]
+struct Price {
+    cents: u64,
+    _currency: PhantomData<Usd>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(transparent)]
+#[compressable(via = 1)]
+struct Tagged(PhantomData<Usd>, i32);
+
+fn main() {
+    assert_eq!(
+        Price::from_inner_slice(vec![250_u64]),
+        vec![Price {
+            cents: 250,
+            _currency: PhantomData
+        }]
+    );
+    assert_eq!(
+        Tagged::from_inner_slice(vec![-1_i32]),
+        vec![Tagged(PhantomData, -1)]
+    );
+}
@@ -0,0 +1,17 @@
+use serde_derive::Serialize;
+use vecdb::Compressable;
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+#[derive(
+    Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout, Serialize, Compressable,
+)]
+#[repr(C)]
+#[compressable(via = c)]
+struct Ohlc {
+    o: f32,
+    h: f32,
+    l: f32,
+    c: f32,
+}
+
+fn main() {}